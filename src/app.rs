@@ -1,187 +1,327 @@
 use super::components::Component;
+use crate::components::columns::ColumnLayouts;
+use crate::components::data_table::Density;
+use crate::components::diff::DiffComponent;
+use crate::components::file_tree::FileTreeComponent;
 use crate::components::footer::FooterComponent;
-use crate::components::host::{HostInfoComponent, TableColors, PALETTES};
-use crate::components::proc::ProcInfoComponent;
-use crate::components::query_input::{QueryInputComponents, QueryType};
+use crate::components::host_detail::HostDetailComponent;
+use crate::components::proc_detail::ProcDetailComponent;
+use crate::components::query_input::QueryType;
+use crate::components::tab_pages::{HostTab, ProcTab, StatsTab, TopologyTab};
 use crate::components::syntax_text::SyntaxTextComponent;
-use crate::components::tabs::{SelectedTab, TabComponent};
-use crate::components::total_proc::{TotalProc, TotalProcInfoComponent};
+use crate::components::tabs::{TabComponent, TabInfo, TabRequest};
+use crate::components::tcm_command::TcmCommandComponent;
+use crate::components::topology::TopologyTarget;
 use crate::components::{DrawableComponent, EventState};
-use crate::config;
-use crate::database::{query_hosts_sql, select_all_host};
-use crate::event::Event;
+use crate::config::Config;
+use crate::theme::Theme;
+use crate::database::{select_proc, select_proc_deploys};
+use crate::event::{Event, Events};
 use crate::tools::host::HostInfo;
-use crate::tools::search::search_vec;
-use crate::utils::drop_app;
+use crate::tools::editor::{editor_command, editor_from_env};
+use crate::utils::{drop_app, reimport_data};
 use crate::{
     components::{
-        command::{self, CommandInfo},
-        error::ErrorComponent,
+        command::{self, Action, CommandInfo},
         help::HelpComponent,
+        notify::NotifyComponent,
+        palette::PaletteComponent,
     },
     config::KeyConfig,
     event::Key,
 };
-use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
+use crossterm::event::{
+    DisableMouseCapture, EnableMouseCapture, MouseButton, MouseEvent, MouseEventKind,
+};
 use crossterm::execute;
 use crossterm::terminal::{
     disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
 };
 use ratatui::backend::CrosstermBackend;
-use ratatui::style::Color;
+use ratatui::style::palette::tailwind;
 use ratatui::Terminal;
 use ratatui::{
     layout::{Constraint, Layout, Rect},
     Frame,
 };
+use anyhow::Context;
 use sqlx::SqlitePool;
-use tracing::error;
+use std::collections::BTreeMap;
+use std::io::Stdout;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+const DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(400);
 
+/// 当前标签页, 或者占满界面的文件树, 文件查看和比较
+#[derive(Clone, Copy, PartialEq)]
 pub enum Focus {
-    Host,
-    Filter,
-    Proc,
-    TotalProc,
+    Tab,
     File,
+    FileTree,
+    Diff,
 }
 pub struct App {
     focus: Focus,
     pool: SqlitePool,
     help: HelpComponent,
-    pub error: ErrorComponent,
+    palette: PaletteComponent,
+    pub notify: NotifyComponent,
     pub config: KeyConfig,
-    pub host: HostInfoComponent,
     pub footer: FooterComponent,
-    pub query_input: QueryInputComponents,
-    pub proc: ProcInfoComponent,
-    pub total_proc: TotalProcInfoComponent,
     pub tabs: TabComponent,
     pub file: SyntaxTextComponent,
+    pub file_tree: FileTreeComponent,
+    pub diff: DiffComponent,
+    pub tcm_command: TcmCommandComponent,
+    pub proc_detail: ProcDetailComponent,
+    pub host_detail: HostDetailComponent,
+    theme: Theme,
+    density: Density,
+    /// 重新导入后重建标签页时使用
+    query_type: QueryType,
+    /// 上次单击的时间和行, 用来判断双击
+    last_click: Option<(Instant, u16)>,
+    /// 等待主循环挂起界面后用编辑器打开的文件和行号
+    pending_edit: Option<(PathBuf, usize)>,
+    /// 当前导入的 TCM 配置目录, 切换 profile 时改变
+    config_path: PathBuf,
+    profiles: BTreeMap<String, PathBuf>,
 }
 
 impl App {
-    pub async fn new(config: KeyConfig, db: &SqlitePool) -> anyhow::Result<App> {
-        let proc_com: ProcInfoComponent;
-        let total_com: TotalProcInfoComponent =
-            TotalProcInfoComponent::new(db, config.clone()).await?;
-        let host_com: HostInfoComponent;
-        match ProcInfoComponent::new(db, config.clone()).await {
-            Ok(p) => proc_com = p,
-            Err(e) => {
-                error!("error: {:#?}", e);
-                std::process::exit(1);
-            }
-        }
-        match HostInfoComponent::new(db, config.clone()).await {
-            Ok(h) => host_com = h,
-            Err(e) => {
-                error!("error: {:#?}", e);
-                std::process::exit(1);
-            }
-        }
-        Ok(Self {
-            error: ErrorComponent::new(config.clone()),
+    pub async fn new(
+        user_config: Config,
+        theme: Theme,
+        db: &SqlitePool,
+        config_path: PathBuf,
+    ) -> anyhow::Result<App> {
+        let config = user_config.key_config;
+        let tabs = load_tabs(db, &config, &theme, user_config.default_query_type).await?;
+        let mut app = Self {
+            notify: NotifyComponent::new(config.clone()),
             config: config.clone(),
-            focus: Focus::Host,
+            focus: Focus::Tab,
             help: HelpComponent::new(config.clone()),
+            palette: PaletteComponent::new(config.clone()),
             pool: db.clone(),
-            host: host_com,
-            proc: proc_com,
-            total_proc: total_com,
             footer: FooterComponent {
-                colors: TableColors::new(&PALETTES[0]),
+                colors: theme.table_colors(0),
+                key_config: config.clone(),
+                marked: 0,
+                message: String::new(),
             },
-            query_input: QueryInputComponents::new(config.clone()),
-            tabs: TabComponent::new(config.clone()),
-            file: SyntaxTextComponent::new(config.clone()),
-        })
+            tabs,
+            file: SyntaxTextComponent::new(config.clone(), theme.clone()),
+            file_tree: FileTreeComponent::new(config.clone(), theme.clone()),
+            diff: DiffComponent::new(config.clone(), theme.clone()),
+            tcm_command: TcmCommandComponent::new(config.clone()),
+            proc_detail: ProcDetailComponent::new(config.clone()),
+            host_detail: HostDetailComponent::new(config.clone()),
+            theme,
+            query_type: user_config.default_query_type,
+            last_click: None,
+            pending_edit: None,
+            density: Density::default(),
+            config_path,
+            profiles: user_config.profiles,
+        };
+        app.set_density(user_config.density);
+        if !app.tabs.select(&user_config.default_tab) {
+            let ids: Vec<&str> = app.tabs.pages().map(|info| info.id).collect();
+            app.notify.warn(format!(
+                "没有名为 {} 的标签页, default_tab 可选 {}",
+                user_config.default_tab,
+                ids.join(" | ")
+            ));
+        }
+        Ok(app)
     }
 
     pub fn draw(&mut self, f: &mut Frame) -> anyhow::Result<()> {
-        match self.tabs.selected_tab {
-            SelectedTab::Tab2 => match self.focus {
-                Focus::File => {
-                    self.file.draw(f, f.size(), false)?;
-                    self.error.draw(f, Rect::default(), false)?;
-                    self.help.draw(f, Rect::default(), false)?;
-                }
-                _ => {
-                    let rects = Layout::vertical([
-                        Constraint::Length(4),
-                        Constraint::Length(5),
-                        Constraint::Min(5),
-                        Constraint::Length(3),
-                    ])
-                    .split(f.size());
-                    self.tabs.draw(f, rects[0], false)?;
-                    self.query_input.draw(f, rects[1], false)?;
-                    if self.query_input.history.len() > 0 {
-                        self.total_proc.draw(f, rects[2], false)?;
+        self.footer.marked = match self.focus {
+            Focus::Tab => self.tabs.page().marked_count(),
+            _ => 0,
+        };
+        // 文件树, 文件查看和比较打开时占满整个界面
+        match self.focus {
+            Focus::FileTree => {
+                if self.file.is_visible() {
+                    let [tree_area, file_area] =
+                        Layout::horizontal([Constraint::Percentage(30), Constraint::Percentage(70)])
+                            .areas(f.size());
+                    self.file_tree.draw(f, tree_area, self.focus == Focus::FileTree)?;
+                    if self.diff.is_visible() {
+                        self.diff.draw(f, file_area, false)?;
                     } else {
-                        self.proc.draw(f, rects[2], false)?;
+                        self.file.draw(f, file_area, false)?;
                     }
-                    self.footer.draw(f, rects[3], false)?;
-                    self.error.draw(f, Rect::default(), false)?;
+                } else {
+                    self.file_tree.draw(f, f.size(), true)?;
                 }
-            },
-            SelectedTab::Tab1 => {
-                let rects = Layout::vertical([
-                    Constraint::Length(4),
-                    Constraint::Length(5),
-                    Constraint::Min(5),
-                    Constraint::Length(3),
-                ])
-                .split(f.size());
-
-                self.tabs.draw(f, rects[0], false)?;
-                self.query_input.draw(f, rects[1], false)?;
-                self.host.draw(f, rects[2], false)?;
-                self.footer.draw(f, rects[3], false)?;
-                self.error.draw(f, Rect::default(), false)?;
-                self.help.draw(f, Rect::default(), false)?;
+            }
+            Focus::Diff => self.diff.draw(f, f.size(), false)?,
+            Focus::File => self.file.draw(f, f.size(), false)?,
+            Focus::Tab => {
+                let [tabs_area, page_area, footer_area] =
+                    Layout::vertical([Constraint::Length(4), Constraint::Min(5), Constraint::Length(3)])
+                        .areas(f.size());
+                self.tabs.draw(f, tabs_area, false)?;
+                self.tabs.page().draw(f, page_area)?;
+                self.footer.draw(f, footer_area, false)?;
             }
         }
+        self.host_detail.draw(f, Rect::default(), false)?;
+        self.proc_detail.draw(f, Rect::default(), false)?;
+        self.tcm_command.draw(f, Rect::default(), false)?;
+        self.help.draw(f, Rect::default(), false)?;
+        self.palette.draw(f, Rect::default(), false)?;
+        self.notify.draw(f, Rect::default(), false)?;
 
         Ok(())
     }
 
     fn update_commands(&mut self) {
-        self.help.set_cmds(self.commands());
+        let cmds = self.commands();
+        self.palette.set_cmds(&cmds);
+        self.help.set_cmds(cmds);
     }
 
+    /// 通用命令加上当前焦点和打开的弹窗所属组件的命令
     fn commands(&self) -> Vec<CommandInfo> {
         let mut res = vec![
             CommandInfo::new(command::exit_pop_up(&self.config)),
             CommandInfo::new(command::filter(&self.config)),
-            CommandInfo::new(command::help(&self.config)),
-            CommandInfo::new(command::toggle_tabs(&self.config)),
+            CommandInfo::new(command::help(&self.config))
+                .action("Open help", Action::Keys(vec![self.config.open_help])),
+            self.tabs.pages().fold(CommandInfo::new(command::toggle_tabs(&self.config)), |cmd, info| {
+                cmd.action(format!("Switch tab: {}", info.name), Action::SwitchTab(info.id))
+            }),
             // CommandInfo::new(command::scroll(&self.config)),
             CommandInfo::new(command::scroll_to_top_bottom(&self.config)),
             CommandInfo::new(command::scroll_up_down_multiple_lines(&self.config)),
             CommandInfo::new(command::move_focus(&self.config)),
-            CommandInfo::new(command::extend_or_shorten_widget_width(&self.config)),
+            CommandInfo::new(command::switch_theme(&self.config))
+                .action("Switch theme", Action::Keys(vec![self.config.switch_theme])),
+            CommandInfo::new(command::switch_density(&self.config))
+                .action("Switch row density", Action::Keys(vec![self.config.switch_density])),
+            CommandInfo::new(command::open_editor(&self.config))
+                .action("Open in $EDITOR", Action::Keys(vec![self.config.open_editor])),
+            CommandInfo::new(command::query_type(&self.config))
+                .action("Query type: TCM", Action::QueryType(QueryType::Tcm))
+                .action("Query type: Text", Action::QueryType(QueryType::Text)),
+            CommandInfo::new(command::reload_config(&self.config))
+                .action("Reload TCM config", Action::Reload),
+            self.profiles.keys().fold(CommandInfo::new(command::switch_profile(&self.config)), |cmd, name| {
+                cmd.action(format!("Switch profile: {}", name), Action::SwitchProfile(name.clone()))
+            }),
+            CommandInfo::new(command::command_palette(&self.config)),
+            CommandInfo::new(command::message_log(&self.config))
+                .action("Open message log", Action::Keys(vec![self.config.message_log])),
         ];
 
-        self.host.commands(&mut res);
-        self.help.commands(&mut res);
+        match self.focus {
+            // 弹窗打开时按键都交给弹窗
+            Focus::Tab if self.proc_detail.is_visible() => self.proc_detail.commands(&mut res),
+            Focus::Tab if self.host_detail.is_visible() => self.host_detail.commands(&mut res),
+            Focus::Tab => self.tabs.page().commands(&mut res),
+            Focus::File | Focus::FileTree => {
+                self.file_tree.commands(&mut res);
+                self.file.commands(&mut res);
+            }
+            Focus::Diff => self.diff.commands(&mut res),
+        }
         res
     }
 
     pub async fn event(&mut self, key: Key) -> anyhow::Result<EventState> {
         self.update_commands();
+        self.footer.message.clear();
 
         if self.components_event(key).await?.is_consumed() {
             return Ok(EventState::Consumed);
         };
-        if self.move_focus(key).await?.is_consumed() {
+        if self.move_tab(key).is_consumed() {
             return Ok(EventState::Consumed);
         };
+        if key == self.config.switch_theme {
+            self.set_theme(self.theme.next())?;
+            return Ok(EventState::Consumed);
+        }
+        if key == self.config.switch_density {
+            self.set_density(self.density.next());
+            return Ok(EventState::Consumed);
+        }
+        if key == self.config.message_log {
+            self.notify.show()?;
+            return Ok(EventState::Consumed);
+        }
 
         Ok(EventState::NotConsumed)
     }
 
+    /// 弹窗打开时忽略鼠标, 文件查看只响应滚轮, 其余情况先给标签栏再给当前标签页.
+    /// 在同一行上双击等同于按下 enter
+    pub async fn mouse_event(&mut self, mouse: MouseEvent) -> anyhow::Result<EventState> {
+        let popup_visible = self.notify.is_visible()
+            || self.help.is_visible()
+            || self.palette.is_visible()
+            || self.tcm_command.is_visible()
+            || self.proc_detail.is_visible()
+            || self.host_detail.is_visible();
+        if popup_visible {
+            return Ok(EventState::NotConsumed);
+        }
+        self.footer.message.clear();
+        match self.focus {
+            Focus::File => return self.file.mouse_event(mouse),
+            Focus::FileTree | Focus::Diff => return Ok(EventState::NotConsumed),
+            _ => {}
+        }
+
+        if mouse.kind == MouseEventKind::Down(MouseButton::Left) {
+            if let Some(id) = self.tabs.tab_at(mouse.column, mouse.row) {
+                if id != self.tabs.selected_id() {
+                    self.switch_tab(id);
+                }
+                return Ok(EventState::Consumed);
+            }
+        }
+
+        let state = self.tabs.page_mut().mouse_event(mouse)?;
+        self.tab_request().await?;
+        if !state.is_consumed() || mouse.kind != MouseEventKind::Down(MouseButton::Left) {
+            return Ok(state);
+        }
+        let now = Instant::now();
+        let double_click = self.last_click.is_some_and(|(time, row)| {
+            row == mouse.row && now.duration_since(time) < DOUBLE_CLICK_INTERVAL
+        });
+        self.last_click = Some((now, mouse.row));
+        if double_click {
+            self.last_click = None;
+            return self.event(self.config.enter).await;
+        }
+        Ok(EventState::Consumed)
+    }
+
+    fn set_density(&mut self, density: Density) {
+        self.tabs.set_density(density);
+        self.density = density;
+    }
+
+    fn set_theme(&mut self, theme: Theme) -> anyhow::Result<()> {
+        self.tabs.set_theme(theme.clone());
+        self.footer.colors = theme.table_colors(0);
+        self.file.set_theme(theme.clone())?;
+        self.file_tree.set_theme(theme.clone());
+        self.diff.set_theme(theme.clone());
+        self.theme = theme;
+        Ok(())
+    }
+
     async fn components_event(&mut self, key: Key) -> anyhow::Result<EventState> {
-        if self.error.event(key)?.is_consumed() {
+        if self.notify.event(key)?.is_consumed() {
             return Ok(EventState::Consumed);
         }
 
@@ -189,213 +329,470 @@ impl App {
             return Ok(EventState::Consumed);
         }
 
+        if self.palette.event(key)?.is_consumed() {
+            if let Some(action) = self.palette.take_action() {
+                self.run_action(action).await?;
+            }
+            return Ok(EventState::Consumed);
+        }
+
+        if self.tcm_command.event(key)?.is_consumed() {
+            return Ok(EventState::Consumed);
+        }
+
+        if self.proc_detail.is_visible() {
+            return self.proc_detail_event(key).await;
+        }
+
+        if self.host_detail.is_visible() {
+            return self.host_detail_event(key).await;
+        }
+
         match self.focus {
-            Focus::Host => {
-                let state = self.host.event(key)?;
-                return Ok(state);
+            Focus::Tab => {
+                let state = self.tabs.page_mut().event(key).await?;
+                self.tab_request().await?;
+                Ok(state)
             }
-            Focus::Filter => match self.tabs.selected_tab {
-                SelectedTab::Tab1 => {
-                    let state = self.query_input.event(key)?;
-                    if self.query_input.history.len() > 0 && key == Key::Enter {
-                        let query_ast = self.query_input.history.first().unwrap().clone();
-                        match self.query_input.query_type {
-                            QueryType::Tcm => {
-                                self.select_target_host_by_tcm(query_ast).await?;
-                            }
-                            QueryType::Text => {
-                                let complate_hosts = select_all_host(&self.pool).await?;
-                                self.host.items = search_vec(&complate_hosts, &query_ast);
-                            }
-                        }
-                        return Ok(EventState::Consumed);
-                    }
-                    if key == Key::Up || key == Key::Down {
-                        self.reset_focus_data().await?
-                    }
-                    return Ok(state);
+            Focus::File => {
+                // 文件查看先处理 Esc (关闭搜索框/清除高亮), 没有处理时才关闭文件
+                let state = self.file.event(key)?;
+                if !state.is_consumed() && key == self.config.exit_popup {
+                    self.file.clear();
+                    self.file.hide();
+                    self.focus = if self.file_tree.is_visible() {
+                        Focus::FileTree
+                    } else {
+                        Focus::Tab
+                    };
+                    return Ok(EventState::Consumed);
                 }
-                SelectedTab::Tab2 => {
-                    let state = self.query_input.event(key)?;
-                    if self.query_input.history.len() > 0 && key == Key::Enter {
-                        let query_ast = self.query_input.history.first().unwrap().clone();
-                        match self.query_input.query_type {
-                            QueryType::Tcm => {
-                                let all_proc = self.select_target_proc_by_tcm(&query_ast).await?;
-                                self.total_proc.items = all_proc;
-                            }
-                            QueryType::Text => {
-                                let all_proc = self.select_target_proc_by_tcm("*.*.*.*").await?;
-                                self.total_proc.items = search_vec(&all_proc, &query_ast);
-                            }
-                        }
-                    }
-                    if key == Key::Up || key == Key::Down {
-                        self.reset_focus_data().await?
-                    }
-                    return Ok(state);
+                if !state.is_consumed() && key == self.config.toggle_focus && self.file_tree.is_visible() {
+                    self.focus = Focus::FileTree;
+                    return Ok(EventState::Consumed);
                 }
-            },
-            Focus::Proc => {
-                if let Some(item) = &self.proc.select_item {
-                    match key {
-                        Key::Enter => {
-                            let path = std::path::Path::new(&item.work_path);
-                            let p = path.join(&item.funcname);
-                            self.file.load_file(&p)?;
-                            self.file.show()?;
-                            self.focus = Focus::File;
-                            return Ok(EventState::Consumed);
-                        }
-                        _ => {
-                            let state = self.proc.event(key)?;
-                            return Ok(state);
-                        }
-                    }
+                if !state.is_consumed() && key == self.config.diff_marked {
+                    self.show_diff()?;
+                    return Ok(EventState::Consumed);
                 }
-                let state = self.proc.event(key)?;
-                return Ok(state);
+                if !state.is_consumed() && key == self.config.open_editor {
+                    self.edit_current_file()?;
+                    return Ok(EventState::Consumed);
+                }
+                Ok(state)
             }
-            Focus::TotalProc => {
-                if let Some(item) = &self.total_proc.select_item {
-                    match key {
-                        Key::Enter => {
-                            let path = std::path::Path::new(&item.work_path);
-                            let p = path.join(&item.proc_name);
-                            self.file.load_file(&p)?;
-                            self.file.show()?;
-                            self.focus = Focus::File;
-                            return Ok(EventState::Consumed);
-                        }
-                        _ => {
-                            let state = self.total_proc.event(key)?;
-                            return Ok(state);
-                        }
-                    }
+            Focus::Diff => {
+                let state = self.diff.event(key)?;
+                if !state.is_consumed() && key == self.config.exit_popup {
+                    self.diff.clear();
+                    self.diff.hide();
+                    self.focus = Focus::File;
+                    return Ok(EventState::Consumed);
                 }
-                let state = self.total_proc.event(key)?;
-                return Ok(state);
+                Ok(state)
             }
-            Focus::File => match key {
-                Key::Esc => {
+            Focus::FileTree => {
+                if key == self.config.enter {
+                    if let Some(path) = self.file_tree.enter()? {
+                        self.file.load_file(&path)?;
+                        self.file.show()?;
+                        self.focus = Focus::File;
+                    }
+                    return Ok(EventState::Consumed);
+                }
+                if key == self.config.toggle_focus && self.file.is_visible() {
+                    self.focus = Focus::File;
+                    return Ok(EventState::Consumed);
+                }
+                if key == self.config.follow_file {
+                    if !self.file_tree.is_local() {
+                        return Err(anyhow::anyhow!("进程没有部署在本机 (TcmHost/127.0.0.1), 不能跟踪文件"));
+                    }
+                    if let Some(path) = self.file_tree.selected_file().map(Path::to_path_buf) {
+                        self.file.follow_file(&path)?;
+                        self.file.show()?;
+                        self.focus = Focus::File;
+                    }
+                    return Ok(EventState::Consumed);
+                }
+                if key == self.config.exit_popup {
                     self.file.clear();
                     self.file.hide();
-                    self.focus = Focus::TotalProc;
+                    self.file_tree.hide();
+                    self.focus = Focus::Tab;
                     return Ok(EventState::Consumed);
                 }
-                _ => {
-                    let state = self.file.event(key)?;
-                    return Ok(state);
-                }
-            },
+                self.file_tree.event(key)
+            }
         }
     }
 
-    async fn reset_focus_data(&mut self) -> anyhow::Result<()> {
-        self.host.items = select_all_host(&self.pool).await?;
-        self.query_input.history = vec![];
+    async fn proc_detail_event(&mut self, key: Key) -> anyhow::Result<EventState> {
+        if key == self.config.enter {
+            if let Some(host) = self.proc_detail.selected_host() {
+                self.proc_detail.hide();
+                self.jump_to_host(&host).await?;
+            }
+            return Ok(EventState::Consumed);
+        }
+        if key == self.config.open_config || key == self.config.open_binary {
+            let path = if key == self.config.open_config {
+                self.proc_detail.config_file()?
+            } else {
+                self.proc_detail.binary_file()?
+            };
+            self.file.load_file(&path)?;
+            self.file.show()?;
+            self.proc_detail.hide();
+            self.focus = Focus::File;
+            return Ok(EventState::Consumed);
+        }
+        if key == self.config.browse_files {
+            if let Some(func_id) = self.proc_detail.proc().map(|proc| proc.func_id) {
+                self.proc_detail.hide();
+                self.show_file_tree(func_id).await?;
+            }
+            return Ok(EventState::Consumed);
+        }
+        self.proc_detail.event(key)
+    }
+
+    async fn host_detail_event(&mut self, key: Key) -> anyhow::Result<EventState> {
+        if key == self.config.enter {
+            if let Some(func_id) = self.host_detail.selected_func_id() {
+                self.host_detail.hide();
+                self.show_proc_detail(func_id).await?;
+            }
+            return Ok(EventState::Consumed);
+        }
+        self.host_detail.event(key)
+    }
+
+    /// 在进程标签页打开进程目录的文件树
+    async fn show_file_tree(&mut self, func_id: i32) -> anyhow::Result<()> {
+        let proc = select_proc(&self.pool, func_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("FuncID -> [{}] 不存在", func_id))?;
+        let local = select_proc_deploys(&self.pool, func_id)
+            .await?
+            .iter()
+            .any(|deploy| deploy.host_name == "TcmHost" || deploy.inner_ip == "127.0.0.1");
+        self.file_tree.load(&proc, local)?;
+        self.file_tree.show()?;
+        self.file.clear();
+        self.file.hide();
+        self.focus = Focus::FileTree;
         Ok(())
     }
 
-    async fn move_focus(&mut self, key: Key) -> anyhow::Result<EventState> {
-        match self.tabs.selected_tab {
-            crate::components::tabs::SelectedTab::Tab1 => {
-                if key == Key::Char('2') {
-                    self.query_input.tab = SelectedTab::Tab2;
-                    self.focus = Focus::Proc;
-                    self.query_input.history = vec![];
-                    self.tabs.selected_tab = SelectedTab::Tab2;
-                    return Ok(EventState::Consumed);
-                }
-                match self.focus {
-                    Focus::Filter => {
-                        if key == Key::Tab {
-                            self.query_input.color = Color::Reset;
-                            self.focus = Focus::Host;
-                            return Ok(EventState::Consumed);
-                        }
-                    }
-                    Focus::Host => {
-                        if key == Key::Tab {
-                            self.focus = Focus::Filter;
-                            self.query_input.color = Color::Red;
-                            return Ok(EventState::Consumed);
-                        }
-                    } // _ => return Ok(EventState::Consumed),
-                    _ => {}
+    /// 定时读取跟踪文件新增的内容
+    pub fn tick(&mut self) -> anyhow::Result<()> {
+        self.file.tick()
+    }
+
+    /// 比较标记的文件和文件查看中打开的文件, 标记的文件显示在左边
+    fn show_diff(&mut self) -> anyhow::Result<()> {
+        let (Some(marked), Some(path)) = (self.file.marked(), self.file.path()) else {
+            return Err(anyhow::anyhow!(
+                "没有标记的文件, 先在文件查看中按 [{}] 标记一个文件",
+                self.config.mark
+            ));
+        };
+        self.diff.load(marked, path)?;
+        self.diff.show()?;
+        self.focus = Focus::Diff;
+        Ok(())
+    }
+
+    /// 在编辑器中打开 proc.xml, 跳到进程定义的那一行
+    fn edit_proc_xml(&mut self, func_id: i32) -> anyhow::Result<()> {
+        let path = self.config_path.join("proc.xml");
+        let xml = std::fs::read_to_string(&path)
+            .with_context(|| format!("Read config file -> {} failed", path.display()))?;
+        let needle = format!("FuncID=\"{}\"", func_id);
+        let line = xml.lines().position(|line| line.contains(&needle)).unwrap_or(0);
+        self.pending_edit = Some((path, line + 1));
+        Ok(())
+    }
+
+    fn edit_current_file(&mut self) -> anyhow::Result<()> {
+        if self.file.is_binary() {
+            return Err(anyhow::anyhow!("二进制文件不能用编辑器打开"));
+        }
+        if let Some(path) = self.file.path() {
+            self.pending_edit = Some((path.to_path_buf(), self.file.current_line()));
+        }
+        Ok(())
+    }
+
+    pub fn take_edit(&mut self) -> Option<(PathBuf, usize)> {
+        self.pending_edit.take()
+    }
+
+    /// 编辑器退出后重新读取正在查看的文件, 改的是 TCM 的 XML 配置时重新导入
+    pub async fn edited(&mut self, path: &Path) -> anyhow::Result<()> {
+        if self.file.path() == Some(path) {
+            self.file.reload()?;
+        }
+        let is_tcm_xml = ["host.xml", "proc.xml", "procdeploy.xml"].iter().any(|name| {
+            let xml = self.config_path.join(name);
+            xml == path || xml.canonicalize().is_ok_and(|xml| path.canonicalize().is_ok_and(|path| xml == path))
+        });
+        if is_tcm_xml {
+            self.reimport().await?;
+        }
+        Ok(())
+    }
+
+    /// 重新导入配置后按新数据重建标签页. 导入失败时数据库没有变化, 标签页也不用重建
+    async fn reimport(&mut self) -> anyhow::Result<()> {
+        reimport_data(&self.pool, self.config_path.clone()).await?;
+        self.rebuild_tabs().await?;
+        self.notify.info("已重新导入 TCM 配置".to_string());
+        Ok(())
+    }
+
+    /// 按数据库中的数据重建标签页, 保留选中的标签页, 主题和密度
+    async fn rebuild_tabs(&mut self) -> anyhow::Result<()> {
+        let selected = self.tabs.selected_id();
+        self.tabs = load_tabs(&self.pool, &self.config, &self.theme, self.query_type).await?;
+        self.tabs.select(selected);
+        self.set_density(self.density);
+        Ok(())
+    }
+
+    /// 执行命令面板选中的动作
+    async fn run_action(&mut self, action: Action) -> anyhow::Result<()> {
+        match action {
+            Action::Keys(keys) => {
+                for key in keys {
+                    Box::pin(self.event(key)).await?;
                 }
             }
-            crate::components::tabs::SelectedTab::Tab2 => {
-                if key == Key::Char('1') {
-                    self.query_input.tab = SelectedTab::Tab1;
-                    self.focus = Focus::Host;
-                    self.query_input.history = vec![];
-                    self.tabs.selected_tab = SelectedTab::Tab1;
-                    return Ok(EventState::Consumed);
-                }
-                match self.focus {
-                    Focus::Filter => {
-                        if key == Key::Tab {
-                            if self.query_input.history.len() > 0 {
-                                self.focus = Focus::TotalProc;
-                                self.query_input.color = Color::Reset;
-                                return Ok(EventState::Consumed);
-                            } else {
-                                self.focus = Focus::Proc;
-                                self.query_input.color = Color::Reset;
-                                return Ok(EventState::Consumed);
-                            }
-                        }
-                    }
-                    Focus::TotalProc => {
-                        if key == Key::Tab {
-                            self.focus = Focus::Filter;
-                            self.query_input.color = Color::Red;
-                            return Ok(EventState::Consumed);
-                        }
-                    }
-                    Focus::Proc => {
-                        if key == Key::Tab {
-                            self.focus = Focus::Filter;
-                            self.query_input.color = Color::Red;
-                            return Ok(EventState::Consumed);
-                        }
-                    }
-                    _ => {}
-                }
+            Action::SwitchTab(tab) => self.switch_tab(tab),
+            Action::QueryType(query_type) => self.tabs.page_mut().set_query_type(query_type),
+            Action::Reload => self.reimport().await?,
+            Action::SwitchProfile(name) => self.switch_profile(&name).await?,
+        }
+        Ok(())
+    }
+
+    /// 切换到另一个 TCM 配置目录. 导入在事务里执行, 失败时数据库, 主机表和标签页
+    /// 都没有变化, 继续使用原来的目录
+    async fn switch_profile(&mut self, name: &str) -> anyhow::Result<()> {
+        let path = self
+            .profiles
+            .get(name)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("没有名为 {} 的配置", name))?;
+        reimport_data(&self.pool, path.clone())
+            .await
+            .with_context(|| format!("切换到配置 {} 失败, 继续使用 {}", name, self.config_path.display()))?;
+        // 导入已经提交, 之后的错误也按新目录处理
+        self.config_path = path;
+        self.rebuild_tabs().await?;
+        self.notify.info(format!("已切换到配置 {} ({})", name, self.config_path.display()));
+        Ok(())
+    }
+
+    /// 执行标签页在事件中提出的请求
+    async fn tab_request(&mut self) -> anyhow::Result<()> {
+        let Some(request) = self.tabs.page_mut().take_request() else {
+            return Ok(());
+        };
+        match request {
+            TabRequest::HostDetail(host) => {
+                self.host_detail.load(&self.pool, &host).await?;
+                self.host_detail.show()?;
+            }
+            TabRequest::ProcDetail(func_id, item) => {
+                self.proc_detail.load(&self.pool, func_id, item.as_ref()).await?;
+                self.proc_detail.show()?;
+            }
+            TabRequest::ShowProc(func_id) => self.show_proc_detail(func_id).await?,
+            TabRequest::FileTree(func_id) => self.show_file_tree(func_id).await?,
+            TabRequest::EditProcXml(func_id) => self.edit_proc_xml(func_id)?,
+            TabRequest::TcmCommand(selected, query) => {
+                self.tcm_command.set_targets(selected, Some(query));
+                self.tcm_command.show()?;
+            }
+            TabRequest::Message(message) => self.footer.message = message,
+            TabRequest::Notice(message) => {
+                self.notify.info(message.clone());
+                self.footer.message = message;
             }
         }
-        Ok(EventState::NotConsumed)
+        Ok(())
+    }
+
+    /// 切到进程标签页打开进程详情
+    async fn show_proc_detail(&mut self, func_id: i32) -> anyhow::Result<()> {
+        self.reveal(&TopologyTarget::Proc(func_id)).await?;
+        self.proc_detail.load(&self.pool, func_id, None).await?;
+        self.proc_detail.show()
+    }
+
+    /// 切到主机标签页并选中部署所在的主机
+    async fn jump_to_host(&mut self, host: &HostInfo) -> anyhow::Result<()> {
+        self.reveal(&TopologyTarget::Host(host.clone())).await
     }
-    async fn select_target_host_by_tcm(&mut self, tcm_sql: String) -> anyhow::Result<()> {
-        let result = query_hosts_sql(&tcm_sql, &self.pool).await?;
-        let hosts: Vec<HostInfo> = result.iter().map(|f| f.into()).collect::<Vec<HostInfo>>();
-        self.host.items = hosts;
+
+    /// 关闭文件界面, 切到能显示 target 的标签页
+    async fn reveal(&mut self, target: &TopologyTarget) -> anyhow::Result<()> {
+        self.close_files();
+        self.tabs.reveal(target).await?;
+        self.focus = Focus::Tab;
         Ok(())
     }
-    async fn select_target_proc_by_tcm(&self, tcm_sql: &str) -> anyhow::Result<Vec<TotalProc>> {
-        let result = query_hosts_sql(tcm_sql, &self.pool).await?;
-        let procs: Vec<TotalProc> = result.iter().map(|f| f.into()).collect::<Vec<TotalProc>>();
-        Ok(procs)
+
+    fn switch_tab(&mut self, id: &str) {
+        if self.tabs.select(id) {
+            self.tabs.page_mut().reset();
+        }
+        self.close_files();
+        self.focus = Focus::Tab;
+    }
+
+    fn close_files(&mut self) {
+        self.file_tree.hide();
+        self.file.clear();
+        self.file.hide();
+        self.diff.clear();
+        self.diff.hide();
+    }
+
+    /// 按上一个/下一个或者标签页的切换按键切换标签页
+    fn move_tab(&mut self, key: Key) -> EventState {
+        let id = if key == self.config.next_tab {
+            Some(self.tabs.next_id())
+        } else if key == self.config.prev_tab {
+            Some(self.tabs.previous_id())
+        } else {
+            self.tabs.id_for_key(key)
+        };
+        match id.filter(|id| *id != self.tabs.selected_id()) {
+            Some(id) => {
+                self.switch_tab(id);
+                EventState::Consumed
+            }
+            None => EventState::NotConsumed,
+        }
+    }
+}
+
+/// 注册标签页. 新的标签页实现 TabContent 后在这里注册, 第一个是默认选中的标签页
+async fn load_tabs(
+    pool: &SqlitePool,
+    config: &KeyConfig,
+    theme: &Theme,
+    query_type: QueryType,
+) -> anyhow::Result<TabComponent> {
+    let layouts = ColumnLayouts::load();
+    let host = HostTab::new(
+        TabInfo::new("host", "主机搜索", config.tab_host, &tailwind::BLUE)
+            .description("检索主机信息, Tab 键切换选中主机模式"),
+        pool,
+        &layouts,
+        config.clone(),
+        theme.clone(),
+        query_type,
+    )
+    .await?;
+    let proc = ProcTab::new(
+        TabInfo::new("proc", "进程搜索", config.tab_proc, &tailwind::EMERALD)
+            .description("更详细的 Tcm 进程信息检索, 支持文件查看, Tab 键切换到检索内容选中模式"),
+        pool,
+        &layouts,
+        config.clone(),
+        theme.clone(),
+        query_type,
+    )
+    .await?;
+    let topology = TopologyTab::new(
+        TabInfo::new("tree", "部署拓扑", config.tab_tree, &tailwind::INDIGO)
+            .description("cluster → world → zone → host → group → proc 部署拓扑, Enter 查看主机/进程详情"),
+        pool,
+        config.clone(),
+        theme.clone(),
+    )
+    .await?;
+    let stats = StatsTab::new(
+        TabInfo::new("stats", "配置统计", config.tab_stats, &tailwind::AMBER)
+            .description("按 world/zone, 主机和 FuncID 统计实例数, 列出没有部署的主机"),
+        pool,
+        config.clone(),
+        theme.clone(),
+    )
+    .await?;
+
+    let mut tabs = TabComponent::new(Box::new(host), theme.clone());
+    tabs.register(Box::new(proc));
+    tabs.register(Box::new(topology));
+    tabs.register(Box::new(stats));
+    Ok(tabs)
+}
+
+/// 挂起界面 (离开备用屏幕, 关闭 raw mode) 运行编辑器, 编辑器退出后恢复终端
+fn run_editor(
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    events: &Events,
+    path: &Path,
+    line: usize,
+    mouse: bool,
+) -> anyhow::Result<()> {
+    let editor = editor_from_env();
+    let (program, args) = editor_command(&editor, path, line)
+        .ok_or_else(|| anyhow::anyhow!("没有可用的编辑器, 请设置 $EDITOR"))?;
+    let _suspended = events.suspend();
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen, DisableMouseCapture)?;
+    terminal.show_cursor()?;
+
+    let status = std::process::Command::new(&program).args(&args).status();
+
+    enable_raw_mode()?;
+    execute!(terminal.backend_mut(), EnterAlternateScreen)?;
+    if mouse {
+        execute!(terminal.backend_mut(), EnableMouseCapture)?;
+    }
+    terminal.clear()?;
+    let status = status.with_context(|| format!("启动编辑器 {} 失败", program))?;
+    if !status.success() {
+        return Err(anyhow::anyhow!("编辑器 {} 异常退出 ({}), 没有重新加载", program, status));
     }
+    Ok(())
 }
 
-pub async fn start_app(db: &SqlitePool) -> Result<(), Box<dyn std::error::Error>> {
+pub async fn start_app(
+    db: &SqlitePool,
+    config: Config,
+    theme: Theme,
+    config_path: PathBuf,
+) -> Result<(), Box<dyn std::error::Error>> {
     enable_raw_mode()?;
     let mut stdout = std::io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    execute!(stdout, EnterAlternateScreen)?;
+    if config.mouse {
+        execute!(stdout, EnableMouseCapture)?;
+    }
+    let mouse = config.mouse;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
-    let config = config::KeyConfig::default();
-    let events = crate::event::Events::new(250);
+    let events = Events::new(config.tick_rate);
+    let conflicts = config.key_config.conflicts();
 
     // create app and run it
     terminal.clear()?;
-    let mut app = App::new(config, db).await?;
+    let mut app = App::new(config, theme, db, config_path)
+        .await
+        .map_err(|err| format!("{:#}", err))?;
+    if !conflicts.is_empty() {
+        app.notify.warn(format!("按键绑定冲突:\n{}", conflicts.join("\n")));
+    }
     loop {
         terminal.draw(|f| {
             if let Err(err) = app.draw(f) {
-                println!("start draw terminal error {}", err);
+                println!("start draw terminal error {:#}", err);
                 std::process::exit(1);
             }
         })?;
@@ -407,9 +804,33 @@ pub async fn start_app(db: &SqlitePool) -> Result<(), Box<dyn std::error::Error>
                         break;
                     }
                 }
-                Err(err) => app.error.set(err.to_string())?,
+                Err(err) => app.notify.error(format!("{:#}", err)),
             },
-            Event::Tick => (),
+            Event::Mouse(mouse) => {
+                if let Err(err) = app.mouse_event(mouse).await {
+                    app.notify.error(format!("{:#}", err));
+                }
+            }
+            // 下一轮 draw 会按新的尺寸重新布局, 这里先清屏避免残留
+            Event::Resize => {
+                terminal.autoresize()?;
+                terminal.clear()?;
+            }
+            Event::Tick => {
+                if let Err(err) = app.tick() {
+                    app.notify.error(format!("{:#}", err));
+                }
+            }
+        }
+        if let Some((path, line)) = app.take_edit() {
+            match run_editor(&mut terminal, &events, &path, line, mouse) {
+                Ok(()) => {
+                    if let Err(err) = app.edited(&path).await {
+                        app.notify.error(format!("{:#}", err));
+                    }
+                }
+                Err(err) => app.notify.error(format!("{:#}", err)),
+            }
         }
     }
 
@@ -422,6 +843,10 @@ pub async fn start_app(db: &SqlitePool) -> Result<(), Box<dyn std::error::Error>
     )?;
     terminal.show_cursor()?;
 
+    for line in &app.tcm_command.output {
+        println!("{}", line);
+    }
+
     drop_app();
     Ok(())
 }
@@ -0,0 +1,50 @@
+use anyhow::Context;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use std::fmt;
+use std::io::{IsTerminal, Write};
+use std::path::PathBuf;
+
+/// 终端一般会限制 OSC 52 的长度 (xterm 默认 100000 字节), 超出时改为写文件
+const OSC52_MAX_LEN: usize = 100_000;
+const FALLBACK_DIR: &str = "tcmhelper";
+const FALLBACK_FILE: &str = "clipboard.txt";
+
+/// 复制的结果, 底栏据此提示用户去哪里取内容
+#[derive(Debug, PartialEq)]
+pub enum Copied {
+    Terminal,
+    File(PathBuf),
+}
+
+impl fmt::Display for Copied {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Copied::Terminal => write!(f, "剪贴板"),
+            Copied::File(path) => write!(f, " {}", path.display()),
+        }
+    }
+}
+
+/// 通过 OSC 52 转义序列写入终端剪贴板, ssh 到跳板机上也能用.
+/// 不在终端里运行, 内容太长或者写入失败时写到文件里
+pub fn copy_string(text: &str) -> anyhow::Result<Copied> {
+    let encoded = STANDARD.encode(text);
+    let mut stdout = std::io::stdout();
+    if stdout.is_terminal() && encoded.len() <= OSC52_MAX_LEN {
+        let written = write!(stdout, "\x1b]52;c;{}\x07", encoded).and_then(|_| stdout.flush());
+        if written.is_ok() {
+            return Ok(Copied::Terminal);
+        }
+    }
+    copy_to_file(text).map(Copied::File)
+}
+
+fn copy_to_file(text: &str) -> anyhow::Result<PathBuf> {
+    let dir = dirs::data_local_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join(FALLBACK_DIR);
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(FALLBACK_FILE);
+    std::fs::write(&path, text).with_context(|| format!("Write clipboard -> [{}] failed", path.display()))?;
+    Ok(path)
+}
@@ -0,0 +1,468 @@
+use crate::config::KeyConfig;
+use crate::event::Key;
+use anyhow::anyhow;
+use ratatui::style::{Modifier, Style};
+use ratatui::widgets::Cell;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use unicode_width::UnicodeWidthStr;
+
+const LAYOUT_DIR: &str = "tcmhelper";
+const LAYOUT_FILE: &str = "columns.toml";
+/// 单列最大宽度, 超出的内容会被截断
+const MAX_COLUMN_WIDTH: u16 = 48;
+
+/// 数字部分按数值比较, 这样 `10.0.0.9` 排在 `10.0.0.10` 前面
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a, b);
+    loop {
+        match (a.chars().next(), b.chars().next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let a_len = a.find(|c: char| !c.is_ascii_digit()).unwrap_or(a.len());
+                let b_len = b.find(|c: char| !c.is_ascii_digit()).unwrap_or(b.len());
+                let (a_num, b_num) = (a[..a_len].trim_start_matches('0'), b[..b_len].trim_start_matches('0'));
+                let ord = a_num.len().cmp(&b_num.len()).then_with(|| a_num.cmp(b_num));
+                if ord != Ordering::Equal {
+                    return ord;
+                }
+                (a, b) = (&a[a_len..], &b[b_len..]);
+            }
+            (Some(x), Some(y)) => {
+                if x != y {
+                    return x.cmp(&y);
+                }
+                (a, b) = (&a[x.len_utf8()..], &b[y.len_utf8()..]);
+            }
+        }
+    }
+}
+
+/// 列操作的结果, 调用方据此决定是否重新排序和保存
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColumnAction {
+    Cursor,
+    Sort,
+    Layout,
+}
+
+/// 表格的列顺序, 隐藏列和排序方式, 按列名保存, 表头变化时自动补齐
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ColumnLayout {
+    order: Vec<String>,
+    hidden: Vec<String>,
+    sort: Option<String>,
+    descending: bool,
+    /// 当前选中的列, 是可见列中的位置
+    #[serde(skip)]
+    cursor: usize,
+}
+
+/// 当前宽度下实际渲染的列
+pub struct ColumnView {
+    /// 列在表头数组里的下标
+    pub columns: Vec<usize>,
+    pub widths: Vec<u16>,
+    /// 选中列在 columns 中的位置
+    pub cursor: Option<usize>,
+    pub more_left: bool,
+    pub more_right: bool,
+}
+
+/// 保存的所有表格的列布局, 由 App 在创建表格前读取一次
+#[derive(Debug, Clone, Default)]
+pub struct ColumnLayouts(BTreeMap<String, ColumnLayout>);
+
+impl ColumnLayouts {
+    /// 读取保存的布局, 文件不存在或格式错误时使用默认布局
+    pub fn load() -> Self {
+        Self(read_layouts().unwrap_or_default())
+    }
+
+    /// 表格的布局, 按当前表头补齐
+    pub fn get(&self, table: &str, names: &[&str]) -> ColumnLayout {
+        let mut layout = self.0.get(table).cloned().unwrap_or_default();
+        layout.normalize(names);
+        layout
+    }
+}
+
+impl ColumnLayout {
+    pub fn save(&self, table: &str) -> anyhow::Result<()> {
+        let path = layout_path().ok_or_else(|| anyhow!("找不到保存列布局的目录"))?;
+        let mut layouts = read_layouts().unwrap_or_default();
+        layouts.insert(table.to_string(), self.clone());
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(&path, toml::to_string(&layouts)?)
+            .map_err(|e| anyhow!("Save column layout -> [{}] failed: {}", path.display(), e))
+    }
+
+    /// 丢掉已经不存在的列, 补上新增的列
+    pub fn normalize(&mut self, names: &[&str]) {
+        self.order.retain(|name| names.contains(&name.as_str()));
+        for name in names {
+            if !self.order.iter().any(|n| n == name) {
+                self.order.push(name.to_string());
+            }
+        }
+        self.hidden.retain(|name| names.contains(&name.as_str()));
+        if self.hidden.len() >= self.order.len() {
+            self.hidden.clear();
+        }
+        if self.sort.as_ref().is_some_and(|s| !names.contains(&s.as_str())) {
+            self.sort = None;
+        }
+    }
+
+    /// 可见列在表头数组里的下标, 按显示顺序
+    pub fn visible(&self, names: &[&str]) -> Vec<usize> {
+        self.order
+            .iter()
+            .filter(|name| !self.hidden.contains(name))
+            .filter_map(|name| names.iter().position(|n| n == name))
+            .collect()
+    }
+
+    fn cursor_name(&self) -> Option<&String> {
+        self.order
+            .iter()
+            .filter(|name| !self.hidden.contains(name))
+            .nth(self.cursor)
+    }
+
+    /// 选中列在表头数组里的下标
+    pub fn cursor_column(&self, names: &[&str]) -> Option<usize> {
+        let name = self.cursor_name()?;
+        names.iter().position(|n| n == name)
+    }
+
+    /// 排序列在表头数组里的下标和是否倒序
+    pub fn sort_column(&self, names: &[&str]) -> Option<(usize, bool)> {
+        let sort = self.sort.as_ref()?;
+        let index = names.iter().position(|n| n == sort)?;
+        Some((index, self.descending))
+    }
+
+    /// 按排序列稳定排序, 没有排序列时保持原顺序
+    pub fn sort_items<T, F>(&self, names: &[&str], items: &mut [T], value: F)
+    where
+        F: Fn(&T, usize) -> &str,
+    {
+        if let Some((column, descending)) = self.sort_column(names) {
+            items.sort_by(|a, b| {
+                let ord = natural_cmp(value(a, column), value(b, column));
+                if descending {
+                    ord.reverse()
+                } else {
+                    ord
+                }
+            });
+        }
+    }
+
+    fn visible_count(&self) -> usize {
+        self.order.len() - self.hidden.len()
+    }
+
+    fn move_cursor(&mut self, forward: bool) {
+        let count = self.visible_count();
+        if forward {
+            self.cursor = (self.cursor + 1).min(count.saturating_sub(1));
+        } else {
+            self.cursor = self.cursor.saturating_sub(1);
+        }
+    }
+
+    /// 升序 → 降序 → 不排序
+    fn toggle_sort(&mut self) {
+        let Some(name) = self.cursor_name().cloned() else {
+            return;
+        };
+        match (&self.sort, self.descending) {
+            (Some(sort), false) if *sort == name => self.descending = true,
+            (Some(sort), true) if *sort == name => {
+                self.sort = None;
+                self.descending = false;
+            }
+            _ => {
+                self.sort = Some(name);
+                self.descending = false;
+            }
+        }
+    }
+
+    /// 至少保留一列
+    fn hide_column(&mut self) {
+        if self.visible_count() <= 1 {
+            return;
+        }
+        if let Some(name) = self.cursor_name().cloned() {
+            self.hidden.push(name);
+            self.cursor = self.cursor.min(self.visible_count() - 1);
+        }
+    }
+
+    fn show_all(&mut self) {
+        self.hidden.clear();
+    }
+
+    /// 和相邻的可见列交换位置, 选中列跟着移动
+    fn move_column(&mut self, forward: bool) {
+        let visible = self
+            .order
+            .iter()
+            .enumerate()
+            .filter(|(_, name)| !self.hidden.contains(name))
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        let target = if forward {
+            self.cursor + 1
+        } else {
+            match self.cursor.checked_sub(1) {
+                Some(target) => target,
+                None => return,
+            }
+        };
+        if let (Some(&a), Some(&b)) = (visible.get(self.cursor), visible.get(target)) {
+            self.order.swap(a, b);
+            self.cursor = target;
+        }
+    }
+
+    pub fn event(&mut self, key: Key, key_config: &KeyConfig) -> Option<ColumnAction> {
+        if key == key_config.scroll_right {
+            self.move_cursor(true);
+            Some(ColumnAction::Cursor)
+        } else if key == key_config.scroll_left {
+            self.move_cursor(false);
+            Some(ColumnAction::Cursor)
+        } else if key == key_config.sort_column {
+            self.toggle_sort();
+            Some(ColumnAction::Sort)
+        } else if key == key_config.hide_column {
+            self.hide_column();
+            Some(ColumnAction::Layout)
+        } else if key == key_config.show_columns {
+            self.show_all();
+            Some(ColumnAction::Layout)
+        } else if key == key_config.move_column_left {
+            self.move_column(false);
+            Some(ColumnAction::Layout)
+        } else if key == key_config.move_column_right {
+            self.move_column(true);
+            Some(ColumnAction::Layout)
+        } else {
+            None
+        }
+    }
+
+    /// 根据每列内容的最大宽度 (见 content_widths) 计算列宽. 放不下时先按顺序去掉
+    /// droppable 里的低优先级列 (选中列除外), 还放不下再从左边滚动, 保证选中列可见
+    pub fn view(
+        &self,
+        names: &[&str],
+        content_widths: &[usize],
+        width: u16,
+        droppable: &[&str],
+    ) -> ColumnView {
+        let mut visible = self.visible(names);
+        let mut widths = visible
+            .iter()
+            .map(|&column| {
+                let content = content_widths.get(column).copied().unwrap_or(0);
+                // 表头留出排序标记的位置
+                let header = names[column].width() + 2;
+                (content.max(header) as u16).min(MAX_COLUMN_WIDTH) + 1
+            })
+            .collect::<Vec<_>>();
+        let mut cursor = self.cursor.min(visible.len().saturating_sub(1));
+
+        for name in droppable {
+            if widths.iter().sum::<u16>() <= width {
+                break;
+            }
+            let Some(pos) = visible.iter().position(|&column| names[column] == *name) else {
+                continue;
+            };
+            if pos == cursor {
+                continue;
+            }
+            visible.remove(pos);
+            widths.remove(pos);
+            if pos < cursor {
+                cursor -= 1;
+            }
+        }
+
+        let fits = |from: usize, to: usize| widths[from..=to].iter().sum::<u16>() <= width;
+        let mut offset = 0;
+        while offset < cursor && !fits(offset, cursor) {
+            offset += 1;
+        }
+        let mut end = offset;
+        while end + 1 < visible.len() && fits(offset, end + 1) {
+            end += 1;
+        }
+        let end = (end + 1).min(visible.len());
+
+        ColumnView {
+            columns: visible[offset..end].to_vec(),
+            widths: widths[offset..end].to_vec(),
+            cursor: (!visible.is_empty()).then_some(cursor - offset),
+            more_left: offset > 0,
+            more_right: end < visible.len(),
+        }
+    }
+
+    /// 表头文字, 带排序标记和左右还有更多列的提示
+    pub fn header(&self, names: &[&str], view: &ColumnView) -> Vec<String> {
+        let sort = self.sort_column(names);
+        let last = view.columns.len().saturating_sub(1);
+        view.columns
+            .iter()
+            .enumerate()
+            .map(|(i, &column)| {
+                let mut title = names[column].to_string();
+                match sort {
+                    Some((sorted, false)) if sorted == column => title.push_str(" ▲"),
+                    Some((sorted, true)) if sorted == column => title.push_str(" ▼"),
+                    _ => {}
+                }
+                if i == 0 && view.more_left {
+                    title = format!("« {}", title);
+                }
+                if i == last && view.more_right {
+                    title.push_str(" »");
+                }
+                title
+            })
+            .collect()
+    }
+
+    /// 表头单元格, 选中列反色显示
+    pub fn header_cells(&self, names: &[&str], view: &ColumnView, style: Style) -> Vec<Cell<'static>> {
+        self.header(names, view)
+            .into_iter()
+            .enumerate()
+            .map(|(i, title)| match view.cursor {
+                Some(cursor) if cursor == i => {
+                    Cell::from(title).style(style.add_modifier(Modifier::REVERSED))
+                }
+                _ => Cell::from(title),
+            })
+            .collect()
+    }
+}
+
+/// 每列内容的最大显示宽度, 数据变化时计算一次
+pub fn content_widths(column_count: usize, rows: &[Vec<String>]) -> Vec<usize> {
+    let mut widths = vec![0; column_count];
+    for row in rows {
+        for (width, value) in widths.iter_mut().zip(row) {
+            *width = (*width).max(value.width());
+        }
+    }
+    widths
+}
+
+fn layout_path() -> Option<PathBuf> {
+    dirs::data_local_dir().map(|dir| dir.join(LAYOUT_DIR).join(LAYOUT_FILE))
+}
+
+fn read_layouts() -> anyhow::Result<BTreeMap<String, ColumnLayout>> {
+    let path = layout_path().ok_or_else(|| anyhow!("找不到保存列布局的目录"))?;
+    let content = std::fs::read_to_string(path)?;
+    Ok(toml::from_str(&content)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NAMES: [&str; 4] = ["InnerIp", "WorldID", "ZoneID", "HostName"];
+
+    fn layout() -> ColumnLayout {
+        let mut layout = ColumnLayout::default();
+        layout.normalize(&NAMES);
+        layout
+    }
+
+    #[test]
+    fn test_natural_cmp() {
+        assert_eq!(natural_cmp("10.0.0.9", "10.0.0.10"), Ordering::Less);
+        assert_eq!(natural_cmp("201", "31"), Ordering::Greater);
+        assert_eq!(natural_cmp("Host_7", "Host_07"), Ordering::Equal);
+        assert_eq!(natural_cmp("Host_DB", "Host_Main"), Ordering::Less);
+        assert_eq!(natural_cmp("svr", "svr2"), Ordering::Less);
+    }
+
+    #[test]
+    fn test_sort_and_layout() {
+        let key_config = KeyConfig::default();
+        let mut layout = layout();
+        let mut items = vec![["10.0.0.10", "2"], ["10.0.0.9", "1"], ["10.0.0.2", "3"]];
+
+        assert_eq!(layout.event(key_config.sort_column, &key_config), Some(ColumnAction::Sort));
+        layout.sort_items(&NAMES, &mut items, |item, column| item[column]);
+        assert_eq!(items[0][0], "10.0.0.2");
+        assert_eq!(items[2][0], "10.0.0.10");
+
+        layout.event(key_config.sort_column, &key_config);
+        layout.sort_items(&NAMES, &mut items, |item, column| item[column]);
+        assert_eq!(items[0][0], "10.0.0.10");
+
+        layout.event(key_config.move_column_right, &key_config);
+        layout.event(key_config.hide_column, &key_config);
+        assert_eq!(layout.visible(&NAMES), vec![1, 2, 3]);
+        layout.event(key_config.show_columns, &key_config);
+        assert_eq!(layout.visible(&NAMES), vec![1, 0, 2, 3]);
+    }
+
+    #[test]
+    fn test_normalize_saved_layout() {
+        let mut layout: ColumnLayout = toml::from_str(
+            r#"
+            order = ["HostName", "Removed", "InnerIp"]
+            hidden = ["Removed", "InnerIp"]
+            sort = "Removed"
+            "#,
+        )
+        .unwrap();
+        layout.normalize(&NAMES);
+        assert_eq!(layout.visible(&NAMES), vec![3, 1, 2]);
+        assert_eq!(layout.sort_column(&NAMES), None);
+    }
+
+    #[test]
+    fn test_horizontal_view() {
+        let mut layout = layout();
+        let rows = vec![vec!["x".repeat(20), "2".into(), "70".into(), "y".repeat(20)]];
+        let widths = content_widths(NAMES.len(), &rows);
+        assert_eq!(widths, vec![20, 1, 2, 20]);
+        let view = layout.view(&NAMES, &widths, 40, &[]);
+        assert_eq!(view.columns, vec![0, 1, 2]);
+        assert!(view.more_right && !view.more_left);
+
+        layout.cursor = 3;
+        let view = layout.view(&NAMES, &widths, 40, &[]);
+        assert_eq!(view.columns, vec![1, 2, 3]);
+        assert_eq!(view.cursor, Some(2));
+        assert!(view.more_left && !view.more_right);
+
+        // 先去掉低优先级的列, 选中列不会被去掉
+        let view = layout.view(&NAMES, &widths, 40, &["HostName", "InnerIp"]);
+        assert_eq!(view.columns, vec![1, 2, 3]);
+        assert!(!view.more_left && !view.more_right);
+        layout.cursor = 0;
+        let view = layout.view(&NAMES, &widths, 40, &["HostName", "InnerIp"]);
+        assert_eq!(view.columns, vec![0, 1, 2]);
+        assert_eq!(view.cursor, Some(0));
+    }
+}
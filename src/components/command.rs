@@ -1,10 +1,10 @@
+use crate::components::query_input::QueryType;
 use crate::config::KeyConfig;
+use crate::event::Key;
 
 static CMD_GROUP_GENERAL: &str = "-- General --";
-#[allow(dead_code)]
 static CMD_GROUP_TABLE: &str = "-- Table --";
 static CMD_GROUP_DATABASES: &str = "-- Databases --";
-#[allow(dead_code)]
 static CMD_GROUP_PROPERTIES: &str = "-- Properties --";
 
 #[derive(Clone, PartialEq, PartialOrd, Ord, Eq)]
@@ -24,13 +24,38 @@ impl CommandText {
     }
 }
 
+/// 命令面板执行的动作
+#[derive(Clone, Debug, PartialEq)]
+pub enum Action {
+    /// 依次模拟按键, 和直接按下的效果相同
+    Keys(Vec<Key>),
+    /// 切换到 id 对应的标签页
+    SwitchTab(&'static str),
+    QueryType(QueryType),
+    /// 重新导入 TCM 配置
+    Reload,
+    /// 切换到用户配置中 profiles 下的另一个 TCM 配置目录
+    SwitchProfile(String),
+}
+
+#[derive(Clone)]
 pub struct CommandInfo {
     pub text: CommandText,
+    /// 命令面板中的条目, 一条帮助可以对应多个动作 (例如切换到每个标签页)
+    pub actions: Vec<(String, Action)>,
 }
 
 impl CommandInfo {
     pub const fn new(text: CommandText) -> Self {
-        Self { text }
+        Self {
+            text,
+            actions: Vec::new(),
+        }
+    }
+
+    pub fn action(mut self, name: impl Into<String>, action: Action) -> Self {
+        self.actions.push((name.into(), action));
+        self
     }
 }
 
@@ -55,6 +80,60 @@ pub fn scroll_to_top_bottom(key: &KeyConfig) -> CommandText {
     )
 }
 
+pub fn columns(key: &KeyConfig) -> CommandText {
+    CommandText::new(
+        format!(
+            "Select/sort/hide/show columns [{},{},{},{},{}]",
+            key.scroll_left, key.scroll_right, key.sort_column, key.hide_column, key.show_columns
+        ),
+        CMD_GROUP_TABLE,
+    )
+}
+
+pub fn move_column(key: &KeyConfig) -> CommandText {
+    CommandText::new(
+        format!(
+            "Move column left/right [{},{}]",
+            key.move_column_left, key.move_column_right
+        ),
+        CMD_GROUP_TABLE,
+    )
+}
+
+pub fn switch_palette(key: &KeyConfig) -> CommandText {
+    CommandText::new(
+        format!("Switch table palette [{}]", key.switch_palette),
+        CMD_GROUP_TABLE,
+    )
+}
+
+pub fn switch_density(key: &KeyConfig) -> CommandText {
+    CommandText::new(
+        format!("Switch row density [{}]", key.switch_density),
+        CMD_GROUP_TABLE,
+    )
+}
+
+pub fn mark_rows(key: &KeyConfig) -> CommandText {
+    CommandText::new(
+        format!(
+            "Mark row/range, clear marks [{},{},{}]",
+            key.mark, key.visual_mark, key.exit_popup
+        ),
+        CMD_GROUP_TABLE,
+    )
+}
+
+pub fn batch_actions(key: &KeyConfig) -> CommandText {
+    CommandText::new(
+        format!(
+            "Marked rows: narrow/copy IPs/export [{},{},{}]",
+            key.narrow_marked, key.copy_ips, key.export_rows
+        ),
+        CMD_GROUP_TABLE,
+    )
+}
+
 pub fn expand_collapse(key: &KeyConfig) -> CommandText {
     CommandText::new(
         format!("Expand/Collapse [{},{}]", key.scroll_right, key.scroll_left,),
@@ -62,42 +141,196 @@ pub fn expand_collapse(key: &KeyConfig) -> CommandText {
     )
 }
 
+pub fn tcm_command(key: &KeyConfig) -> CommandText {
+    CommandText::new(
+        format!("Generate tcm command [{}]", key.tcm_command),
+        CMD_GROUP_TABLE,
+    )
+}
+
+pub fn proc_detail(key: &KeyConfig) -> CommandText {
+    CommandText::new(
+        format!("Proc detail / jump to host [{}]", key.enter),
+        CMD_GROUP_PROPERTIES,
+    )
+}
+
+pub fn host_detail(key: &KeyConfig) -> CommandText {
+    CommandText::new(
+        format!("Host detail / open proc detail [{}]", key.enter),
+        CMD_GROUP_PROPERTIES,
+    )
+}
+
+pub fn open_proc_file(key: &KeyConfig) -> CommandText {
+    CommandText::new(
+        format!(
+            "Open config/binary file [{},{}]",
+            key.open_config, key.open_binary
+        ),
+        CMD_GROUP_PROPERTIES,
+    )
+}
+
+pub fn browse_files(key: &KeyConfig) -> CommandText {
+    CommandText::new(
+        format!(
+            "Browse proc files [{}] expand/collapse [{},{}]",
+            key.browse_files, key.scroll_right, key.scroll_left
+        ),
+        CMD_GROUP_PROPERTIES,
+    )
+}
+
+pub fn follow_file(key: &KeyConfig) -> CommandText {
+    CommandText::new(
+        format!(
+            "Follow file [{}] pause [{}] filter [{}]",
+            key.follow_file, key.pause_follow, key.filter_follow
+        ),
+        CMD_GROUP_PROPERTIES,
+    )
+}
+
+pub fn switch_theme(key: &KeyConfig) -> CommandText {
+    CommandText::new(
+        format!("Switch theme [{}]", key.switch_theme),
+        CMD_GROUP_GENERAL,
+    )
+}
+
 pub fn filter(key: &KeyConfig) -> CommandText {
     CommandText::new(format!("Filter [{}]", key.filter), CMD_GROUP_GENERAL)
 }
 
 pub fn move_focus(key: &KeyConfig) -> CommandText {
+    CommandText::new(
+        format!("Move focus [{}]", key.toggle_focus),
+        CMD_GROUP_GENERAL,
+    )
+}
+
+pub fn toggle_tabs(key_config: &KeyConfig) -> CommandText {
     CommandText::new(
         format!(
-            "Move focus to left/right [{},{}]",
-            key.focus_left, key.focus_right
+            "Tab [{},{},{},{}] next/prev [{},{}]",
+            key_config.tab_host,
+            key_config.tab_proc,
+            key_config.tab_tree,
+            key_config.tab_stats,
+            key_config.next_tab,
+            key_config.prev_tab
         ),
         CMD_GROUP_GENERAL,
     )
 }
 
+pub fn file_search(key_config: &KeyConfig) -> CommandText {
+    CommandText::new(
+        format!(
+            "Search file [{}] next/prev [{},{}] goto line [{}]",
+            key_config.filter, key_config.search_next, key_config.search_prev, key_config.goto_line
+        ),
+        CMD_GROUP_GENERAL,
+    )
+}
+
+pub fn toggle_wrap(key_config: &KeyConfig) -> CommandText {
+    CommandText::new(
+        format!(
+            "Toggle wrap [{}] scroll left/right [{},{}]",
+            key_config.toggle_wrap, key_config.scroll_left, key_config.scroll_right
+        ),
+        CMD_GROUP_GENERAL,
+    )
+}
 
-pub fn extend_or_shorten_widget_width(key: &KeyConfig) -> CommandText {
+pub fn diff_file(key_config: &KeyConfig) -> CommandText {
     CommandText::new(
         format!(
-            "Extend/shorten widget width to left/right [{},{}]",
-            key.extend_or_shorten_widget_width_to_left, key.extend_or_shorten_widget_width_to_right
+            "Mark file for diff [{}] diff with marked [{}]",
+            key_config.mark, key_config.diff_marked
         ),
         CMD_GROUP_GENERAL,
     )
 }
 
+pub fn diff_hunk(key_config: &KeyConfig) -> CommandText {
+    CommandText::new(
+        format!(
+            "Diff next/prev hunk [{},{}] side-by-side [{}] scroll left/right [{},{}]",
+            key_config.search_next,
+            key_config.search_prev,
+            key_config.toggle_diff_layout,
+            key_config.scroll_left,
+            key_config.scroll_right
+        ),
+        CMD_GROUP_GENERAL,
+    )
+}
 
-pub fn toggle_tabs(key_config: &KeyConfig) -> CommandText {
+pub fn open_editor(key_config: &KeyConfig) -> CommandText {
     CommandText::new(
         format!(
-            "Tab [{},{},{}]",
-            key_config.tab_records, key_config.tab_properties, key_config.tab_sql_editor
+            "Open file / proc.xml in $EDITOR [{}]",
+            key_config.open_editor
         ),
         CMD_GROUP_GENERAL,
     )
 }
 
+pub fn query_type(key_config: &KeyConfig) -> CommandText {
+    CommandText::new(
+        format!(
+            "Switch query type [{},{}]",
+            key_config.move_up, key_config.move_down
+        ),
+        CMD_GROUP_GENERAL,
+    )
+}
+
+pub fn reload_config(key_config: &KeyConfig) -> CommandText {
+    CommandText::new(
+        format!("Reload TCM config [{} 命令面板]", key_config.command_palette),
+        CMD_GROUP_GENERAL,
+    )
+}
+
+pub fn switch_profile(key_config: &KeyConfig) -> CommandText {
+    CommandText::new(
+        format!("Switch TCM config profile [{} 命令面板]", key_config.command_palette),
+        CMD_GROUP_GENERAL,
+    )
+}
+
+pub fn command_palette(key_config: &KeyConfig) -> CommandText {
+    CommandText::new(
+        format!("Command palette [{}]", key_config.command_palette),
+        CMD_GROUP_GENERAL,
+    )
+}
+
+pub fn message_log(key_config: &KeyConfig) -> CommandText {
+    CommandText::new(
+        format!("Message log [{}]", key_config.message_log),
+        CMD_GROUP_GENERAL,
+    )
+}
+
+pub fn copy(key_config: &KeyConfig) -> CommandText {
+    CommandText::new(
+        format!(
+            "Copy cell/row/column/IPs/selector [{} then {},{},{},{},{}]",
+            key_config.copy,
+            key_config.yank_cell,
+            key_config.yank_row,
+            key_config.yank_column,
+            key_config.yank_ips,
+            key_config.yank_selector
+        ),
+        CMD_GROUP_TABLE,
+    )
+}
 
 pub fn help(key_config: &KeyConfig) -> CommandText {
     CommandText::new(
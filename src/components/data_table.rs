@@ -0,0 +1,880 @@
+use super::columns::{content_widths, natural_cmp, ColumnAction, ColumnLayout, ColumnLayouts};
+use super::{Component, DrawableComponent, EventState};
+use crate::components::command::{self, Action, CommandInfo};
+use crate::config::KeyConfig;
+use crate::event::Key;
+use crate::theme::{TableColors, Theme};
+use anyhow::Context;
+use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
+use hashbrown::HashSet;
+use ratatui::layout::Position;
+use serde::Deserialize;
+use ratatui::{prelude::*, widgets::*};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 高亮符号的宽度
+const HIGHLIGHT_WIDTH: u16 = 3;
+/// 标记列的宽度
+const MARK_WIDTH: u16 = 2;
+
+/// 可以放进 DataTable 的数据行
+pub trait TableRow: Clone + PartialEq {
+    /// 表头, 顺序和 cells 一致
+    const COLUMNS: &'static [&'static str];
+
+    /// 终端太窄时优先去掉的列, 按顺序去掉
+    const DROPPABLE: &'static [&'static str] = &[];
+
+    fn cells(&self) -> Vec<String>;
+
+    /// 唯一标识一行, 重新查询后用来保持标记
+    fn key(&self) -> String;
+
+    /// 行对应的主机 IP, 没有 IP 列的表格返回 None
+    fn inner_ip(&self) -> Option<&str> {
+        None
+    }
+
+    /// 生成 tcm 选择器, 例如 `2.70.201.1-3`
+    fn tcm_selectors(_items: &[Self]) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+/// 表格行高
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Density {
+    /// 每行一行
+    Compact,
+    /// 每行两行, 下面留一行空白
+    Normal,
+    /// 每行四行, 内容上下各留一行空白
+    #[default]
+    Spacious,
+}
+
+impl Density {
+    pub fn height(self) -> usize {
+        match self {
+            Density::Compact => 1,
+            Density::Normal => 2,
+            Density::Spacious => 4,
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            Density::Compact => Density::Normal,
+            Density::Normal => Density::Spacious,
+            Density::Spacious => Density::Compact,
+        }
+    }
+
+    /// 单元格内容前面的空行
+    fn pad(self, content: &str) -> String {
+        match self {
+            Density::Spacious => format!("\n{}", content),
+            _ => content.to_string(),
+        }
+    }
+
+    fn highlight_symbol(self) -> Text<'static> {
+        let bar = " █ ";
+        let lines: Vec<Line> = match self {
+            Density::Compact => vec![bar.into()],
+            Density::Normal => vec![bar.into(), bar.into()],
+            Density::Spacious => vec!["".into(), bar.into(), bar.into(), "".into()],
+        };
+        Text::from(lines)
+    }
+}
+
+/// 复制内容的格式, 按下 copy 后再按对应的字母选择
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum YankFormat {
+    Cell,
+    Row,
+    Column,
+    Ips,
+    Selector,
+}
+
+impl YankFormat {
+    pub const ALL: [YankFormat; 5] = [
+        YankFormat::Cell,
+        YankFormat::Row,
+        YankFormat::Column,
+        YankFormat::Ips,
+        YankFormat::Selector,
+    ];
+
+    /// 选择这个格式的按键
+    pub fn key(&self, key_config: &KeyConfig) -> Key {
+        match self {
+            YankFormat::Cell => key_config.yank_cell,
+            YankFormat::Row => key_config.yank_row,
+            YankFormat::Column => key_config.yank_column,
+            YankFormat::Ips => key_config.yank_ips,
+            YankFormat::Selector => key_config.yank_selector,
+        }
+    }
+
+    pub fn from_key(key: Key, key_config: &KeyConfig) -> Option<Self> {
+        Self::ALL.into_iter().find(|format| format.key(key_config) == key)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            YankFormat::Cell => "单元格",
+            YankFormat::Row => "行",
+            YankFormat::Column => "列",
+            YankFormat::Ips => "IP",
+            YankFormat::Selector => "TCM 选择器",
+        }
+    }
+
+    /// 等待选择格式时底栏的提示
+    pub fn hint(key_config: &KeyConfig) -> String {
+        let formats = Self::ALL
+            .iter()
+            .map(|format| format!("[{}]{}", format.key(key_config), format.name()))
+            .collect::<Vec<_>>()
+            .join(" ");
+        format!("复制: {}", formats)
+    }
+}
+
+/// 通用的结果表格: 列布局, 排序, 翻页和配色都在这里处理
+#[derive(Clone)]
+pub struct DataTable<T: TableRow> {
+    /// 保存列布局时使用的表格名
+    name: &'static str,
+    items: Vec<T>,
+    /// 每行的单元格文字, 和 items 一一对应, 数据变化时生成一次
+    cells: Vec<Vec<String>>,
+    /// 每行的 key, 和 items 一一对应
+    keys: Vec<String>,
+    /// 每列内容的最大宽度, 数据变化时计算一次
+    content_widths: Vec<usize>,
+    state: TableState,
+    columns: ColumnLayout,
+    scroll_state: ScrollbarState,
+    /// 上次绘制时一页能显示的行数, 翻页时使用
+    page_rows: std::cell::Cell<usize>,
+    /// 上次绘制的区域和第一行的下标, 鼠标点击时换算行号
+    area: std::cell::Cell<Rect>,
+    offset: std::cell::Cell<usize>,
+    density: Density,
+    /// 已经按下一次 scroll_to_top, 再按一次回到顶部
+    pending_top: bool,
+    /// 标记的行的 key, 重新查询后仍然存在的行保持标记
+    marked: HashSet<String>,
+    /// 区间标记的起点
+    visual_anchor: Option<usize>,
+    color_index: usize,
+    theme: Theme,
+    key_config: KeyConfig,
+}
+
+impl<T: TableRow> DataTable<T> {
+    pub fn new(
+        name: &'static str,
+        items: Vec<T>,
+        layouts: &ColumnLayouts,
+        key_config: KeyConfig,
+        theme: Theme,
+    ) -> Self {
+        let mut table = Self {
+            name,
+            items: Vec::new(),
+            cells: Vec::new(),
+            keys: Vec::new(),
+            content_widths: Vec::new(),
+            state: TableState::default(),
+            columns: layouts.get(name, T::COLUMNS),
+            scroll_state: ScrollbarState::default(),
+            page_rows: std::cell::Cell::new(1),
+            area: std::cell::Cell::new(Rect::default()),
+            offset: std::cell::Cell::new(0),
+            density: Density::default(),
+            pending_top: false,
+            marked: HashSet::new(),
+            visual_anchor: None,
+            color_index: 0,
+            theme,
+            key_config,
+        };
+        table.set_items(items);
+        table
+    }
+
+    pub fn items(&self) -> &[T] {
+        &self.items
+    }
+
+    /// 替换表格内容, 按当前排序列排序并选中第一行
+    pub fn set_items(&mut self, items: Vec<T>) {
+        let rows = items
+            .into_iter()
+            .map(|item| {
+                let cells = item.cells();
+                (item, cells)
+            })
+            .collect();
+        self.set_rows(rows);
+        self.marked = self
+            .keys
+            .iter()
+            .filter(|key| self.marked.contains(*key))
+            .cloned()
+            .collect();
+        self.visual_anchor = None;
+        self.content_widths = content_widths(T::COLUMNS.len(), &self.cells);
+        self.scroll_state =
+            ScrollbarState::new(self.items.len().saturating_sub(1) * self.density.height());
+        self.select_index(0);
+    }
+
+    /// 按当前排序列排序后保存行和对应的单元格文字
+    fn set_rows(&mut self, mut rows: Vec<(T, Vec<String>)>) {
+        self.columns
+            .sort_items(T::COLUMNS, &mut rows, |(_, cells), column| &cells[column]);
+        (self.items, self.cells) = rows.into_iter().unzip();
+        self.keys = self.items.iter().map(T::key).collect();
+    }
+
+    /// 切换行高, 滚动条按新的行高重新计算
+    pub fn set_density(&mut self, density: Density) {
+        self.density = density;
+        self.scroll_state =
+            ScrollbarState::new(self.items.len().saturating_sub(1) * density.height());
+        let selected = self.state.selected().unwrap_or(0);
+        self.select_index(selected);
+    }
+
+    pub fn selected_item(&self) -> Option<&T> {
+        self.state.selected().and_then(|i| self.items.get(i))
+    }
+
+    pub fn marked_count(&self) -> usize {
+        self.marked.len()
+    }
+
+    /// 批量操作的对象: 有标记时是标记的行 (按表格顺序), 否则是选中行
+    pub fn targets(&self) -> Vec<T> {
+        if self.marked.is_empty() {
+            return self.selected_item().cloned().into_iter().collect();
+        }
+        self.items
+            .iter()
+            .zip(&self.keys)
+            .filter(|(_, key)| self.marked.contains(*key))
+            .map(|(item, _)| item.clone())
+            .collect()
+    }
+
+    /// 批量操作对象的 IP, 去重并按数值排序
+    pub fn target_ips(&self) -> Vec<String> {
+        let mut ips = self
+            .targets()
+            .iter()
+            .filter_map(|item| item.inner_ip().map(str::to_string))
+            .collect::<Vec<_>>();
+        ips.sort_by(|a, b| natural_cmp(a, b));
+        ips.dedup();
+        ips
+    }
+
+    /// 要复制的内容, 每个元素一行. 列是标记的行 (没有标记时是整个表格) 的选中列,
+    /// 其余格式针对批量操作对象
+    pub fn yank(&self, format: YankFormat) -> Vec<String> {
+        let cursor = self.columns.cursor_column(T::COLUMNS);
+        match format {
+            YankFormat::Cell => self
+                .selected_item()
+                .zip(cursor)
+                .map(|(item, column)| item.cells()[column].clone())
+                .into_iter()
+                .collect(),
+            YankFormat::Row => {
+                let columns = self.columns.visible(T::COLUMNS);
+                self.targets()
+                    .iter()
+                    .map(|item| {
+                        let cells = item.cells();
+                        columns
+                            .iter()
+                            .map(|&column| cells[column].as_str())
+                            .collect::<Vec<_>>()
+                            .join("\t")
+                    })
+                    .collect()
+            }
+            YankFormat::Column => {
+                let Some(column) = cursor else {
+                    return Vec::new();
+                };
+                let rows = if self.marked.is_empty() {
+                    self.items.clone()
+                } else {
+                    self.targets()
+                };
+                rows.iter().map(|item| item.cells()[column].clone()).collect()
+            }
+            YankFormat::Ips => self.target_ips(),
+            YankFormat::Selector => T::tcm_selectors(&self.targets()),
+        }
+    }
+
+    /// 把批量操作对象按当前列布局导出成 csv, 返回文件路径
+    pub fn export(&self) -> anyhow::Result<PathBuf> {
+        let columns = self.columns.visible(T::COLUMNS);
+        let mut lines = vec![columns
+            .iter()
+            .map(|&column| csv_field(T::COLUMNS[column]))
+            .collect::<Vec<_>>()
+            .join(",")];
+        for item in self.targets() {
+            let cells = item.cells();
+            lines.push(
+                columns
+                    .iter()
+                    .map(|&column| csv_field(&cells[column]))
+                    .collect::<Vec<_>>()
+                    .join(","),
+            );
+        }
+        let secs = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let path = std::env::current_dir()?.join(format!("tcmhelper-{}-{}.csv", self.name, secs));
+        std::fs::write(&path, lines.join("\n") + "\n")
+            .with_context(|| format!("Export -> [{}] failed", path.display()))?;
+        Ok(path)
+    }
+
+    fn is_marked(&self, index: usize) -> bool {
+        let in_visual = match (self.visual_anchor, self.state.selected()) {
+            (Some(anchor), Some(selected)) => {
+                (anchor.min(selected)..=anchor.max(selected)).contains(&index)
+            }
+            _ => false,
+        };
+        in_visual || self.keys.get(index).is_some_and(|key| self.marked.contains(key))
+    }
+
+    /// 切换选中行的标记并移到下一行
+    fn toggle_mark(&mut self) {
+        let Some(key) = self.state.selected().and_then(|i| self.keys.get(i)) else {
+            return;
+        };
+        if !self.marked.remove(key) {
+            self.marked.insert(key.clone());
+        }
+        let next = self.state.selected().map_or(0, |i| i + 1);
+        self.select_index(next);
+    }
+
+    /// 第一次按下记录起点, 再按一次把起点到选中行之间的行都标记上
+    fn toggle_visual(&mut self) {
+        let Some(selected) = self.state.selected() else {
+            return;
+        };
+        match self.visual_anchor.take() {
+            None => self.visual_anchor = Some(selected),
+            Some(anchor) => {
+                let range = anchor.min(selected)..=anchor.max(selected);
+                self.marked.extend(self.keys[range].iter().cloned());
+            }
+        }
+    }
+
+    /// 只显示标记的行
+    fn narrow(&mut self) {
+        if !self.marked.is_empty() {
+            let items = self.targets();
+            self.set_items(items);
+        }
+    }
+
+    /// 选中指定的行, 不在列表中时保持原来的选中行
+    pub fn select(&mut self, item: &T) {
+        if let Some(i) = self.items.iter().position(|it| it == item) {
+            self.select_index(i);
+        }
+    }
+
+    /// 选中下标, 超出范围时选中最后一行, 没有数据时清空选中
+    fn select_index(&mut self, index: usize) {
+        if self.items.is_empty() {
+            self.state.select(None);
+            self.scroll_state = self.scroll_state.position(0);
+            return;
+        }
+        let index = index.min(self.items.len() - 1);
+        self.state.select(Some(index));
+        self.scroll_state = self.scroll_state.position(index * self.density.height());
+    }
+
+    /// 上下移动一行, 到头后回绕
+    fn step(&mut self, forward: bool) {
+        let count = self.items.len();
+        if count == 0 {
+            return;
+        }
+        let index = match self.state.selected() {
+            Some(i) if forward => (i + 1) % count,
+            Some(i) => (i + count - 1) % count,
+            None => 0,
+        };
+        self.select_index(index);
+    }
+
+    /// 移动多行, 到头后停住
+    fn jump(&mut self, rows: usize, forward: bool) {
+        let current = self.state.selected().unwrap_or(0);
+        let index = if forward {
+            current.saturating_add(rows)
+        } else {
+            current.saturating_sub(rows)
+        };
+        self.select_index(index);
+    }
+
+    /// 重新排序, 保持选中原来的行
+    fn sort(&mut self) {
+        let selected = self.selected_item().cloned();
+        let items = std::mem::take(&mut self.items);
+        let cells = std::mem::take(&mut self.cells);
+        self.set_rows(items.into_iter().zip(cells).collect());
+        if let Some(item) = selected {
+            self.select(&item);
+        }
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    fn next_color(&mut self) {
+        self.color_index = (self.color_index + 1) % self.theme.palette_count();
+    }
+
+    fn render_table(&self, f: &mut Frame, area: Rect, colors: &TableColors) {
+        let header_style = Style::default().fg(colors.header_fg).bg(colors.header_bg);
+        let selected_style = Style::default()
+            .add_modifier(Modifier::REVERSED)
+            .fg(colors.selected_style_fg);
+
+        let view = self
+            .columns
+            .view(
+                T::COLUMNS,
+                &self.content_widths,
+                area.width.saturating_sub(HIGHLIGHT_WIDTH + MARK_WIDTH),
+                T::DROPPABLE,
+            );
+        let header = std::iter::once(Cell::from(""))
+            .chain(self.columns.header_cells(T::COLUMNS, &view, header_style))
+            .collect::<Row>()
+            .style(header_style)
+            .height(1);
+        let rows = self.cells.iter().enumerate().map(|(i, data)| {
+            let color = match i % 2 {
+                0 => colors.normal_row_color,
+                _ => colors.alt_row_color,
+            };
+            let mark = if self.is_marked(i) { "●" } else { "" };
+            std::iter::once(Cell::from(Text::from(self.density.pad(mark))))
+                .chain(
+                    view.columns
+                        .iter()
+                        .map(|&column| Cell::from(Text::from(self.density.pad(&data[column])))),
+                )
+                .collect::<Row>()
+                .style(Style::new().fg(colors.row_fg).bg(color))
+                .height(self.density.height() as u16)
+        });
+        let widths = std::iter::once(MARK_WIDTH)
+            .chain(view.widths.iter().copied())
+            .map(Constraint::Length);
+        let t = Table::new(rows, widths)
+            .column_spacing(0)
+            .header(header)
+            .highlight_style(selected_style)
+            .highlight_symbol(self.density.highlight_symbol())
+            .bg(colors.buffer_bg)
+            .highlight_spacing(HighlightSpacing::Always);
+        let mut state = self.state.clone();
+        f.render_stateful_widget(t, area, &mut state);
+        self.offset.set(state.offset());
+    }
+
+    /// 鼠标位置对应的行, 表头和空白处返回 None
+    fn row_at(&self, column: u16, row: u16) -> Option<usize> {
+        let area = self.area.get();
+        if !area.contains(Position::new(column, row)) || row == area.y {
+            return None;
+        }
+        let index = self.offset.get() + (row - area.y - 1) as usize / self.density.height();
+        (index < self.items.len()).then_some(index)
+    }
+
+    fn render_empty(&self, f: &mut Frame, area: Rect, colors: &TableColors) {
+        let [header_area, body_area] =
+            Layout::vertical([Constraint::Length(1), Constraint::Min(1)]).areas(area);
+        let header = self
+            .columns
+            .visible(T::COLUMNS)
+            .into_iter()
+            .map(|column| T::COLUMNS[column])
+            .collect::<Vec<_>>()
+            .join("  ");
+        f.render_widget(
+            Paragraph::new(format!("   {}", header))
+                .style(Style::default().fg(colors.header_fg).bg(colors.header_bg)),
+            header_area,
+        );
+        let [_, message_area, _] = Layout::vertical([
+            Constraint::Percentage(40),
+            Constraint::Length(1),
+            Constraint::Min(0),
+        ])
+        .areas(body_area);
+        f.render_widget(Block::default().bg(colors.buffer_bg), body_area);
+        f.render_widget(
+            Paragraph::new("没有匹配的结果")
+                .alignment(Alignment::Center)
+                .style(Style::default().fg(Color::DarkGray).bg(colors.buffer_bg)),
+            message_area,
+        );
+    }
+
+    fn render_scrollbar(&self, f: &mut Frame, area: Rect) {
+        f.render_stateful_widget(
+            Scrollbar::default()
+                .orientation(ScrollbarOrientation::VerticalRight)
+                .begin_symbol(None)
+                .end_symbol(None),
+            area.inner(&Margin {
+                vertical: 1,
+                horizontal: 1,
+            }),
+            &mut self.scroll_state.clone(),
+        );
+    }
+}
+
+impl<T: TableRow> DrawableComponent for DataTable<T> {
+    fn draw(&self, f: &mut Frame, area: Rect, _focused: bool) -> anyhow::Result<()> {
+        let colors = self.theme.table_colors(self.color_index);
+        self.area.set(area);
+        // 去掉表头后一页能放下的行数
+        self.page_rows
+            .set((area.height.saturating_sub(1) as usize / self.density.height()).max(1));
+        if self.items.is_empty() {
+            self.render_empty(f, area, &colors);
+        } else {
+            self.render_table(f, area, &colors);
+            self.render_scrollbar(f, area);
+        }
+        Ok(())
+    }
+}
+
+impl<T: TableRow> Component for DataTable<T> {
+    fn commands(&self, out: &mut Vec<CommandInfo>) {
+        out.push(CommandInfo::new(command::columns(&self.key_config)));
+        out.push(CommandInfo::new(command::move_column(&self.key_config)));
+        out.push(
+            CommandInfo::new(command::switch_palette(&self.key_config))
+                .action("Switch table palette", Action::Keys(vec![self.key_config.switch_palette])),
+        );
+        out.push(CommandInfo::new(command::mark_rows(&self.key_config)));
+        let mut copy = CommandInfo::new(command::copy(&self.key_config));
+        for format in YankFormat::ALL {
+            copy = copy.action(
+                format!("Copy {}", format.name()),
+                Action::Keys(vec![self.key_config.copy, format.key(&self.key_config)]),
+            );
+        }
+        out.push(copy);
+        out.push(
+            CommandInfo::new(command::batch_actions(&self.key_config))
+                .action("Export rows to csv", Action::Keys(vec![self.key_config.export_rows]))
+                .action("Copy IPs of marked rows", Action::Keys(vec![self.key_config.copy_ips]))
+                .action("Narrow to marked rows", Action::Keys(vec![self.key_config.narrow_marked])),
+        );
+    }
+
+    fn event(&mut self, key: Key) -> anyhow::Result<EventState> {
+        let pending_top = std::mem::take(&mut self.pending_top);
+        let page = self.page_rows.get();
+        if key == self.key_config.scroll_down || key == self.key_config.move_down {
+            self.step(true);
+        } else if key == self.key_config.scroll_up || key == self.key_config.move_up {
+            self.step(false);
+        } else if key == self.key_config.scroll_down_multiple_lines {
+            self.jump((page / 2).max(1), true);
+        } else if key == self.key_config.scroll_up_multiple_lines {
+            self.jump((page / 2).max(1), false);
+        } else if key == Key::PageDown {
+            self.jump(page, true);
+        } else if key == Key::PageUp {
+            self.jump(page, false);
+        } else if key == self.key_config.scroll_to_top || key == Key::Home {
+            // 字符键和 vim 一样要连按两次
+            if pending_top || !matches!(key, Key::Char(_)) {
+                self.select_index(0);
+            } else {
+                self.pending_top = true;
+            }
+        } else if key == self.key_config.scroll_to_bottom || key == Key::End {
+            self.select_index(usize::MAX);
+        } else if key == self.key_config.switch_palette {
+            self.next_color();
+        } else if key == self.key_config.mark {
+            self.toggle_mark();
+        } else if key == self.key_config.visual_mark {
+            self.toggle_visual();
+        } else if key == self.key_config.narrow_marked {
+            self.narrow();
+        } else if key == self.key_config.exit_popup
+            && (self.visual_anchor.is_some() || !self.marked.is_empty())
+        {
+            // 先退出区间标记, 再清空标记
+            if self.visual_anchor.take().is_none() {
+                self.marked.clear();
+            }
+        } else if let Some(action) = self.columns.event(key, &self.key_config) {
+            if action == ColumnAction::Sort {
+                self.sort();
+            }
+            if action != ColumnAction::Cursor {
+                self.columns.save(self.name)?;
+            }
+        } else {
+            return Ok(EventState::NotConsumed);
+        }
+        Ok(EventState::Consumed)
+    }
+
+    /// 单击选中行, 滚轮上下移动
+    fn mouse_event(&mut self, mouse: MouseEvent) -> anyhow::Result<EventState> {
+        let area = self.area.get();
+        if !area.contains(Position::new(mouse.column, mouse.row)) {
+            return Ok(EventState::NotConsumed);
+        }
+        match mouse.kind {
+            MouseEventKind::ScrollDown => self.jump(1, true),
+            MouseEventKind::ScrollUp => self.jump(1, false),
+            MouseEventKind::Down(MouseButton::Left) => match self.row_at(mouse.column, mouse.row) {
+                Some(index) => self.select_index(index),
+                None => return Ok(EventState::NotConsumed),
+            },
+            _ => return Ok(EventState::NotConsumed),
+        }
+        Ok(EventState::Consumed)
+    }
+}
+
+/// 含有逗号, 引号或换行的字段加引号
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, PartialEq, Debug)]
+    struct Item(usize);
+
+    impl TableRow for Item {
+        const COLUMNS: &'static [&'static str] = &["Id", "Name"];
+
+        fn cells(&self) -> Vec<String> {
+            vec![self.0.to_string(), format!("item{}", self.0)]
+        }
+
+        fn key(&self) -> String {
+            self.0.to_string()
+        }
+    }
+
+    fn table(count: usize) -> DataTable<Item> {
+        let mut table = DataTable {
+            name: "test",
+            items: Vec::new(),
+            cells: Vec::new(),
+            keys: Vec::new(),
+            content_widths: Vec::new(),
+            state: TableState::default(),
+            columns: ColumnLayout::default(),
+            scroll_state: ScrollbarState::default(),
+            page_rows: std::cell::Cell::new(10),
+            area: std::cell::Cell::new(Rect::new(0, 0, 80, 41)),
+            offset: std::cell::Cell::new(0),
+            density: Density::default(),
+            pending_top: false,
+            marked: HashSet::new(),
+            visual_anchor: None,
+            color_index: 0,
+            theme: Theme::default(),
+            key_config: KeyConfig::default(),
+        };
+        table.columns.normalize(Item::COLUMNS);
+        table.set_items((0..count).map(Item).collect());
+        table
+    }
+
+    fn selected(table: &DataTable<Item>) -> Option<usize> {
+        table.selected_item().map(|item| item.0)
+    }
+
+    #[test]
+    fn test_empty_table() {
+        let key = KeyConfig::default();
+        let mut table = table(0);
+        assert_eq!(selected(&table), None);
+        for k in [key.scroll_down, key.scroll_up, key.scroll_to_bottom, key.scroll_down_multiple_lines] {
+            assert!(table.event(k).unwrap().is_consumed());
+        }
+        assert_eq!(selected(&table), None);
+    }
+
+    #[test]
+    fn test_table_navigation() {
+        let key = KeyConfig::default();
+        let mut table = table(25);
+        table.event(key.scroll_up).unwrap();
+        assert_eq!(selected(&table), Some(24));
+        table.event(key.scroll_down).unwrap();
+        assert_eq!(selected(&table), Some(0));
+
+        table.event(key.scroll_down_multiple_lines).unwrap();
+        assert_eq!(selected(&table), Some(5));
+        table.event(Key::PageDown).unwrap();
+        assert_eq!(selected(&table), Some(15));
+        table.event(Key::PageDown).unwrap();
+        assert_eq!(selected(&table), Some(24));
+        table.event(key.scroll_up_multiple_lines).unwrap();
+        assert_eq!(selected(&table), Some(19));
+
+        table.event(key.scroll_to_top).unwrap();
+        assert_eq!(selected(&table), Some(19));
+        table.event(key.scroll_to_top).unwrap();
+        assert_eq!(selected(&table), Some(0));
+        table.event(key.scroll_to_bottom).unwrap();
+        assert_eq!(selected(&table), Some(24));
+
+        table.set_items(vec![Item(3)]);
+        assert_eq!(selected(&table), Some(3));
+    }
+
+    #[test]
+    fn test_mark_rows() {
+        let key = KeyConfig::default();
+        let mut table = table(10);
+        assert_eq!(table.targets(), vec![Item(0)]);
+
+        table.event(key.mark).unwrap();
+        table.event(key.mark).unwrap();
+        assert_eq!(selected(&table), Some(2));
+        table.event(key.scroll_up).unwrap();
+        table.event(key.mark).unwrap();
+        assert_eq!(table.targets(), vec![Item(0)]);
+
+        table.event(key.scroll_down_multiple_lines).unwrap();
+        table.event(key.visual_mark).unwrap();
+        table.event(key.scroll_down).unwrap();
+        table.event(key.scroll_down).unwrap();
+        assert!(table.is_marked(8) && table.marked_count() == 1);
+        table.event(key.visual_mark).unwrap();
+        assert_eq!(table.targets(), vec![Item(0), Item(7), Item(8), Item(9)]);
+
+        // 重新查询后仍然存在的行保持标记
+        table.set_items(vec![Item(9), Item(1), Item(0)]);
+        assert_eq!(table.targets(), vec![Item(9), Item(0)]);
+        table.event(key.narrow_marked).unwrap();
+        assert_eq!(table.items(), &[Item(9), Item(0)]);
+
+        assert!(table.event(key.exit_popup).unwrap().is_consumed());
+        assert_eq!(table.marked_count(), 0);
+        assert!(!table.event(key.exit_popup).unwrap().is_consumed());
+    }
+
+    #[test]
+    fn test_yank() {
+        let key = KeyConfig::default();
+        let mut table = table(3);
+        table.event(key.scroll_right).unwrap();
+        assert_eq!(table.yank(YankFormat::Cell), vec!["item0"]);
+        assert_eq!(table.yank(YankFormat::Row), vec!["0\titem0"]);
+        assert_eq!(table.yank(YankFormat::Column), vec!["item0", "item1", "item2"]);
+        assert!(table.yank(YankFormat::Ips).is_empty());
+
+        table.event(key.scroll_down).unwrap();
+        table.event(key.mark).unwrap();
+        table.event(key.mark).unwrap();
+        assert_eq!(table.yank(YankFormat::Column), vec!["item1", "item2"]);
+        let key_config = KeyConfig::default();
+        assert_eq!(
+            YankFormat::from_key(Key::Char('C'), &key_config),
+            Some(YankFormat::Column)
+        );
+        assert_eq!(YankFormat::from_key(Key::Char('x'), &key_config), None);
+    }
+
+    #[test]
+    fn test_mouse_select() {
+        let mut table = table(20);
+        let click = |column, row| MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column,
+            row,
+            modifiers: crossterm::event::KeyModifiers::NONE,
+        };
+        // 第 0 行是表头, 每行占 4 行高度
+        assert!(!table.mouse_event(click(5, 0)).unwrap().is_consumed());
+        table.mouse_event(click(5, 9)).unwrap();
+        assert_eq!(selected(&table), Some(2));
+        table.offset.set(10);
+        table.mouse_event(click(5, 1)).unwrap();
+        assert_eq!(selected(&table), Some(10));
+        assert!(!table.mouse_event(click(5, 45)).unwrap().is_consumed());
+
+        let wheel = MouseEvent {
+            kind: MouseEventKind::ScrollDown,
+            ..click(5, 5)
+        };
+        table.mouse_event(wheel).unwrap();
+        assert_eq!(selected(&table), Some(11));
+    }
+
+    #[test]
+    fn test_density() {
+        let mut table = table(20);
+        table.set_density(Density::Compact);
+        let click = MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column: 5,
+            row: 4,
+            modifiers: crossterm::event::KeyModifiers::NONE,
+        };
+        table.mouse_event(click).unwrap();
+        assert_eq!(selected(&table), Some(3));
+        assert_eq!(Density::Spacious.next(), Density::Compact);
+    }
+
+    #[test]
+    fn test_csv_field() {
+        assert_eq!(csv_field("10.0.0.1"), "10.0.0.1");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+}
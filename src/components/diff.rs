@@ -1,1017 +1,481 @@
-use super::{
-	utils::scroll_horizontal::HorizontalScroll,
-	utils::scroll_vertical::VerticalScroll, CommandBlocking,
-	Direction, DrawableComponent, HorizontalScrollType, ScrollType,
-};
-use crate::{
-	app::Environment,
-	components::{CommandInfo, Component, EventState},
-	keys::{key_match, SharedKeyConfig},
-	options::SharedOptions,
-	queue::{Action, InternalEvent, NeedsUpdate, Queue, ResetItem},
-	string_utils::tabs_to_spaces,
-	string_utils::trim_offset,
-	strings, try_or_popup,
-	ui::style::SharedTheme,
-};
+use super::command::{self, Action, CommandInfo};
+use super::syntax_text::{MoveSelection, SyntaxText};
+use super::{Component, DrawableComponent, EventState};
+use crate::config::KeyConfig;
+use crate::event::Key;
+use crate::theme::Theme;
+use crate::tools::diff::{diff_hunks, DiffLine, DiffTag, Hunk};
+use crate::ui::scrollbar::{draw_scrollbar, Orientation};
 use anyhow::Result;
-use asyncgit::{
-	hash,
-	sync::{self, diff::DiffLinePosition, RepoPathRef},
-	DiffLine, DiffLineType, FileDiff,
-};
-use bytesize::ByteSize;
-use crossterm::event::Event;
 use ratatui::{
-	layout::Rect,
-	symbols,
-	text::{Line, Span},
-	widgets::{Block, Borders, Paragraph},
-	Frame,
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{
+        block::{Position, Title},
+        Block, Borders, Paragraph,
+    },
+    Frame,
 };
-use std::{borrow::Cow, cell::Cell, cmp, path::Path};
+use std::cell::Cell;
+use std::ops::Range;
+use std::path::Path;
+use unicode_width::UnicodeWidthStr;
 
-#[derive(Default)]
-struct Current {
-	path: String,
-	is_stage: bool,
-	hash: u64,
-}
+/// 每段修改前后显示的上下文行数
+const DIFF_CONTEXT: usize = 3;
+/// 每次水平滚动的列数
+const HSCROLL_STEP: u16 = 8;
 
-///
+/// 差异视图中的一行
 #[derive(Clone, Copy)]
-enum Selection {
-	Single(usize),
-	Multiple(usize, usize),
-}
-
-impl Selection {
-	const fn get_start(&self) -> usize {
-		match self {
-			Self::Single(start) | Self::Multiple(start, _) => *start,
-		}
-	}
-
-	const fn get_end(&self) -> usize {
-		match self {
-			Self::Single(end) | Self::Multiple(_, end) => *end,
-		}
-	}
-
-	fn get_top(&self) -> usize {
-		match self {
-			Self::Single(start) => *start,
-			Self::Multiple(start, end) => cmp::min(*start, *end),
-		}
-	}
-
-	fn get_bottom(&self) -> usize {
-		match self {
-			Self::Single(start) => *start,
-			Self::Multiple(start, end) => cmp::max(*start, *end),
-		}
-	}
-
-	fn modify(&mut self, direction: Direction, max: usize) {
-		let start = self.get_start();
-		let old_end = self.get_end();
-
-		*self = match direction {
-			Direction::Up => {
-				Self::Multiple(start, old_end.saturating_sub(1))
-			}
-
-			Direction::Down => {
-				Self::Multiple(start, cmp::min(old_end + 1, max))
-			}
-		};
-	}
-
-	fn contains(&self, index: usize) -> bool {
-		match self {
-			Self::Single(start) => index == *start,
-			Self::Multiple(start, end) => {
-				if start <= end {
-					*start <= index && index <= *end
-				} else {
-					*end <= index && index <= *start
-				}
-			}
-		}
-	}
+enum DiffRow {
+    /// 第几段修改的开头
+    Header(usize),
+    Line(DiffLine),
 }
 
-///
+/// 两个文件的差异, 支持统一视图和并排视图, 两边的内容都带语法高亮
 pub struct DiffComponent {
-	repo: RepoPathRef,
-	diff: Option<FileDiff>,
-	longest_line: usize,
-	pending: bool,
-	selection: Selection,
-	selected_hunk: Option<usize>,
-	current_size: Cell<(u16, u16)>,
-	focused: bool,
-	current: Current,
-	vertical_scroll: VerticalScroll,
-	horizontal_scroll: HorizontalScroll,
-	queue: Queue,
-	theme: SharedTheme,
-	key_config: SharedKeyConfig,
-	is_immutable: bool,
-	options: SharedOptions,
+    old: Option<SyntaxText>,
+    new: Option<SyntaxText>,
+    hunks: Vec<Hunk>,
+    rows: Vec<DiffRow>,
+    side_by_side: bool,
+    /// 当前所在的修改段
+    current: usize,
+    top: Cell<usize>,
+    height: Cell<usize>,
+    scroll_x: u16,
+    /// 两个文件中最长一行的宽度
+    max_width: u16,
+    visible: bool,
+    theme: Theme,
+    key_config: KeyConfig,
 }
 
 impl DiffComponent {
-	///
-	pub fn new(env: &Environment, is_immutable: bool) -> Self {
-		Self {
-			focused: false,
-			queue: env.queue.clone(),
-			current: Current::default(),
-			pending: false,
-			selected_hunk: None,
-			diff: None,
-			longest_line: 0,
-			current_size: Cell::new((0, 0)),
-			selection: Selection::Single(0),
-			vertical_scroll: VerticalScroll::new(),
-			horizontal_scroll: HorizontalScroll::new(),
-			theme: env.theme.clone(),
-			key_config: env.key_config.clone(),
-			is_immutable,
-			repo: env.repo.clone(),
-			options: env.options.clone(),
-		}
-	}
-	///
-	fn can_scroll(&self) -> bool {
-		self.diff.as_ref().is_some_and(|diff| diff.lines > 1)
-	}
-	///
-	pub fn current(&self) -> (String, bool) {
-		(self.current.path.clone(), self.current.is_stage)
-	}
-	///
-	pub fn clear(&mut self, pending: bool) {
-		self.current = Current::default();
-		self.diff = None;
-		self.longest_line = 0;
-		self.vertical_scroll.reset();
-		self.horizontal_scroll.reset();
-		self.selection = Selection::Single(0);
-		self.selected_hunk = None;
-		self.pending = pending;
-	}
-	///
-	pub fn update(
-		&mut self,
-		path: String,
-		is_stage: bool,
-		diff: FileDiff,
-	) {
-		self.pending = false;
-
-		let hash = hash(&diff);
-
-		if self.current.hash != hash {
-			let reset_selection = self.current.path != path;
-
-			self.current = Current {
-				path,
-				is_stage,
-				hash,
-			};
-
-			self.diff = Some(diff);
-
-			self.longest_line = self
-				.diff
-				.iter()
-				.flat_map(|diff| diff.hunks.iter())
-				.flat_map(|hunk| hunk.lines.iter())
-				.map(|line| {
-					let converted_content = tabs_to_spaces(
-						line.content.as_ref().to_string(),
-					);
-
-					converted_content.len()
-				})
-				.max()
-				.map_or(0, |len| {
-					// Each hunk uses a 1-character wide vertical bar to its left to indicate
-					// selection.
-					len + 1
-				});
-
-			if reset_selection {
-				self.vertical_scroll.reset();
-				self.selection = Selection::Single(0);
-				self.update_selection(0);
-			} else {
-				let old_selection = match self.selection {
-					Selection::Single(line) => line,
-					Selection::Multiple(start, _) => start,
-				};
-				self.update_selection(old_selection);
-			}
-		}
-	}
-
-	fn move_selection(&mut self, move_type: ScrollType) {
-		if let Some(diff) = &self.diff {
-			let max = diff.lines.saturating_sub(1);
-
-			let new_start = match move_type {
-				ScrollType::Down => {
-					self.selection.get_bottom().saturating_add(1)
-				}
-				ScrollType::Up => {
-					self.selection.get_top().saturating_sub(1)
-				}
-				ScrollType::Home => 0,
-				ScrollType::End => max,
-				ScrollType::PageDown => {
-					self.selection.get_bottom().saturating_add(
-						self.current_size.get().1.saturating_sub(1)
-							as usize,
-					)
-				}
-				ScrollType::PageUp => {
-					self.selection.get_top().saturating_sub(
-						self.current_size.get().1.saturating_sub(1)
-							as usize,
-					)
-				}
-			};
-
-			self.update_selection(new_start);
-		}
-	}
-
-	fn update_selection(&mut self, new_start: usize) {
-		if let Some(diff) = &self.diff {
-			let max = diff.lines.saturating_sub(1);
-			let new_start = cmp::min(max, new_start);
-			self.selection = Selection::Single(new_start);
-			self.selected_hunk =
-				Self::find_selected_hunk(diff, new_start);
-		}
-	}
-
-	fn lines_count(&self) -> usize {
-		self.diff.as_ref().map_or(0, |diff| diff.lines)
-	}
-
-	fn max_scroll_right(&self) -> usize {
-		self.longest_line
-			.saturating_sub(self.current_size.get().0.into())
-	}
-
-	fn modify_selection(&mut self, direction: Direction) {
-		if self.diff.is_some() {
-			self.selection.modify(direction, self.lines_count());
-		}
-	}
-
-	fn copy_selection(&self) {
-		if let Some(diff) = &self.diff {
-			let lines_to_copy: Vec<&str> =
-				diff.hunks
-					.iter()
-					.flat_map(|hunk| hunk.lines.iter())
-					.enumerate()
-					.filter_map(|(i, line)| {
-						if self.selection.contains(i) {
-							Some(line.content.trim_matches(|c| {
-								c == '\n' || c == '\r'
-							}))
-						} else {
-							None
-						}
-					})
-					.collect();
-
-			try_or_popup!(
-				self,
-				"copy to clipboard error:",
-				crate::clipboard::copy_string(
-					&lines_to_copy.join("\n")
-				)
-			);
-		}
-	}
-
-	fn find_selected_hunk(
-		diff: &FileDiff,
-		line_selected: usize,
-	) -> Option<usize> {
-		let mut line_cursor = 0_usize;
-		for (i, hunk) in diff.hunks.iter().enumerate() {
-			let hunk_len = hunk.lines.len();
-			let hunk_min = line_cursor;
-			let hunk_max = line_cursor + hunk_len;
-
-			let hunk_selected =
-				hunk_min <= line_selected && hunk_max > line_selected;
-
-			if hunk_selected {
-				return Some(i);
-			}
-
-			line_cursor += hunk_len;
-		}
-
-		None
-	}
-
-	fn get_text(&self, width: u16, height: u16) -> Vec<Line> {
-		if let Some(diff) = &self.diff {
-			return if diff.hunks.is_empty() {
-				self.get_text_binary(diff)
-			} else {
-				let mut res: Vec<Line> = Vec::new();
-
-				let min = self.vertical_scroll.get_top();
-				let max = min + height as usize;
-
-				let mut line_cursor = 0_usize;
-				let mut lines_added = 0_usize;
-
-				for (i, hunk) in diff.hunks.iter().enumerate() {
-					let hunk_selected = self.focused()
-						&& self
-							.selected_hunk
-							.map_or(false, |s| s == i);
-
-					if lines_added >= height as usize {
-						break;
-					}
-
-					let hunk_len = hunk.lines.len();
-					let hunk_min = line_cursor;
-					let hunk_max = line_cursor + hunk_len;
-
-					if Self::hunk_visible(
-						hunk_min, hunk_max, min, max,
-					) {
-						for (i, line) in hunk.lines.iter().enumerate()
-						{
-							if line_cursor >= min
-								&& line_cursor <= max
-							{
-								res.push(Self::get_line_to_add(
-									width,
-									line,
-									self.focused()
-										&& self
-											.selection
-											.contains(line_cursor),
-									hunk_selected,
-									i == hunk_len - 1,
-									&self.theme,
-									self.horizontal_scroll
-										.get_right(),
-								));
-								lines_added += 1;
-							}
-
-							line_cursor += 1;
-						}
-					} else {
-						line_cursor += hunk_len;
-					}
-				}
-
-				res
-			};
-		}
-
-		vec![]
-	}
-
-	fn get_text_binary(&self, diff: &FileDiff) -> Vec<Line> {
-		let is_positive = diff.size_delta >= 0;
-		let delta_byte_size =
-			ByteSize::b(diff.size_delta.unsigned_abs());
-		let sign = if is_positive { "+" } else { "-" };
-		vec![Line::from(vec![
-			Span::raw(Cow::from("size: ")),
-			Span::styled(
-				Cow::from(format!("{}", ByteSize::b(diff.sizes.0))),
-				self.theme.text(false, false),
-			),
-			Span::raw(Cow::from(" -> ")),
-			Span::styled(
-				Cow::from(format!("{}", ByteSize::b(diff.sizes.1))),
-				self.theme.text(false, false),
-			),
-			Span::raw(Cow::from(" (")),
-			Span::styled(
-				Cow::from(format!("{sign}{delta_byte_size:}")),
-				self.theme.diff_line(
-					if is_positive {
-						DiffLineType::Add
-					} else {
-						DiffLineType::Delete
-					},
-					false,
-				),
-			),
-			Span::raw(Cow::from(")")),
-		])]
-	}
-
-	fn get_line_to_add<'a>(
-		width: u16,
-		line: &'a DiffLine,
-		selected: bool,
-		selected_hunk: bool,
-		end_of_hunk: bool,
-		theme: &SharedTheme,
-		scrolled_right: usize,
-	) -> Line<'a> {
-		let style = theme.diff_hunk_marker(selected_hunk);
-
-		let is_content_line =
-			matches!(line.line_type, DiffLineType::None);
-
-		let left_side_of_line = if end_of_hunk {
-			Span::styled(Cow::from(symbols::line::BOTTOM_LEFT), style)
-		} else {
-			match line.line_type {
-				DiffLineType::Header => Span::styled(
-					Cow::from(symbols::line::TOP_LEFT),
-					style,
-				),
-				_ => Span::styled(
-					Cow::from(symbols::line::VERTICAL),
-					style,
-				),
-			}
-		};
-
-		let content =
-			if !is_content_line && line.content.as_ref().is_empty() {
-				theme.line_break()
-			} else {
-				tabs_to_spaces(line.content.as_ref().to_string())
-			};
-		let content = trim_offset(&content, scrolled_right);
-
-		let filled = if selected {
-			// selected line
-			format!("{content:w$}\n", w = width as usize)
-		} else {
-			// weird eof missing eol line
-			format!("{content}\n")
-		};
-
-		Line::from(vec![
-			left_side_of_line,
-			Span::styled(
-				Cow::from(filled),
-				theme.diff_line(line.line_type, selected),
-			),
-		])
-	}
-
-	const fn hunk_visible(
-		hunk_min: usize,
-		hunk_max: usize,
-		min: usize,
-		max: usize,
-	) -> bool {
-		// full overlap
-		if hunk_min <= min && hunk_max >= max {
-			return true;
-		}
-
-		// partly overlap
-		if (hunk_min >= min && hunk_min <= max)
-			|| (hunk_max >= min && hunk_max <= max)
-		{
-			return true;
-		}
-
-		false
-	}
-
-	fn unstage_hunk(&mut self) -> Result<()> {
-		if let Some(diff) = &self.diff {
-			if let Some(hunk) = self.selected_hunk {
-				let hash = diff.hunks[hunk].header_hash;
-				sync::unstage_hunk(
-					&self.repo.borrow(),
-					&self.current.path,
-					hash,
-					Some(self.options.borrow().diff_options()),
-				)?;
-				self.queue_update();
-			}
-		}
-
-		Ok(())
-	}
-
-	fn stage_hunk(&mut self) -> Result<()> {
-		if let Some(diff) = &self.diff {
-			if let Some(hunk) = self.selected_hunk {
-				if diff.untracked {
-					sync::stage_add_file(
-						&self.repo.borrow(),
-						Path::new(&self.current.path),
-					)?;
-				} else {
-					let hash = diff.hunks[hunk].header_hash;
-					sync::stage_hunk(
-						&self.repo.borrow(),
-						&self.current.path,
-						hash,
-						Some(self.options.borrow().diff_options()),
-					)?;
-				}
-
-				self.queue_update();
-			}
-		}
-
-		Ok(())
-	}
-
-	fn queue_update(&self) {
-		self.queue.push(InternalEvent::Update(NeedsUpdate::ALL));
-	}
-
-	fn reset_hunk(&self) {
-		if let Some(diff) = &self.diff {
-			if let Some(hunk) = self.selected_hunk {
-				let hash = diff.hunks[hunk].header_hash;
-
-				self.queue.push(InternalEvent::ConfirmAction(
-					Action::ResetHunk(
-						self.current.path.clone(),
-						hash,
-					),
-				));
-			}
-		}
-	}
-
-	fn reset_lines(&self) {
-		self.queue.push(InternalEvent::ConfirmAction(
-			Action::ResetLines(
-				self.current.path.clone(),
-				self.selected_lines(),
-			),
-		));
-	}
-
-	fn stage_lines(&self) {
-		if let Some(diff) = &self.diff {
-			//TODO: support untracked files as well
-			if !diff.untracked {
-				let selected_lines = self.selected_lines();
-
-				try_or_popup!(
-					self,
-					"(un)stage lines:",
-					sync::stage_lines(
-						&self.repo.borrow(),
-						&self.current.path,
-						self.is_stage(),
-						&selected_lines,
-					)
-				);
-
-				self.queue_update();
-			}
-		}
-	}
-
-	fn selected_lines(&self) -> Vec<DiffLinePosition> {
-		self.diff
-			.as_ref()
-			.map(|diff| {
-				diff.hunks
-					.iter()
-					.flat_map(|hunk| hunk.lines.iter())
-					.enumerate()
-					.filter_map(|(i, line)| {
-						let is_add_or_delete = line.line_type
-							== DiffLineType::Add
-							|| line.line_type == DiffLineType::Delete;
-						if self.selection.contains(i)
-							&& is_add_or_delete
-						{
-							Some(line.position)
-						} else {
-							None
-						}
-					})
-					.collect()
-			})
-			.unwrap_or_default()
-	}
-
-	fn reset_untracked(&self) {
-		self.queue.push(InternalEvent::ConfirmAction(Action::Reset(
-			ResetItem {
-				path: self.current.path.clone(),
-			},
-		)));
-	}
-
-	fn stage_unstage_hunk(&mut self) -> Result<()> {
-		if self.current.is_stage {
-			self.unstage_hunk()?;
-		} else {
-			self.stage_hunk()?;
-		}
-
-		Ok(())
-	}
-
-	fn calc_hunk_move_target(
-		&self,
-		direction: isize,
-	) -> Option<usize> {
-		let diff = self.diff.as_ref()?;
-		if diff.hunks.is_empty() {
-			return None;
-		}
-		let max = diff.hunks.len() - 1;
-		let target_index = self.selected_hunk.map_or(0, |i| {
-			let target = if direction >= 0 {
-				i.saturating_add(direction.unsigned_abs())
-			} else {
-				i.saturating_sub(direction.unsigned_abs())
-			};
-			std::cmp::min(max, target)
-		});
-		Some(target_index)
-	}
-
-	fn diff_hunk_move_up_down(&mut self, direction: isize) {
-		let Some(diff) = &self.diff else { return };
-		let hunk_index = self.calc_hunk_move_target(direction);
-		// return if selected_hunk not change
-		if self.selected_hunk == hunk_index {
-			return;
-		}
-		if let Some(hunk_index) = hunk_index {
-			let line_index = diff
-				.hunks
-				.iter()
-				.take(hunk_index)
-				.fold(0, |sum, hunk| sum + hunk.lines.len());
-			let hunk = &diff.hunks[hunk_index];
-			self.selection = Selection::Single(line_index);
-			self.selected_hunk = Some(hunk_index);
-			self.vertical_scroll.move_area_to_visible(
-				self.current_size.get().1 as usize,
-				line_index,
-				line_index.saturating_add(hunk.lines.len()),
-			);
-		}
-	}
-
-	const fn is_stage(&self) -> bool {
-		self.current.is_stage
-	}
+    pub fn new(key_config: KeyConfig, theme: Theme) -> Self {
+        Self {
+            old: None,
+            new: None,
+            hunks: Vec::new(),
+            rows: Vec::new(),
+            side_by_side: true,
+            current: 0,
+            top: Cell::new(0),
+            height: Cell::new(0),
+            scroll_x: 0,
+            max_width: 0,
+            visible: false,
+            theme,
+            key_config,
+        }
+    }
+
+    /// old 显示在左边, new 显示在右边
+    pub fn load(&mut self, old: &Path, new: &Path) -> Result<()> {
+        let old = SyntaxText::open(old, self.theme.syntax_theme())?;
+        let new = SyntaxText::open(new, self.theme.syntax_theme())?;
+        self.hunks = diff_hunks(old.text(), new.text(), DIFF_CONTEXT);
+        self.max_width = [&old, &new]
+            .iter()
+            .flat_map(|text| text.text().lines())
+            .map(|line| u16::try_from(line.width()).unwrap_or(u16::MAX))
+            .max()
+            .unwrap_or_default();
+        self.old = Some(old);
+        self.new = Some(new);
+        self.current = 0;
+        self.top.set(0);
+        self.scroll_x = 0;
+        self.build_rows();
+        Ok(())
+    }
+
+    pub fn clear(&mut self) {
+        self.old = None;
+        self.new = None;
+        self.hunks.clear();
+        self.rows.clear();
+    }
+
+    /// 切换主题后两边的内容按新的语法高亮主题重新高亮, 比较结果不变
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+        for text in [&mut self.old, &mut self.new].into_iter().flatten() {
+            text.set_theme(self.theme.syntax_theme());
+        }
+    }
+
+    fn build_rows(&mut self) {
+        self.rows.clear();
+        for (index, hunk) in self.hunks.iter().enumerate() {
+            self.rows.push(DiffRow::Header(index));
+            let lines = if self.side_by_side {
+                hunk.side_by_side()
+            } else {
+                hunk.lines.clone()
+            };
+            self.rows.extend(lines.into_iter().map(DiffRow::Line));
+        }
+    }
+
+    fn hunk_row(&self, hunk: usize) -> usize {
+        self.rows
+            .iter()
+            .position(|row| matches!(row, DiffRow::Header(h) if *h == hunk))
+            .unwrap_or_default()
+    }
+
+    fn jump(&mut self, forward: bool) {
+        let count = self.hunks.len();
+        if count == 0 {
+            return;
+        }
+        self.current = if forward {
+            (self.current + 1) % count
+        } else {
+            (self.current + count - 1) % count
+        };
+        self.top.set(self.hunk_row(self.current));
+    }
+
+    /// 切换后停在当前修改段的开头
+    fn toggle_layout(&mut self) {
+        self.side_by_side = !self.side_by_side;
+        self.build_rows();
+        self.top.set(self.hunk_row(self.current));
+    }
+
+    fn max_top(&self) -> usize {
+        self.rows.len().saturating_sub(self.height.get())
+    }
+
+    fn scroll(&mut self, nav: MoveSelection) {
+        let top = self.top.get();
+        let height = self.height.get();
+        let new_top = match nav {
+            MoveSelection::Down => top.saturating_add(1),
+            MoveSelection::Up => top.saturating_sub(1),
+            MoveSelection::Top => 0,
+            MoveSelection::End => self.max_top(),
+            MoveSelection::PageUp => top.saturating_sub(height),
+            MoveSelection::PageDown => top.saturating_add(height),
+            MoveSelection::Left => {
+                self.scroll_x = self.scroll_x.saturating_sub(HSCROLL_STEP);
+                return;
+            }
+            MoveSelection::Right => {
+                self.scroll_x = self.scroll_x.saturating_add(HSCROLL_STEP).min(self.max_width);
+                return;
+            }
+        };
+        let new_top = new_top.min(self.max_top());
+        self.top.set(new_top);
+        // 当前修改段跟着滚动位置走
+        self.current = self
+            .rows
+            .get(..=new_top)
+            .unwrap_or_default()
+            .iter()
+            .rev()
+            .find_map(|row| match row {
+                DiffRow::Header(hunk) => Some(*hunk),
+                DiffRow::Line(_) => None,
+            })
+            .unwrap_or_default();
+    }
+
+    fn title(&self) -> String {
+        let name = |text: &Option<SyntaxText>| {
+            text.as_ref()
+                .map(|text| text.path().display().to_string())
+                .unwrap_or_default()
+        };
+        let state = if self.hunks.is_empty() {
+            "[相同]".to_string()
+        } else {
+            format!("[{}/{}]", self.current + 1, self.hunks.len())
+        };
+        format!("{} ↔ {} {}", name(&self.old), name(&self.new), state)
+    }
+
+    fn bottom_title(&self) -> String {
+        format!(
+            " [{}/{}] hunk [{}] {} ",
+            self.key_config.search_next,
+            self.key_config.search_prev,
+            self.key_config.toggle_diff_layout,
+            if self.side_by_side { "unified" } else { "side-by-side" }
+        )
+    }
+
+    /// 修改行的背景色, mono 模式下只靠行号旁边的 -/+ 区分
+    fn change_style(&self, tag: DiffTag) -> Style {
+        let color = match tag {
+            DiffTag::Delete => Color::Rgb(80, 30, 30),
+            DiffTag::Insert => Color::Rgb(30, 70, 30),
+            DiffTag::Replace => Color::Rgb(70, 60, 20),
+            DiffTag::Equal => return Style::default(),
+        };
+        match self.theme.accent(color) {
+            Color::Reset => Style::default(),
+            color => Style::default().bg(color),
+        }
+    }
+
+    fn sign_style(&self, tag: DiffTag) -> Style {
+        let color = match tag {
+            DiffTag::Delete => Color::Red,
+            DiffTag::Insert => Color::Green,
+            DiffTag::Replace => Color::Yellow,
+            DiffTag::Equal => Color::DarkGray,
+        };
+        Style::default().fg(self.theme.accent(color))
+    }
+
+    /// 只高亮可见的行
+    fn request(&self, rows: &[DiffRow]) {
+        let range = |lines: &mut dyn Iterator<Item = usize>| -> Option<Range<usize>> {
+            let (min, max) = lines.fold(None, |acc: Option<(usize, usize)>, line| {
+                Some(acc.map_or((line, line), |(min, max)| (min.min(line), max.max(line))))
+            })?;
+            Some(min..max + 1)
+        };
+        let lines = || {
+            rows.iter().filter_map(|row| match row {
+                DiffRow::Line(line) => Some(*line),
+                DiffRow::Header(_) => None,
+            })
+        };
+        if let (Some(text), Some(range)) = (&self.old, range(&mut lines().filter_map(|l| l.old))) {
+            text.request(range);
+        }
+        if let (Some(text), Some(range)) = (&self.new, range(&mut lines().filter_map(|l| l.new))) {
+            text.request(range);
+        }
+    }
+
+    fn draw_unified(&self, f: &mut Frame, area: Rect, rows: &[DiffRow], width: usize) {
+        let gutter = u16::try_from(width * 2 + 4).unwrap_or(u16::MAX);
+        let [gutter_area, content_area] =
+            Layout::horizontal([Constraint::Length(gutter), Constraint::Min(0)]).areas(area);
+        let (mut numbers, mut contents) = (Vec::new(), Vec::new());
+        for row in rows {
+            match row {
+                DiffRow::Header(hunk) => {
+                    numbers.push(Line::raw(""));
+                    contents.push(self.header_line(*hunk));
+                }
+                DiffRow::Line(line) => {
+                    let number = |n: Option<usize>| n.map_or(String::new(), |n| (n + 1).to_string());
+                    let sign = match line.tag {
+                        DiffTag::Delete => "-",
+                        DiffTag::Insert => "+",
+                        _ => " ",
+                    };
+                    numbers.push(Line::from(vec![
+                        Span::styled(
+                            format!("{:>w$} {:>w$} ", number(line.old), number(line.new), w = width),
+                            Style::default().fg(Color::DarkGray),
+                        ),
+                        Span::styled(sign, self.sign_style(line.tag)),
+                    ]));
+                    let (text, number) = match line.tag {
+                        DiffTag::Delete => (&self.old, line.old),
+                        _ => (&self.new, line.new),
+                    };
+                    contents.push(self.content_line(text, number, line.tag));
+                }
+            }
+        }
+        f.render_widget(Paragraph::new(numbers), gutter_area);
+        f.render_widget(Paragraph::new(contents).scroll((0, self.scroll_x)), content_area);
+    }
+
+    fn draw_side_by_side(&self, f: &mut Frame, area: Rect, rows: &[DiffRow], width: usize) {
+        let [old_area, new_area] =
+            Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(area);
+        for (area, is_old) in [(old_area, true), (new_area, false)] {
+            let gutter = u16::try_from(width + 2).unwrap_or(u16::MAX);
+            let [gutter_area, content_area] =
+                Layout::horizontal([Constraint::Length(gutter), Constraint::Min(0)]).areas(area);
+            let (mut numbers, mut contents) = (Vec::new(), Vec::new());
+            for row in rows {
+                match row {
+                    DiffRow::Header(hunk) if is_old => {
+                        numbers.push(Line::raw(""));
+                        contents.push(self.header_line(*hunk));
+                    }
+                    DiffRow::Header(_) => {
+                        numbers.push(Line::raw(""));
+                        contents.push(Line::raw(""));
+                    }
+                    DiffRow::Line(line) => {
+                        let (text, number) = if is_old {
+                            (&self.old, line.old)
+                        } else {
+                            (&self.new, line.new)
+                        };
+                        let Some(number) = number else {
+                            numbers.push(Line::raw(""));
+                            contents.push(Line::raw(""));
+                            continue;
+                        };
+                        let tag = match line.tag {
+                            DiffTag::Replace | DiffTag::Equal => line.tag,
+                            _ if is_old => DiffTag::Delete,
+                            _ => DiffTag::Insert,
+                        };
+                        let sign = match tag {
+                            DiffTag::Delete => "-",
+                            DiffTag::Insert => "+",
+                            DiffTag::Replace => "~",
+                            DiffTag::Equal => " ",
+                        };
+                        numbers.push(Line::from(vec![
+                            Span::styled(
+                                format!("{:>w$}", number + 1, w = width),
+                                Style::default().fg(Color::DarkGray),
+                            ),
+                            Span::styled(sign, self.sign_style(tag)),
+                        ]));
+                        contents.push(self.content_line(text, Some(number), tag));
+                    }
+                }
+            }
+            f.render_widget(Paragraph::new(numbers), gutter_area);
+            f.render_widget(Paragraph::new(contents).scroll((0, self.scroll_x)), content_area);
+        }
+    }
+
+    fn header_line(&self, hunk: usize) -> Line<'_> {
+        let mut style = Style::default().fg(self.theme.accent(Color::Cyan));
+        if hunk == self.current {
+            style = style.add_modifier(Modifier::BOLD);
+        }
+        Line::styled(self.hunks[hunk].header(), style)
+    }
+
+    /// 语法高亮之上叠加修改的背景色
+    fn content_line<'a>(&self, text: &'a Option<SyntaxText>, number: Option<usize>, tag: DiffTag) -> Line<'a> {
+        let (Some(text), Some(number)) = (text, number) else {
+            return Line::raw("");
+        };
+        let content = text.line(number).unwrap_or_default();
+        let change = self.change_style(tag);
+        let spans: Vec<Span> = text
+            .styles(number)
+            .into_iter()
+            .map(|(style, range)| Span::styled(&content[range], style.patch(change)))
+            .collect();
+        Line::from(spans).style(change)
+    }
 }
 
 impl DrawableComponent for DiffComponent {
-	fn draw(&self, f: &mut Frame, r: Rect) -> Result<()> {
-		self.current_size.set((
-			r.width.saturating_sub(2),
-			r.height.saturating_sub(2),
-		));
-
-		let current_width = self.current_size.get().0;
-		let current_height = self.current_size.get().1;
-
-		self.vertical_scroll.update(
-			self.selection.get_end(),
-			self.lines_count(),
-			usize::from(current_height),
-		);
-
-		self.horizontal_scroll.update_no_selection(
-			self.longest_line,
-			current_width.into(),
-		);
-
-		let title = format!(
-			"{}{}",
-			strings::title_diff(&self.key_config),
-			self.current.path
-		);
-
-		let txt = if self.pending {
-			vec![Line::from(vec![Span::styled(
-				Cow::from(strings::loading_text(&self.key_config)),
-				self.theme.text(false, false),
-			)])]
-		} else {
-			self.get_text(r.width, current_height)
-		};
-
-		f.render_widget(
-			Paragraph::new(txt).block(
-				Block::default()
-					.title(Span::styled(
-						title.as_str(),
-						self.theme.title(self.focused()),
-					))
-					.borders(Borders::ALL)
-					.border_style(self.theme.block(self.focused())),
-			),
-			r,
-		);
-
-		if self.focused() {
-			self.vertical_scroll.draw(f, r, &self.theme);
-
-			if self.max_scroll_right() > 0 {
-				self.horizontal_scroll.draw(f, r, &self.theme);
-			}
-		}
-
-		Ok(())
-	}
+    fn draw(&self, f: &mut Frame, area: Rect, _focused: bool) -> Result<()> {
+        if !self.visible {
+            return Ok(());
+        }
+        let block = Block::default()
+            .title(self.title())
+            .title(Title::from(self.bottom_title()).position(Position::Bottom))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(self.theme.accent(Color::LightMagenta)));
+        let inner = block.inner(area);
+        f.render_widget(block, area);
+
+        self.height.set(usize::from(inner.height));
+        let top = self.top.get().min(self.max_top());
+        self.top.set(top);
+        if self.hunks.is_empty() {
+            f.render_widget(Paragraph::new(" 两个文件内容相同"), inner);
+            return Ok(());
+        }
+
+        let rows = &self.rows[top..(top + usize::from(inner.height)).min(self.rows.len())];
+        self.request(rows);
+        let line_count = [&self.old, &self.new]
+            .iter()
+            .filter_map(|text| text.as_ref().map(SyntaxText::line_count))
+            .max()
+            .unwrap_or_default();
+        let width = line_count.to_string().len();
+        if self.side_by_side {
+            self.draw_side_by_side(f, inner, rows, width);
+        } else {
+            self.draw_unified(f, inner, rows, width);
+        }
+
+        draw_scrollbar(
+            f,
+            area,
+            &self.theme.accent(Color::LightMagenta),
+            self.max_top(),
+            top,
+            Orientation::Vertical,
+        );
+        Ok(())
+    }
 }
 
 impl Component for DiffComponent {
-	fn commands(
-		&self,
-		out: &mut Vec<CommandInfo>,
-		_force_all: bool,
-	) -> CommandBlocking {
-		out.push(CommandInfo::new(
-			strings::commands::scroll(&self.key_config),
-			self.can_scroll(),
-			self.focused(),
-		));
-		out.push(CommandInfo::new(
-			strings::commands::diff_hunk_next(&self.key_config),
-			self.calc_hunk_move_target(1) != self.selected_hunk,
-			self.focused(),
-		));
-		out.push(CommandInfo::new(
-			strings::commands::diff_hunk_prev(&self.key_config),
-			self.calc_hunk_move_target(-1) != self.selected_hunk,
-			self.focused(),
-		));
-		out.push(
-			CommandInfo::new(
-				strings::commands::diff_home_end(&self.key_config),
-				self.can_scroll(),
-				self.focused(),
-			)
-			.hidden(),
-		);
-
-		if !self.is_immutable {
-			out.push(CommandInfo::new(
-				strings::commands::diff_hunk_remove(&self.key_config),
-				self.selected_hunk.is_some(),
-				self.focused() && self.is_stage(),
-			));
-			out.push(CommandInfo::new(
-				strings::commands::diff_hunk_add(&self.key_config),
-				self.selected_hunk.is_some(),
-				self.focused() && !self.is_stage(),
-			));
-			out.push(CommandInfo::new(
-				strings::commands::diff_hunk_revert(&self.key_config),
-				self.selected_hunk.is_some(),
-				self.focused() && !self.is_stage(),
-			));
-			out.push(CommandInfo::new(
-				strings::commands::diff_lines_revert(
-					&self.key_config,
-				),
-				//TODO: only if any modifications are selected
-				true,
-				self.focused() && !self.is_stage(),
-			));
-			out.push(CommandInfo::new(
-				strings::commands::diff_lines_stage(&self.key_config),
-				//TODO: only if any modifications are selected
-				true,
-				self.focused() && !self.is_stage(),
-			));
-			out.push(CommandInfo::new(
-				strings::commands::diff_lines_unstage(
-					&self.key_config,
-				),
-				//TODO: only if any modifications are selected
-				true,
-				self.focused() && self.is_stage(),
-			));
-		}
-
-		out.push(CommandInfo::new(
-			strings::commands::copy(&self.key_config),
-			true,
-			self.focused(),
-		));
-
-		CommandBlocking::PassingOn
-	}
-
-	#[allow(clippy::cognitive_complexity, clippy::too_many_lines)]
-	fn event(&mut self, ev: &Event) -> Result<EventState> {
-		if self.focused() {
-			if let Event::Key(e) = ev {
-				return if key_match(e, self.key_config.keys.move_down)
-				{
-					self.move_selection(ScrollType::Down);
-					Ok(EventState::Consumed)
-				} else if key_match(
-					e,
-					self.key_config.keys.shift_down,
-				) {
-					self.modify_selection(Direction::Down);
-					Ok(EventState::Consumed)
-				} else if key_match(e, self.key_config.keys.shift_up)
-				{
-					self.modify_selection(Direction::Up);
-					Ok(EventState::Consumed)
-				} else if key_match(e, self.key_config.keys.end) {
-					self.move_selection(ScrollType::End);
-					Ok(EventState::Consumed)
-				} else if key_match(e, self.key_config.keys.home) {
-					self.move_selection(ScrollType::Home);
-					Ok(EventState::Consumed)
-				} else if key_match(e, self.key_config.keys.move_up) {
-					self.move_selection(ScrollType::Up);
-					Ok(EventState::Consumed)
-				} else if key_match(e, self.key_config.keys.page_up) {
-					self.move_selection(ScrollType::PageUp);
-					Ok(EventState::Consumed)
-				} else if key_match(e, self.key_config.keys.page_down)
-				{
-					self.move_selection(ScrollType::PageDown);
-					Ok(EventState::Consumed)
-				} else if key_match(
-					e,
-					self.key_config.keys.move_right,
-				) {
-					self.horizontal_scroll
-						.move_right(HorizontalScrollType::Right);
-					Ok(EventState::Consumed)
-				} else if key_match(e, self.key_config.keys.move_left)
-				{
-					self.horizontal_scroll
-						.move_right(HorizontalScrollType::Left);
-					Ok(EventState::Consumed)
-				} else if key_match(
-					e,
-					self.key_config.keys.diff_hunk_next,
-				) {
-					self.diff_hunk_move_up_down(1);
-					Ok(EventState::Consumed)
-				} else if key_match(
-					e,
-					self.key_config.keys.diff_hunk_prev,
-				) {
-					self.diff_hunk_move_up_down(-1);
-					Ok(EventState::Consumed)
-				} else if key_match(
-					e,
-					self.key_config.keys.stage_unstage_item,
-				) && !self.is_immutable
-				{
-					try_or_popup!(
-						self,
-						"hunk error:",
-						self.stage_unstage_hunk()
-					);
-
-					Ok(EventState::Consumed)
-				} else if key_match(
-					e,
-					self.key_config.keys.status_reset_item,
-				) && !self.is_immutable
-					&& !self.is_stage()
-				{
-					if let Some(diff) = &self.diff {
-						if diff.untracked {
-							self.reset_untracked();
-						} else {
-							self.reset_hunk();
-						}
-					}
-					Ok(EventState::Consumed)
-				} else if key_match(
-					e,
-					self.key_config.keys.diff_stage_lines,
-				) && !self.is_immutable
-				{
-					self.stage_lines();
-					Ok(EventState::Consumed)
-				} else if key_match(
-					e,
-					self.key_config.keys.diff_reset_lines,
-				) && !self.is_immutable
-					&& !self.is_stage()
-				{
-					if let Some(diff) = &self.diff {
-						//TODO: reset untracked lines
-						if !diff.untracked {
-							self.reset_lines();
-						}
-					}
-					Ok(EventState::Consumed)
-				} else if key_match(e, self.key_config.keys.copy) {
-					self.copy_selection();
-					Ok(EventState::Consumed)
-				} else {
-					Ok(EventState::NotConsumed)
-				};
-			}
-		}
-
-		Ok(EventState::NotConsumed)
-	}
-
-	fn focused(&self) -> bool {
-		self.focused
-	}
-	fn focus(&mut self, focus: bool) {
-		self.focused = focus;
-	}
-}
-
-#[cfg(test)]
-mod tests {
-	use super::*;
-	use crate::ui::style::Theme;
-	use std::io::Write;
-	use std::rc::Rc;
-	use tempfile::NamedTempFile;
-
-	#[test]
-	fn test_line_break() {
-		let diff_line = DiffLine {
-			content: "".into(),
-			line_type: DiffLineType::Add,
-			position: Default::default(),
-		};
-
-		{
-			let default_theme = Rc::new(Theme::default());
-
-			assert_eq!(
-				DiffComponent::get_line_to_add(
-					4,
-					&diff_line,
-					false,
-					false,
-					false,
-					&default_theme,
-					0
-				)
-				.spans
-				.last()
-				.unwrap(),
-				&Span::styled(
-					Cow::from("¶\n"),
-					default_theme
-						.diff_line(diff_line.line_type, false)
-				)
-			);
-		}
-
-		{
-			let mut file = NamedTempFile::new().unwrap();
-
-			writeln!(
-				file,
-				r#"
-(
-	line_break: Some("+")
-)
-"#
-			)
-			.unwrap();
-
-			let theme =
-				Rc::new(Theme::init(&file.path().to_path_buf()));
-
-			assert_eq!(
-				DiffComponent::get_line_to_add(
-					4, &diff_line, false, false, false, &theme, 0
-				)
-				.spans
-				.last()
-				.unwrap(),
-				&Span::styled(
-					Cow::from("+\n"),
-					theme.diff_line(diff_line.line_type, false)
-				)
-			);
-		}
-	}
+    fn commands(&self, out: &mut Vec<CommandInfo>) {
+        out.push(
+            CommandInfo::new(command::diff_hunk(&self.key_config))
+                .action("Toggle side-by-side diff", Action::Keys(vec![self.key_config.toggle_diff_layout])),
+        );
+    }
+
+    fn event(&mut self, key: Key) -> Result<EventState> {
+        if !self.visible {
+            return Ok(EventState::NotConsumed);
+        }
+        let nav = match key {
+            _ if key == self.key_config.search_next => {
+                self.jump(true);
+                return Ok(EventState::Consumed);
+            }
+            _ if key == self.key_config.search_prev => {
+                self.jump(false);
+                return Ok(EventState::Consumed);
+            }
+            _ if key == self.key_config.toggle_diff_layout => {
+                self.toggle_layout();
+                return Ok(EventState::Consumed);
+            }
+            _ if key == self.key_config.scroll_up || key == self.key_config.move_up => MoveSelection::Up,
+            _ if key == self.key_config.scroll_down || key == self.key_config.move_down => {
+                MoveSelection::Down
+            }
+            _ if key == self.key_config.scroll_left || key == Key::Left => MoveSelection::Left,
+            _ if key == self.key_config.scroll_right || key == Key::Right => MoveSelection::Right,
+            _ if key == self.key_config.scroll_down_multiple_lines || key == Key::PageDown => {
+                MoveSelection::PageDown
+            }
+            _ if key == self.key_config.scroll_up_multiple_lines || key == Key::PageUp => {
+                MoveSelection::PageUp
+            }
+            _ if key == self.key_config.scroll_to_top || key == Key::Home => MoveSelection::Top,
+            _ if key == self.key_config.scroll_to_bottom || key == Key::End => MoveSelection::End,
+            _ => return Ok(EventState::NotConsumed),
+        };
+        self.scroll(nav);
+        Ok(EventState::Consumed)
+    }
+
+    fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    fn hide(&mut self) {
+        self.visible = false;
+    }
+
+    fn show(&mut self) -> Result<()> {
+        self.visible = true;
+        Ok(())
+    }
 }
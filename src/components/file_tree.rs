@@ -0,0 +1,221 @@
+use super::{Component, DrawableComponent, EventState};
+use crate::components::command::{self, Action, CommandInfo};
+use crate::config::KeyConfig;
+use crate::event::Key;
+use crate::theme::Theme;
+use crate::tools::file_tree::FileTree;
+use crate::tools::proc::ProcInfo;
+use anyhow::Result;
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState},
+    Frame,
+};
+use std::path::{Path, PathBuf};
+
+/// 进程工作目录和 ConfigPath 目录的文件树, 回车在文件查看中打开文件
+pub struct FileTreeComponent {
+    tree: FileTree,
+    state: ListState,
+    title: String,
+    /// 进程部署在本机时才能跟踪文件
+    local: bool,
+    visible: bool,
+    theme: Theme,
+    key_config: KeyConfig,
+}
+
+impl FileTreeComponent {
+    pub fn new(key_config: KeyConfig, theme: Theme) -> Self {
+        Self {
+            tree: FileTree::default(),
+            state: ListState::default(),
+            title: String::new(),
+            local: false,
+            visible: false,
+            theme,
+            key_config,
+        }
+    }
+
+    /// ConfigPath 是文件时使用所在的目录, 和 WorkPath 相同时不再单独列出
+    pub fn load(&mut self, proc: &ProcInfo, local: bool) -> Result<()> {
+        let work_path = PathBuf::from(&proc.work_path);
+        let mut roots = vec![(format!("WorkPath {}", work_path.display()), work_path.clone())];
+        if let Some(config_path) = &proc.attributes.config_path {
+            let path = work_path.join(config_path);
+            let dir = if path.is_file() {
+                path.parent().map_or(path.clone(), Path::to_path_buf)
+            } else {
+                path
+            };
+            if dir != work_path {
+                roots.push((format!("ConfigPath {}", dir.display()), dir));
+            }
+        }
+        self.tree = FileTree::new(roots)?;
+        self.state = ListState::default().with_selected(Some(0));
+        self.title = format!("{}({})", proc.funcname, proc.func_id);
+        self.local = local;
+        Ok(())
+    }
+
+    pub fn is_local(&self) -> bool {
+        self.local
+    }
+
+    /// 选中的文件, 选中目录时为 None
+    pub fn selected_file(&self) -> Option<&Path> {
+        let node = self.tree.node(self.state.selected()?)?;
+        (!node.is_dir).then_some(node.path.as_path())
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    /// 选中的是文件时返回路径, 目录则展开/折叠
+    pub fn enter(&mut self) -> Result<Option<PathBuf>> {
+        let Some(index) = self.state.selected() else {
+            return Ok(None);
+        };
+        let Some(node) = self.tree.node(index) else {
+            return Ok(None);
+        };
+        if !node.is_dir {
+            return Ok(Some(node.path.clone()));
+        }
+        if node.expanded {
+            self.tree.collapse(index);
+        } else {
+            self.tree.expand(index)?;
+        }
+        Ok(None)
+    }
+
+    fn step(&mut self, forward: bool) {
+        let count = self.tree.nodes().len();
+        if count == 0 {
+            return;
+        }
+        let i = self.state.selected().map_or(0, |i| {
+            if forward {
+                (i + 1) % count
+            } else {
+                (i + count - 1) % count
+            }
+        });
+        self.state.select(Some(i));
+    }
+
+    /// 已展开的目录折叠, 否则跳到父目录
+    fn collapse(&mut self) {
+        let Some(index) = self.state.selected() else {
+            return;
+        };
+        if self.tree.node(index).is_some_and(|node| node.expanded) {
+            self.tree.collapse(index);
+        } else if let Some(parent) = self.tree.parent(index) {
+            self.state.select(Some(parent));
+        }
+    }
+}
+
+impl DrawableComponent for FileTreeComponent {
+    fn draw(&self, f: &mut Frame, area: Rect, focused: bool) -> Result<()> {
+        if !self.visible {
+            return Ok(());
+        }
+        let colors = self.theme.table_colors(0);
+        let items = self.tree.nodes().iter().map(|node| {
+            let marker = match (node.is_dir, node.expanded) {
+                (false, _) => "  ",
+                (true, true) => "▾ ",
+                (true, false) => "▸ ",
+            };
+            let name = if node.is_dir && node.depth > 0 {
+                format!("{}/", node.name)
+            } else {
+                node.name.clone()
+            };
+            let style = if node.is_dir {
+                Style::default().add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            ListItem::new(Line::from(vec![
+                Span::raw("  ".repeat(node.depth)),
+                Span::raw(marker),
+                Span::styled(name, style),
+            ]))
+        });
+        let border_style = if focused {
+            Style::default().fg(self.theme.accent(Color::Green))
+        } else {
+            Style::default().fg(Color::DarkGray)
+        };
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(border_style)
+                    .title(format!("Files {}", self.title)),
+            )
+            .style(Style::default().fg(colors.row_fg).bg(colors.buffer_bg))
+            .highlight_style(
+                Style::default()
+                    .add_modifier(Modifier::REVERSED)
+                    .fg(colors.selected_style_fg),
+            );
+        f.render_stateful_widget(list, area, &mut self.state.clone());
+        Ok(())
+    }
+}
+
+impl Component for FileTreeComponent {
+    fn commands(&self, out: &mut Vec<CommandInfo>) {
+        out.push(
+            CommandInfo::new(command::browse_files(&self.key_config))
+                .action("Browse proc files", Action::Keys(vec![self.key_config.browse_files])),
+        );
+        out.push(
+            CommandInfo::new(command::follow_file(&self.key_config))
+                .action("Follow selected file", Action::Keys(vec![self.key_config.follow_file])),
+        )
+    }
+
+    fn event(&mut self, key: Key) -> Result<EventState> {
+        if !self.visible {
+            return Ok(EventState::NotConsumed);
+        }
+        if key == self.key_config.scroll_down || key == self.key_config.move_down {
+            self.step(true);
+        } else if key == self.key_config.scroll_up || key == self.key_config.move_up {
+            self.step(false);
+        } else if key == self.key_config.scroll_right || key == Key::Right {
+            if let Some(index) = self.state.selected() {
+                self.tree.expand(index)?;
+            }
+        } else if key == self.key_config.scroll_left || key == Key::Left {
+            self.collapse();
+        } else {
+            return Ok(EventState::NotConsumed);
+        }
+        Ok(EventState::Consumed)
+    }
+
+    fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    fn hide(&mut self) {
+        self.visible = false;
+    }
+
+    fn show(&mut self) -> Result<()> {
+        self.visible = true;
+        Ok(())
+    }
+}
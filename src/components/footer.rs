@@ -1,13 +1,17 @@
 use ratatui::{prelude::*, widgets::*};
 
-use super::{host::TableColors, DrawableComponent};
-const INFO_TEXT: &str =
-    "(Tab) change Tab | (enter) search | (q) quit | (↑) move up | (↓) move down | (1) host search | (2) proc search";
-
+use super::DrawableComponent;
+use crate::theme::TableColors;
+use crate::config::KeyConfig;
 
 #[derive(Clone)]
 pub struct FooterComponent {
     pub colors: TableColors,
+    pub key_config: KeyConfig,
+    /// 当前表格标记的行数
+    pub marked: usize,
+    /// 最近一次操作的提示, 下一次按键时清空
+    pub message: String,
 }
 
 impl DrawableComponent for FooterComponent {
@@ -22,8 +26,30 @@ impl DrawableComponent for FooterComponent {
     }
 }
 impl FooterComponent {
+    fn info_text(&self) -> String {
+        let k = &self.key_config;
+        format!(
+            "({}) change Tab | ({}) search | ({}) quit | ({}) move up | ({}) move down | ({}) host search | ({}) proc search | ({}) topology | ({}) stats",
+            k.toggle_focus, k.enter, k.quit, k.move_up, k.move_down, k.tab_host, k.tab_proc, k.tab_tree, k.tab_stats
+        )
+    }
+
     fn render_footer(&mut self, f: &mut Frame, area: Rect) {
-        let info_footer = Paragraph::new(Line::from(INFO_TEXT))
+        let mut spans = Vec::new();
+        if self.marked > 0 {
+            spans.push(Span::styled(
+                format!("[{} marked] ", self.marked),
+                Style::new().add_modifier(Modifier::BOLD),
+            ));
+        }
+        if !self.message.is_empty() {
+            spans.push(Span::styled(
+                format!("{} | ", self.message),
+                Style::new().add_modifier(Modifier::BOLD),
+            ));
+        }
+        spans.push(Span::raw(self.info_text()));
+        let info_footer = Paragraph::new(Line::from(spans))
             .style(
                 Style::new()
                     .fg(self.colors.row_fg)
@@ -2,11 +2,12 @@ use super::{Component, DrawableComponent, EventState};
 use crate::components::command::CommandInfo;
 use crate::config::KeyConfig;
 use crate::event::Key;
+use crate::ui::centered_rect;
 use anyhow::Result;
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
-    style::Style,
-    text::Span,
+    style::{Modifier, Style},
+    text::{Line, Span},
     widgets::{Block, BorderType, Borders, Clear, Paragraph},
     Frame,
 };
@@ -14,23 +15,21 @@ use ratatui::{
 pub struct HelpComponent {
     cmds: Vec<CommandInfo>,
     visible: bool,
+    /// 选中的命令在过滤后列表中的序号
     selection: u16,
+    /// 按名称和分组过滤, 不区分大小写
+    filter: String,
+    filtering: bool,
     key_config: KeyConfig,
 }
 
 impl DrawableComponent for HelpComponent {
     fn draw(&self, f: &mut Frame, _area: Rect, _focused: bool) -> Result<()> {
         if self.visible {
-            const SIZE: (u16, u16) = (65, 24);
+            const SIZE: (u16, u16) = (80, 24);
             let scroll_threshold = SIZE.1 / 3;
-            let scroll = self.selection.saturating_sub(scroll_threshold);
 
-            let area = Rect::new(
-                (f.size().width.saturating_sub(SIZE.0)) / 2,
-                (f.size().height.saturating_sub(SIZE.1)) / 2,
-                SIZE.0.min(f.size().width),
-                SIZE.1.min(f.size().height),
-            );
+            let area = centered_rect(SIZE, f.size());
 
             f.render_widget(Clear, area);
             f.render_widget(
@@ -45,18 +44,45 @@ impl DrawableComponent for HelpComponent {
                 .vertical_margin(1)
                 .horizontal_margin(1)
                 .direction(Direction::Vertical)
-                .constraints([Constraint::Min(1), Constraint::Length(1)].as_ref())
+                .constraints(
+                    [
+                        Constraint::Length(1),
+                        Constraint::Min(1),
+                        Constraint::Length(1),
+                    ]
+                    .as_ref(),
+                )
                 .split(area);
 
+            let cursor = if self.filtering { "█" } else { "" };
             f.render_widget(
-                Paragraph::new(Span::styled(format!("tcm baby walker {}", "0.1"), Style::default())).scroll((scroll, 0)),
+                Paragraph::new(Line::from(vec![
+                    Span::styled(
+                        format!("{} ", self.key_config.filter),
+                        Style::default().add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw(format!("{}{}", self.filter, cursor)),
+                ])),
                 chunks[0],
             );
 
+            let (lines, selected_line) = self.lines();
+            let scroll = selected_line.saturating_sub(scroll_threshold);
+            f.render_widget(Paragraph::new(lines).scroll((scroll, 0)), chunks[1]);
+
             f.render_widget(
-                Paragraph::new(Span::styled(format!("tcm baby walker {}", "0.1"), Style::default()))
-                    .alignment(Alignment::Right),
-                chunks[1],
+                Paragraph::new(Span::styled(
+                    format!(
+                        "[{}] 过滤 [{},{}] 选择 [{}] 关闭",
+                        self.key_config.filter,
+                        self.key_config.scroll_down,
+                        self.key_config.scroll_up,
+                        self.key_config.exit_popup
+                    ),
+                    Style::default(),
+                ))
+                .alignment(Alignment::Right),
+                chunks[2],
             );
         }
 
@@ -67,15 +93,40 @@ impl DrawableComponent for HelpComponent {
 impl Component for HelpComponent {
     fn commands(&self, _out: &mut Vec<CommandInfo>) {}
 
+    /// 过滤框打开时接管所有按键, Esc 先清除过滤条件再关闭
     fn event(&mut self, key: Key) -> Result<EventState> {
         if self.visible {
+            if self.filtering {
+                match key {
+                    Key::Char(c) => self.filter.push(c),
+                    Key::Backspace => {
+                        self.filter.pop();
+                    }
+                    _ if key == self.key_config.enter => self.filtering = false,
+                    _ if key == self.key_config.exit_popup => {
+                        self.filter.clear();
+                        self.filtering = false;
+                    }
+                    _ => {}
+                }
+                self.selection = 0;
+                return Ok(EventState::Consumed);
+            }
             if key == self.key_config.exit_popup {
-                self.hide();
+                if self.filter.is_empty() {
+                    self.hide();
+                } else {
+                    self.filter.clear();
+                    self.selection = 0;
+                }
+                return Ok(EventState::Consumed);
+            } else if key == self.key_config.filter {
+                self.filtering = true;
                 return Ok(EventState::Consumed);
-            } else if key == self.key_config.scroll_down {
+            } else if key == self.key_config.scroll_down || key == self.key_config.move_down {
                 self.scroll_selection(true);
                 return Ok(EventState::Consumed);
-            } else if key == self.key_config.scroll_up {
+            } else if key == self.key_config.scroll_up || key == self.key_config.move_up {
                 self.scroll_selection(false);
                 return Ok(EventState::Consumed);
             }
@@ -87,12 +138,19 @@ impl Component for HelpComponent {
         Ok(EventState::NotConsumed)
     }
 
+    fn is_visible(&self) -> bool {
+        self.visible
+    }
+
     fn hide(&mut self) {
         self.visible = false;
     }
 
     fn show(&mut self) -> Result<()> {
         self.visible = true;
+        self.selection = 0;
+        self.filter.clear();
+        self.filtering = false;
 
         Ok(())
     }
@@ -104,15 +162,67 @@ impl HelpComponent {
             cmds: vec![],
             visible: false,
             selection: 0,
+            filter: String::new(),
+            filtering: false,
             key_config,
         }
     }
 
+    /// 按分组第一次出现的顺序排列, 多个组件提供的同一条命令只保留一条
     pub fn set_cmds(&mut self, cmds: Vec<CommandInfo>) {
-        self.cmds = cmds
-            .into_iter()
-            .filter(|e| !e.text.hide_help)
-            .collect::<Vec<_>>();
+        let mut groups: Vec<&'static str> = Vec::new();
+        let mut unique: Vec<CommandInfo> = Vec::new();
+        for cmd in cmds {
+            if cmd.text.hide_help || unique.iter().any(|e| e.text == cmd.text) {
+                continue;
+            }
+            if !groups.contains(&cmd.text.group) {
+                groups.push(cmd.text.group);
+            }
+            unique.push(cmd);
+        }
+        unique.sort_by_key(|e| groups.iter().position(|group| *group == e.text.group));
+        self.cmds = unique;
+    }
+
+    fn filtered(&self) -> Vec<&CommandInfo> {
+        let filter = self.filter.to_lowercase();
+        self.cmds
+            .iter()
+            .filter(|e| {
+                e.text.name.to_lowercase().contains(&filter) || e.text.group.to_lowercase().contains(&filter)
+            })
+            .collect()
+    }
+
+    /// 每个分组一行标题, 返回所有行和选中命令所在的行
+    fn lines(&self) -> (Vec<Line<'_>>, u16) {
+        let mut lines = Vec::new();
+        let mut selected_line = 0;
+        let mut group = "";
+        for (i, cmd) in self.filtered().into_iter().enumerate() {
+            if cmd.text.group != group {
+                group = cmd.text.group;
+                lines.push(Line::from(Span::styled(
+                    group,
+                    Style::default().add_modifier(Modifier::BOLD),
+                )));
+            }
+            let selected = i == usize::from(self.selection);
+            if selected {
+                selected_line = lines.len() as u16;
+            }
+            let style = if selected {
+                Style::default().add_modifier(Modifier::REVERSED)
+            } else {
+                Style::default()
+            };
+            lines.push(Line::from(Span::styled(format!("  {}", cmd.text.name), style)));
+        }
+        if lines.is_empty() {
+            lines.push(Line::from("  没有匹配的命令"));
+        }
+        (lines, selected_line)
     }
 
     fn scroll_selection(&mut self, inc: bool) {
@@ -123,8 +233,7 @@ impl HelpComponent {
         } else {
             new_selection.saturating_sub(1)
         };
-        new_selection = new_selection.max(0);
 
-        self.selection = new_selection.min(self.cmds.len().saturating_sub(1) as u16);
+        self.selection = new_selection.min(self.filtered().len().saturating_sub(1) as u16);
     }
 }
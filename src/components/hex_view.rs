@@ -0,0 +1,214 @@
+use super::syntax_text::MoveSelection;
+use crate::theme::Theme;
+use crate::tools::binary::{format_size, format_utc, hex_row, read_elf, sha256, HEX_WIDTH};
+use crate::ui::scrollbar::{draw_scrollbar, Orientation};
+use anyhow::Context;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{block::Position, block::Title, Block, Borders, Paragraph},
+    Frame,
+};
+use std::cell::{Cell, RefCell};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::UNIX_EPOCH;
+use unicode_width::UnicodeWidthStr;
+
+/// 二进制文件的十六进制视图. 上面是文件信息, 用来确认部署的是哪个版本,
+/// 下面只在滚动或者窗口大小变化时读取可见的部分, 大文件也不用整个读进内存
+pub struct HexView {
+    path: PathBuf,
+    size: u64,
+    /// 文件信息面板的每一行: 名称和值
+    info: Vec<(&'static str, String)>,
+    /// 大文件的 sha256 要算很久, 在后台线程里计算, 算完之前为 None
+    sha256: Arc<Mutex<Option<String>>>,
+    file: RefCell<File>,
+    /// 上次读取的可见部分: 第一行, 行数和字节
+    window: RefCell<Option<(u64, u64, Vec<u8>)>>,
+    /// 第一个可见行, 每行 HEX_WIDTH 个字节
+    top: Cell<u64>,
+    rows: Cell<u64>,
+}
+
+impl HexView {
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        let context = || format!("Read file -> [{}] failed", path.display());
+        let mut file = File::open(path).with_context(context)?;
+        let meta = file.metadata().with_context(context)?;
+
+        let mut info = vec![("大小", format_size(meta.len()))];
+        if let Some(secs) = meta
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        {
+            info.push(("修改时间", format_utc(secs.as_secs())));
+        }
+        match read_elf(&mut file).with_context(context)? {
+            Some(elf) => {
+                info.push(("类型", elf.to_string()));
+                info.push(("build-id", elf.build_id.unwrap_or_else(|| "无".to_string())));
+            }
+            None => info.push(("类型", "非 ELF 文件".to_string())),
+        }
+        // 单独打开一次, 和十六进制视图的读取位置互不影响
+        let mut hash_file = File::open(path).with_context(context)?;
+        let digest = Arc::new(Mutex::new(None));
+        let result = digest.clone();
+        std::thread::spawn(move || {
+            let value = sha256(&mut hash_file).unwrap_or_else(|e| format!("读取失败: {}", e));
+            if let Ok(mut result) = result.lock() {
+                *result = Some(value);
+            }
+        });
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            size: meta.len(),
+            info,
+            sha256: digest,
+            file: RefCell::new(file),
+            window: RefCell::new(None),
+            top: Cell::new(0),
+            rows: Cell::new(0),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn total_rows(&self) -> u64 {
+        self.size.div_ceil(HEX_WIDTH as u64)
+    }
+
+    fn max_top(&self) -> u64 {
+        self.total_rows().saturating_sub(self.rows.get())
+    }
+
+    pub fn scroll(&self, nav: MoveSelection) -> bool {
+        let top = self.top.get();
+        let rows = self.rows.get();
+        let new_top = match nav {
+            MoveSelection::Down => top.saturating_add(1),
+            MoveSelection::Up => top.saturating_sub(1),
+            MoveSelection::Top => 0,
+            MoveSelection::End => self.max_top(),
+            MoveSelection::PageUp => top.saturating_sub(rows),
+            MoveSelection::PageDown => top.saturating_add(rows),
+            MoveSelection::Left | MoveSelection::Right => return false,
+        }
+        .min(self.max_top());
+        if new_top == top {
+            return false;
+        }
+        self.top.set(new_top);
+        true
+    }
+
+    /// 读取可见的字节, 位置和行数没变时使用上次读到的内容.
+    /// 文件在查看期间变短时只显示还在的部分
+    fn read_window(&self, top: u64, rows: u64) -> anyhow::Result<std::cell::Ref<'_, [u8]>> {
+        let cached = matches!(*self.window.borrow(), Some((t, r, _)) if (t, r) == (top, rows));
+        if !cached {
+            let mut file = self.file.borrow_mut();
+            file.seek(SeekFrom::Start(top * HEX_WIDTH as u64))?;
+            let mut buf = Vec::new();
+            (&mut *file).take(rows * HEX_WIDTH as u64).read_to_end(&mut buf)?;
+            *self.window.borrow_mut() = Some((top, rows, buf));
+        }
+        Ok(std::cell::Ref::map(self.window.borrow(), |window| {
+            window.as_ref().map_or(&[][..], |(_, _, bytes)| bytes.as_slice())
+        }))
+    }
+
+    pub fn draw(&self, f: &mut Frame, area: Rect, theme: &Theme, border: Style) -> anyhow::Result<()> {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(self.info.len() as u16 + 3),
+                Constraint::Min(3),
+            ])
+            .split(area);
+
+        let sha256 = self
+            .sha256
+            .lock()
+            .ok()
+            .and_then(|sha256| sha256.clone())
+            .unwrap_or_else(|| "计算中…".to_string());
+        let fields = self.info.iter().cloned().chain([("sha256", sha256)]).collect::<Vec<_>>();
+        let label_width = fields.iter().map(|(name, _)| name.width()).max().unwrap_or(0);
+        let info: Vec<Line> = fields
+            .iter()
+            .map(|(name, value)| {
+                Line::from(vec![
+                    Span::styled(
+                        format!("{}{}", name, " ".repeat(label_width + 2 - name.width())),
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                    Span::raw(value.as_str()),
+                ])
+            })
+            .collect();
+        f.render_widget(
+            Paragraph::new(info).block(
+                Block::default()
+                    .title(format!("{} [二进制]", self.path.display()))
+                    .borders(Borders::ALL)
+                    .border_style(border),
+            ),
+            chunks[0],
+        );
+
+        let rows = u64::from(chunks[1].height.saturating_sub(2));
+        self.rows.set(rows);
+        let top = self.top.get().min(self.max_top());
+        self.top.set(top);
+        let bytes = self.read_window(top, rows)?;
+        let ascii_style = Style::default().fg(theme.accent(Color::Cyan));
+        let lines: Vec<Line> = bytes
+            .chunks(HEX_WIDTH)
+            .enumerate()
+            .map(|(i, chunk)| {
+                let (offset, hex, ascii) = hex_row((top + i as u64) * HEX_WIDTH as u64, chunk);
+                Line::from(vec![
+                    Span::styled(offset, Style::default().fg(Color::DarkGray)),
+                    Span::raw("  "),
+                    Span::raw(hex),
+                    Span::raw(" |"),
+                    Span::styled(ascii, ascii_style),
+                    Span::raw("|"),
+                ])
+            })
+            .collect();
+        f.render_widget(
+            Paragraph::new(lines).block(
+                Block::default()
+                    .title(Title::from(format!(
+                        " {:#010x} / {:#010x} ",
+                        top * HEX_WIDTH as u64,
+                        self.size
+                    ))
+                    .position(Position::Bottom))
+                    .borders(Borders::ALL)
+                    .border_style(border),
+            ),
+            chunks[1],
+        );
+        draw_scrollbar(
+            f,
+            chunks[1],
+            &theme.accent(Color::LightMagenta),
+            self.max_top() as usize,
+            top as usize,
+            Orientation::Vertical,
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,224 @@
+use super::{Component, DrawableComponent, EventState};
+use crate::components::command::{self, CommandInfo};
+use crate::config::KeyConfig;
+use crate::database::{select_host_deploys, TcmQueryResult};
+use crate::event::Key;
+use crate::tools::host::HostInfo;
+use crate::ui::{centered_rect, wrap_next, wrap_previous};
+use anyhow::Result;
+use hashbrown::HashSet;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Cell, Clear, Paragraph, Row, Table, TableState},
+    Frame,
+};
+use sqlx::SqlitePool;
+
+/// 主机详情弹窗: 同一个内网 IP 上部署的全部进程组和进程实例
+pub struct HostDetailComponent {
+    visible: bool,
+    host: Option<HostInfo>,
+    deploys: Vec<TcmQueryResult>,
+    state: TableState,
+    key_config: KeyConfig,
+}
+
+impl HostDetailComponent {
+    pub fn new(key_config: KeyConfig) -> Self {
+        Self {
+            visible: false,
+            host: None,
+            deploys: Vec::new(),
+            state: TableState::default(),
+            key_config,
+        }
+    }
+
+    pub async fn load(&mut self, pool: &SqlitePool, host: &HostInfo) -> Result<()> {
+        self.deploys = select_host_deploys(pool, &host.inner_ip).await?;
+        self.state = TableState::default().with_selected((!self.deploys.is_empty()).then_some(0));
+        self.host = Some(host.clone());
+        Ok(())
+    }
+
+    /// 选中行对应的 FuncID, 用于打开进程详情
+    pub fn selected_func_id(&self) -> Option<i32> {
+        self.deploys
+            .get(self.state.selected()?)
+            .map(|deploy| deploy.func_id)
+    }
+
+    fn next(&mut self) {
+        if let Some(i) = wrap_next(self.state.selected(), self.deploys.len()) {
+            self.state.select(Some(i));
+        }
+    }
+
+    fn previous(&mut self) {
+        if let Some(i) = wrap_previous(self.state.selected(), self.deploys.len()) {
+            self.state.select(Some(i));
+        }
+    }
+
+    /// 按 Layer 统计进程实例数, 顺序与第一次出现的顺序一致
+    fn layer_counts(&self) -> Vec<(&str, usize)> {
+        let mut counts: Vec<(&str, usize)> = Vec::new();
+        for deploy in &self.deploys {
+            match counts.iter_mut().find(|(layer, _)| *layer == deploy.proc_type) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((&deploy.proc_type, 1)),
+            }
+        }
+        counts
+    }
+
+    fn summary(&self) -> Line<'static> {
+        let label = Style::default().add_modifier(Modifier::BOLD);
+        let groups = self
+            .deploys
+            .iter()
+            .map(|d| (&d.world_id, &d.zone_id, &d.proc_group_name))
+            .collect::<HashSet<_>>();
+        let zones = self
+            .deploys
+            .iter()
+            .map(|d| (&d.world_id, &d.zone_id))
+            .collect::<HashSet<_>>();
+        let layers = self
+            .layer_counts()
+            .iter()
+            .map(|(layer, count)| format!("{} {}", layer, count))
+            .collect::<Vec<_>>()
+            .join(", ");
+        Line::from(vec![
+            Span::styled("实例 ", label),
+            Span::raw(format!("{}  ", self.deploys.len())),
+            Span::styled("部署组 ", label),
+            Span::raw(format!("{}  ", groups.len())),
+            Span::styled("World/Zone ", label),
+            Span::raw(format!("{}  ", zones.len())),
+            Span::styled("Layer ", label),
+            Span::raw(layers),
+        ])
+    }
+}
+
+impl DrawableComponent for HostDetailComponent {
+    fn draw(&self, f: &mut Frame, _area: Rect, _focused: bool) -> Result<()> {
+        if !self.visible {
+            return Ok(());
+        }
+        let Some(host) = &self.host else {
+            return Ok(());
+        };
+        const SIZE: (u16, u16) = (100, 32);
+        let area = centered_rect(SIZE, f.size());
+
+        f.render_widget(Clear, area);
+        f.render_widget(
+            Block::default()
+                .title(format!("Host Detail - {} ({})", host.inner_ip, host.host_name))
+                .borders(Borders::ALL)
+                .border_type(BorderType::Thick),
+            area,
+        );
+
+        let chunks = Layout::default()
+            .vertical_margin(1)
+            .horizontal_margin(1)
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(1),
+                Constraint::Min(3),
+                Constraint::Length(1),
+            ])
+            .split(area);
+
+        f.render_widget(Paragraph::new(self.summary()), chunks[0]);
+
+        let header = [
+            "WorldID", "ZoneID", "HostName", "Group", "FuncName", "FuncID", "InstID", "Layer",
+        ]
+        .into_iter()
+        .map(Cell::from)
+        .collect::<Row>()
+        .style(Style::default().add_modifier(Modifier::BOLD));
+        let rows = self.deploys.iter().map(|d| {
+            Row::new([
+                d.world_id.clone(),
+                d.zone_id.clone(),
+                d.host_name.clone(),
+                d.proc_group_name.clone(),
+                d.func_name.clone(),
+                d.func_id.to_string(),
+                d.inst_id.to_string(),
+                d.proc_type.clone(),
+            ])
+        });
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(8),
+                Constraint::Length(8),
+                Constraint::Min(14),
+                Constraint::Min(12),
+                Constraint::Min(12),
+                Constraint::Length(8),
+                Constraint::Length(8),
+                Constraint::Length(8),
+            ],
+        )
+        .header(header)
+        .block(Block::default().borders(Borders::TOP))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        f.render_stateful_widget(table, chunks[1], &mut self.state.clone());
+
+        let help = format!(
+            "[{}/{}] select [{}] proc detail [{}] close",
+            self.key_config.scroll_up,
+            self.key_config.scroll_down,
+            self.key_config.enter,
+            self.key_config.exit_popup,
+        );
+        f.render_widget(
+            Paragraph::new(Span::styled(help, Style::default().fg(Color::DarkGray))),
+            chunks[2],
+        );
+        Ok(())
+    }
+}
+
+impl Component for HostDetailComponent {
+    fn commands(&self, out: &mut Vec<CommandInfo>) {
+        out.push(CommandInfo::new(command::host_detail(&self.key_config)));
+    }
+
+    fn event(&mut self, key: Key) -> Result<EventState> {
+        if !self.visible {
+            return Ok(EventState::NotConsumed);
+        }
+        if key == self.key_config.exit_popup {
+            self.hide();
+        } else if key == self.key_config.scroll_down || key == self.key_config.move_down {
+            self.next();
+        } else if key == self.key_config.scroll_up || key == self.key_config.move_up {
+            self.previous();
+        }
+        Ok(EventState::Consumed)
+    }
+
+    fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    fn hide(&mut self) {
+        self.visible = false;
+    }
+
+    fn show(&mut self) -> Result<()> {
+        self.visible = true;
+        Ok(())
+    }
+}
@@ -1,6 +1,6 @@
-pub mod error;
 pub mod help;
 pub mod tabs;
+pub mod tab_pages;
 pub mod proc;
 pub mod command;
 pub mod syntax_text;
@@ -9,7 +9,20 @@ pub mod footer;
 use anyhow::Result;
 pub mod query_input;
 pub mod total_proc;
+pub mod tcm_command;
+pub mod proc_detail;
+pub mod host_detail;
+pub mod topology;
+pub mod columns;
+pub mod data_table;
+pub mod stats;
+pub mod file_tree;
+pub mod diff;
+pub mod hex_view;
+pub mod palette;
+pub mod notify;
 use async_trait::async_trait;
+use crossterm::event::MouseEvent;
 use ratatui::{backend::Backend, layout::Rect, Frame};
 
 use self::command::CommandInfo;
@@ -62,6 +75,11 @@ pub trait Component {
 
     fn event(&mut self, key: crate::event::Key) -> Result<EventState>;
 
+    /// 鼠标事件, 组件根据上次绘制的区域判断是否命中
+    fn mouse_event(&mut self, _mouse: MouseEvent) -> Result<EventState> {
+        Ok(EventState::NotConsumed)
+    }
+
     async fn async_event(
         &mut self,
         _key: crate::event::Key,
@@ -0,0 +1,217 @@
+use super::{Component, DrawableComponent, EventState};
+use crate::clipboard::copy_string;
+use crate::components::command::CommandInfo;
+use crate::config::KeyConfig;
+use crate::event::Key;
+use crate::tools::notify::{Fade, Level, Notice, NoticeLog};
+use crate::ui::centered_rect;
+use anyhow::Result;
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+use std::time::Instant;
+use unicode_width::UnicodeWidthStr;
+
+/// 提示的宽度和最多显示的行数
+const TOAST_WIDTH: u16 = 50;
+const TOAST_LINES: u16 = 4;
+
+/// 右上角自动消失的提示, 以及查看全部提示的消息记录
+pub struct NotifyComponent {
+    log: NoticeLog,
+    /// 消息记录是否打开
+    visible: bool,
+    selection: usize,
+    key_config: KeyConfig,
+}
+
+impl DrawableComponent for NotifyComponent {
+    fn draw(&self, f: &mut Frame, _area: Rect, _focused: bool) -> Result<()> {
+        if self.visible {
+            self.draw_log(f);
+        } else {
+            self.draw_toasts(f);
+        }
+        Ok(())
+    }
+}
+
+impl Component for NotifyComponent {
+    fn commands(&self, _out: &mut Vec<CommandInfo>) {}
+
+    fn event(&mut self, key: Key) -> Result<EventState> {
+        // 打开消息记录的按键由 App 在其它组件之后处理, 避免输入框里打不出这个字符
+        if !self.visible {
+            return Ok(EventState::NotConsumed);
+        }
+        let last = self.log.notices().len().saturating_sub(1);
+        if key == self.key_config.exit_popup || key == self.key_config.message_log {
+            self.hide();
+        } else if key == self.key_config.scroll_down || key == self.key_config.move_down {
+            self.selection = (self.selection + 1).min(last);
+        } else if key == self.key_config.scroll_up || key == self.key_config.move_up {
+            self.selection = self.selection.saturating_sub(1);
+        } else if key == self.key_config.scroll_to_top {
+            self.selection = 0;
+        } else if key == self.key_config.scroll_to_bottom {
+            self.selection = last;
+        } else if key == self.key_config.copy {
+            if let Some(notice) = self.log.notices().get(self.selection) {
+                let copied = copy_string(&notice.log_line())?;
+                self.info(format!("已复制消息到{}", copied));
+            }
+        }
+        Ok(EventState::Consumed)
+    }
+
+    fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    fn hide(&mut self) {
+        self.visible = false;
+    }
+
+    /// 打开时选中最新的一条
+    fn show(&mut self) -> Result<()> {
+        self.visible = true;
+        self.selection = self.log.notices().len().saturating_sub(1);
+        Ok(())
+    }
+}
+
+impl NotifyComponent {
+    pub fn new(key_config: KeyConfig) -> Self {
+        Self {
+            log: NoticeLog::default(),
+            visible: false,
+            selection: 0,
+            key_config,
+        }
+    }
+
+    pub fn info(&mut self, text: String) {
+        self.log.push(Level::Info, text);
+    }
+
+    pub fn warn(&mut self, text: String) {
+        self.log.push(Level::Warn, text);
+    }
+
+    pub fn error(&mut self, text: String) {
+        self.log.push(Level::Error, text);
+    }
+
+    /// 最新的提示在最上面, 淡出时变暗
+    fn draw_toasts(&self, f: &mut Frame) {
+        let size = f.size();
+        let width = TOAST_WIDTH.min(size.width);
+        let mut y = size.y;
+        for (notice, fade) in self.log.toasts(Instant::now()) {
+            let text_width = usize::from(width.saturating_sub(2)).max(1);
+            let lines = notice
+                .text
+                .lines()
+                .map(|line| (line.width() / text_width + 1) as u16)
+                .sum::<u16>()
+                .clamp(1, TOAST_LINES);
+            let height = lines + 2;
+            if y + height > size.bottom() {
+                break;
+            }
+            let mut style = Style::default().fg(level_color(notice.level));
+            if fade == Fade::Fading {
+                style = style.add_modifier(Modifier::DIM);
+            }
+            let area = Rect::new(size.right().saturating_sub(width), y, width, height);
+            f.render_widget(Clear, area);
+            f.render_widget(
+                Paragraph::new(notice.text.as_str())
+                    .block(
+                        Block::default()
+                            .title(notice.level.name())
+                            .borders(Borders::ALL)
+                            .border_style(style),
+                    )
+                    .style(style)
+                    .wrap(Wrap { trim: true }),
+                area,
+            );
+            y += height;
+        }
+    }
+
+    fn draw_log(&self, f: &mut Frame) {
+        const SIZE: (u16, u16) = (100, 24);
+        let area = centered_rect(SIZE, f.size());
+        f.render_widget(Clear, area);
+        f.render_widget(
+            Block::default()
+                .title(format!("Messages ({})", self.log.notices().len()))
+                .borders(Borders::ALL),
+            area,
+        );
+        let chunks = Layout::default()
+            .vertical_margin(1)
+            .horizontal_margin(1)
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(1), Constraint::Length(1)].as_ref())
+            .split(area);
+
+        let (lines, selected_line) = self.lines();
+        let scroll = selected_line.saturating_sub(chunks[0].height.saturating_sub(1));
+        f.render_widget(Paragraph::new(lines).scroll((scroll, 0)), chunks[0]);
+        f.render_widget(
+            Paragraph::new(format!(
+                "[{},{}] 选择 [{}] 复制 [{}] 关闭",
+                self.key_config.scroll_down,
+                self.key_config.scroll_up,
+                self.key_config.copy,
+                self.key_config.exit_popup
+            ))
+            .alignment(Alignment::Right),
+            chunks[1],
+        );
+    }
+
+    /// 多行的消息后面几行缩进显示, 返回所有行和选中消息的最后一行
+    fn lines(&self) -> (Vec<Line<'_>>, u16) {
+        let mut lines = Vec::new();
+        let mut selected_line = 0;
+        for (i, notice) in self.log.notices().iter().enumerate() {
+            let mut style = Style::default().fg(level_color(notice.level));
+            if i == self.selection {
+                style = style.add_modifier(Modifier::REVERSED);
+            }
+            lines.extend(log_lines(notice, style));
+            if i == self.selection {
+                selected_line = lines.len().saturating_sub(1) as u16;
+            }
+        }
+        if lines.is_empty() {
+            lines.push(Line::from("没有消息"));
+        }
+        (lines, selected_line)
+    }
+}
+
+fn log_lines(notice: &Notice, style: Style) -> Vec<Line<'static>> {
+    let line = notice.log_line();
+    let mut text = line.lines();
+    let first = Line::from(Span::styled(text.next().unwrap_or_default().to_string(), style));
+    std::iter::once(first)
+        .chain(text.map(|rest| Line::from(Span::styled(format!("    {}", rest), style))))
+        .collect()
+}
+
+fn level_color(level: Level) -> Color {
+    match level {
+        Level::Info => Color::Reset,
+        Level::Warn => Color::Yellow,
+        Level::Error => Color::Red,
+    }
+}
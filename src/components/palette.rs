@@ -0,0 +1,185 @@
+use super::command::{Action, CommandInfo};
+use super::{Component, DrawableComponent, EventState};
+use crate::config::KeyConfig;
+use crate::event::Key;
+use crate::tools::fuzzy::fuzzy_filter;
+use crate::ui::centered_rect;
+use anyhow::Result;
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+    Frame,
+};
+
+/// 命令面板: 输入时模糊匹配当前可用命令的动作, Enter 执行
+pub struct PaletteComponent {
+    actions: Vec<(String, Action)>,
+    visible: bool,
+    query: String,
+    /// 选中的动作在匹配结果中的序号
+    selection: usize,
+    /// 选中后等待 App 执行的动作
+    pending: Option<Action>,
+    key_config: KeyConfig,
+}
+
+impl DrawableComponent for PaletteComponent {
+    fn draw(&self, f: &mut Frame, _area: Rect, _focused: bool) -> Result<()> {
+        if self.visible {
+            const SIZE: (u16, u16) = (70, 18);
+
+            let area = centered_rect(SIZE, f.size());
+
+            f.render_widget(Clear, area);
+            f.render_widget(
+                Block::default()
+                    .title("Command Palette")
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Thick),
+                area,
+            );
+
+            let chunks = Layout::default()
+                .vertical_margin(1)
+                .horizontal_margin(1)
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(1), Constraint::Min(1)].as_ref())
+                .split(area);
+
+            f.render_widget(
+                Paragraph::new(Line::from(vec![
+                    Span::styled("> ", Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(format!("{}█", self.query)),
+                ])),
+                chunks[0],
+            );
+
+            let matches = fuzzy_filter(&self.query, self.actions.iter().map(|(name, _)| name.as_str()));
+            if matches.is_empty() {
+                f.render_widget(Paragraph::new("  没有匹配的命令"), chunks[1]);
+                return Ok(());
+            }
+            let height = usize::from(chunks[1].height);
+            let offset = (self.selection + 1).saturating_sub(height);
+            for (row, (i, positions)) in matches.iter().enumerate().skip(offset).take(height) {
+                let (name, action) = &self.actions[*i];
+                let selected = row == self.selection;
+                let base = if selected {
+                    Style::default().add_modifier(Modifier::REVERSED)
+                } else {
+                    Style::default()
+                };
+                let mut spans = vec![Span::styled("  ", base)];
+                spans.extend(name.chars().enumerate().map(|(j, c)| {
+                    let style = if positions.contains(&j) {
+                        base.add_modifier(Modifier::BOLD)
+                    } else {
+                        base
+                    };
+                    Span::styled(c.to_string(), style)
+                }));
+                let line = Rect::new(chunks[1].x, chunks[1].y + (row - offset) as u16, chunks[1].width, 1);
+                f.render_widget(Paragraph::new(Line::from(spans)).style(base), line);
+                if let Action::Keys(keys) = action {
+                    let keys = keys.iter().map(|key| key.to_string()).collect::<Vec<_>>().join(" ");
+                    f.render_widget(
+                        Paragraph::new(Span::styled(format!("{} ", keys), base)).alignment(Alignment::Right),
+                        line,
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Component for PaletteComponent {
+    fn commands(&self, _out: &mut Vec<CommandInfo>) {}
+
+    /// 打开时接管所有按键, 字符都进入输入框
+    fn event(&mut self, key: Key) -> Result<EventState> {
+        if !self.visible {
+            if key == self.key_config.command_palette {
+                self.show()?;
+                return Ok(EventState::Consumed);
+            }
+            return Ok(EventState::NotConsumed);
+        }
+        match key {
+            _ if key == self.key_config.exit_popup => self.hide(),
+            _ if key == self.key_config.enter => {
+                let matches = fuzzy_filter(&self.query, self.actions.iter().map(|(name, _)| name.as_str()));
+                self.pending = matches
+                    .get(self.selection)
+                    .map(|(i, _)| self.actions[*i].1.clone());
+                self.hide();
+            }
+            _ if key == self.key_config.move_down => self.select(true),
+            _ if key == self.key_config.move_up => self.select(false),
+            Key::Char(c) => {
+                self.query.push(c);
+                self.selection = 0;
+            }
+            Key::Backspace => {
+                self.query.pop();
+                self.selection = 0;
+            }
+            _ => {}
+        }
+        Ok(EventState::Consumed)
+    }
+
+    fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    fn hide(&mut self) {
+        self.visible = false;
+    }
+
+    fn show(&mut self) -> Result<()> {
+        self.visible = true;
+        self.query.clear();
+        self.selection = 0;
+        Ok(())
+    }
+}
+
+impl PaletteComponent {
+    pub const fn new(key_config: KeyConfig) -> Self {
+        Self {
+            actions: vec![],
+            visible: false,
+            query: String::new(),
+            selection: 0,
+            pending: None,
+            key_config,
+        }
+    }
+
+    /// 收集所有命令的动作, 同名的只保留第一个
+    pub fn set_cmds(&mut self, cmds: &[CommandInfo]) {
+        self.actions.clear();
+        for (name, action) in cmds.iter().flat_map(|cmd| cmd.actions.iter()) {
+            if !self.actions.iter().any(|(e, _)| e == name) {
+                self.actions.push((name.clone(), action.clone()));
+            }
+        }
+    }
+
+    pub fn take_action(&mut self) -> Option<Action> {
+        self.pending.take()
+    }
+
+    fn select(&mut self, inc: bool) {
+        let len = fuzzy_filter(&self.query, self.actions.iter().map(|(name, _)| name.as_str())).len();
+        self.selection = if inc {
+            (self.selection + 1).min(len.saturating_sub(1))
+        } else {
+            self.selection.saturating_sub(1)
+        };
+    }
+}
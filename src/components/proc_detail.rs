@@ -0,0 +1,279 @@
+use super::{Component, DrawableComponent, EventState};
+use crate::components::command::{self, Action, CommandInfo};
+use crate::components::total_proc::TotalProc;
+use crate::config::KeyConfig;
+use crate::database::{select_group_procs, select_proc, select_proc_deploys};
+use crate::event::Key;
+use crate::tools::host::HostInfo;
+use crate::tools::proc::ProcInfo;
+use crate::ui::{centered_rect, wrap_next, wrap_previous};
+use anyhow::{anyhow, Result};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Cell, Clear, Paragraph, Row, Table, TableState},
+    Frame,
+};
+use sqlx::SqlitePool;
+use std::path::PathBuf;
+
+/// 进程详情弹窗: XML 属性, 所属 ProcGroup 以及全部部署位置
+pub struct ProcDetailComponent {
+    visible: bool,
+    proc: Option<ProcInfo>,
+    group_procs: Vec<ProcInfo>,
+    deploys: Vec<TotalProc>,
+    state: TableState,
+    key_config: KeyConfig,
+}
+
+impl ProcDetailComponent {
+    pub fn new(key_config: KeyConfig) -> Self {
+        Self {
+            visible: false,
+            proc: None,
+            group_procs: Vec::new(),
+            deploys: Vec::new(),
+            state: TableState::default(),
+            key_config,
+        }
+    }
+
+    /// 从结果表打开时, 默认选中对应的那一条部署
+    pub async fn load(
+        &mut self,
+        pool: &SqlitePool,
+        func_id: i32,
+        current: Option<&TotalProc>,
+    ) -> Result<()> {
+        let proc = select_proc(pool, func_id)
+            .await?
+            .ok_or_else(|| anyhow!("FuncID -> [{}] 不存在", func_id))?;
+        self.group_procs = select_group_procs(pool, &proc.group_name).await?;
+        self.deploys = select_proc_deploys(pool, func_id)
+            .await?
+            .iter()
+            .map(TotalProc::from)
+            .collect();
+        let selected = current
+            .and_then(|c| {
+                self.deploys.iter().position(|d| {
+                    d.inner_ip == c.inner_ip
+                        && d.world_id == c.world_id
+                        && d.zone_id == c.zone_id
+                        && d.inst_id == c.inst_id
+                })
+            })
+            .or((!self.deploys.is_empty()).then_some(0));
+        self.state = TableState::default().with_selected(selected);
+        self.proc = Some(proc);
+        Ok(())
+    }
+
+    pub fn selected_host(&self) -> Option<HostInfo> {
+        let deploy = self.deploys.get(self.state.selected()?)?;
+        Some(HostInfo {
+            inner_ip: deploy.inner_ip.clone(),
+            host_name: deploy.host_name.clone(),
+            world_id: deploy.world_id.clone(),
+            zone_id: deploy.zone_id.clone(),
+        })
+    }
+
+    /// ConfigPath 为相对路径时相对于进程的 WorkPath
+    pub fn config_file(&self) -> Result<PathBuf> {
+        let proc = self.proc.as_ref().ok_or_else(|| anyhow!("没有选中进程"))?;
+        let config_path = proc
+            .attributes
+            .config_path
+            .as_ref()
+            .ok_or_else(|| anyhow!("{} 没有配置 ConfigPath", proc.funcname))?;
+        let path = std::path::Path::new(&proc.work_path).join(config_path);
+        if path.is_dir() {
+            return Err(anyhow!("ConfigPath -> [{}] 是目录", path.display()));
+        }
+        Ok(path)
+    }
+
+    pub fn proc(&self) -> Option<&ProcInfo> {
+        self.proc.as_ref()
+    }
+
+    pub fn binary_file(&self) -> Result<PathBuf> {
+        let proc = self.proc.as_ref().ok_or_else(|| anyhow!("没有选中进程"))?;
+        Ok(std::path::Path::new(&proc.work_path).join(&proc.proc_name))
+    }
+
+    fn next(&mut self) {
+        if let Some(i) = wrap_next(self.state.selected(), self.deploys.len()) {
+            self.state.select(Some(i));
+        }
+    }
+
+    fn previous(&mut self) {
+        if let Some(i) = wrap_previous(self.state.selected(), self.deploys.len()) {
+            self.state.select(Some(i));
+        }
+    }
+
+    fn attribute_lines(&self, proc: &ProcInfo) -> Vec<Line<'static>> {
+        let label = Style::default().add_modifier(Modifier::BOLD);
+        let mut pairs = vec![
+            ("FuncName", Some(proc.funcname.clone())),
+            ("FuncID", Some(proc.func_id.to_string())),
+            ("ProcName", Some(proc.proc_name.clone())),
+            ("Layer", Some(proc.layer.clone())),
+            ("WorkPath", Some(proc.work_path.clone())),
+        ];
+        pairs.extend(
+            proc.attributes
+                .pairs()
+                .into_iter()
+                .map(|(name, value)| (name, value.map(str::to_string))),
+        );
+        let mut lines = pairs
+            .into_iter()
+            .map(|(name, value)| {
+                Line::from(vec![
+                    Span::styled(format!("{:<12}", name), label),
+                    Span::raw(value.unwrap_or_else(|| "-".to_string())),
+                ])
+            })
+            .collect::<Vec<_>>();
+        let members = self
+            .group_procs
+            .iter()
+            .map(|p| format!("{}({})", p.funcname, p.func_id))
+            .collect::<Vec<_>>()
+            .join(", ");
+        lines.push(Line::from(vec![
+            Span::styled(format!("{:<12}", "ProcGroup"), label),
+            Span::raw(format!("{} [{}]", proc.group_name, members)),
+        ]));
+        lines
+    }
+}
+
+impl DrawableComponent for ProcDetailComponent {
+    fn draw(&self, f: &mut Frame, _area: Rect, _focused: bool) -> Result<()> {
+        if !self.visible {
+            return Ok(());
+        }
+        let Some(proc) = &self.proc else {
+            return Ok(());
+        };
+        const SIZE: (u16, u16) = (100, 32);
+        let area = centered_rect(SIZE, f.size());
+
+        f.render_widget(Clear, area);
+        f.render_widget(
+            Block::default()
+                .title(format!("Proc Detail - {}", proc.funcname))
+                .borders(Borders::ALL)
+                .border_type(BorderType::Thick),
+            area,
+        );
+
+        let attributes = self.attribute_lines(proc);
+        let chunks = Layout::default()
+            .vertical_margin(1)
+            .horizontal_margin(1)
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(attributes.len() as u16),
+                Constraint::Min(3),
+                Constraint::Length(1),
+            ])
+            .split(area);
+
+        f.render_widget(Paragraph::new(attributes), chunks[0]);
+
+        let header = ["WorldID", "ZoneID", "HostName", "InnerIp", "InstID"]
+            .into_iter()
+            .map(Cell::from)
+            .collect::<Row>()
+            .style(Style::default().add_modifier(Modifier::BOLD));
+        let rows = self.deploys.iter().map(|d| {
+            Row::new([
+                d.world_id.clone(),
+                d.zone_id.clone(),
+                d.host_name.clone(),
+                d.inner_ip.clone(),
+                d.inst_id.clone(),
+            ])
+        });
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(8),
+                Constraint::Length(8),
+                Constraint::Min(16),
+                Constraint::Min(16),
+                Constraint::Length(8),
+            ],
+        )
+        .header(header)
+        .block(
+            Block::default()
+                .borders(Borders::TOP)
+                .title(format!("部署 ({})", self.deploys.len())),
+        )
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        f.render_stateful_widget(table, chunks[1], &mut self.state.clone());
+
+        let help = format!(
+            "[{}/{}] select [{}] jump to host [{}] open config [{}] open binary [{}] browse files [{}] close",
+            self.key_config.scroll_up,
+            self.key_config.scroll_down,
+            self.key_config.enter,
+            self.key_config.open_config,
+            self.key_config.open_binary,
+            self.key_config.browse_files,
+            self.key_config.exit_popup,
+        );
+        f.render_widget(
+            Paragraph::new(Span::styled(help, Style::default().fg(Color::DarkGray))),
+            chunks[2],
+        );
+        Ok(())
+    }
+}
+
+impl Component for ProcDetailComponent {
+    fn commands(&self, out: &mut Vec<CommandInfo>) {
+        out.push(CommandInfo::new(command::proc_detail(&self.key_config)));
+        out.push(
+            CommandInfo::new(command::open_proc_file(&self.key_config))
+                .action("Open config file", Action::Keys(vec![self.key_config.open_config]))
+                .action("Open binary file", Action::Keys(vec![self.key_config.open_binary])),
+        );
+    }
+
+    fn event(&mut self, key: Key) -> Result<EventState> {
+        if !self.visible {
+            return Ok(EventState::NotConsumed);
+        }
+        if key == self.key_config.exit_popup {
+            self.hide();
+        } else if key == self.key_config.scroll_down || key == self.key_config.move_down {
+            self.next();
+        } else if key == self.key_config.scroll_up || key == self.key_config.move_up {
+            self.previous();
+        }
+        Ok(EventState::Consumed)
+    }
+
+    fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    fn hide(&mut self) {
+        self.visible = false;
+    }
+
+    fn show(&mut self) -> Result<()> {
+        self.visible = true;
+        Ok(())
+    }
+}
@@ -5,15 +5,17 @@ use ratatui::{
 };
 
 use crate::{config::KeyConfig, event::Key};
+use serde::Deserialize;
 
 use super::{
     command::{self, CommandInfo},
-    tabs::SelectedTab,
     Component, DrawableComponent, EventState,
 };
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum QueryType {
+    #[default]
     Tcm,
     Text,
 }
@@ -26,19 +28,17 @@ pub struct QueryInputComponents {
     /// History of recorded messages
     pub history: Vec<String>,
     pub key_config: KeyConfig,
-    pub tab: SelectedTab,
     pub color: Color,
 }
 
 impl QueryInputComponents {
-    pub const fn new(key_config: KeyConfig) -> Self {
+    pub const fn new(key_config: KeyConfig, query_type: QueryType) -> Self {
         Self {
-            query_type: QueryType::Tcm,
+            query_type,
             input: String::new(),
             cursor_position: 0,
             history: Vec::new(),
             key_config: key_config,
-            tab: SelectedTab::Tab1,
             color: Color::Reset,
         }
     }
@@ -161,10 +161,15 @@ impl Component for QueryInputComponents {
         out.push(CommandInfo::new(command::expand_collapse(&self.key_config)))
     }
     fn event(&mut self, key: crate::event::Key) -> anyhow::Result<EventState> {
+        if key == self.key_config.enter {
+            self.submit_message();
+            return Ok(EventState::Consumed);
+        }
+        if key == self.key_config.move_up || key == self.key_config.move_down {
+            self.swich_query_type();
+            return Ok(EventState::Consumed);
+        }
         match key {
-            Key::Enter => self.submit_message(),
-            Key::Up => self.swich_query_type(),
-            Key::Down => self.swich_query_type(),
             Key::Char(to_insert) => {
                 self.enter_char(to_insert);
             }
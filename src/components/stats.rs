@@ -0,0 +1,190 @@
+use super::{Component, DrawableComponent, EventState};
+use crate::components::command::CommandInfo;
+use crate::config::KeyConfig;
+use crate::database::{
+    count_func_instances, count_host_instances, count_world_zone_instances, select_all_host,
+    select_idle_hosts, CountRow,
+};
+use crate::event::Key;
+use crate::theme::Theme;
+use crate::tools::host::HostInfo;
+use anyhow::Result;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{palette::tailwind, Modifier, Style},
+    text::Line,
+    widgets::{Bar, BarChart, BarGroup, Block, Borders, Cell, Paragraph, Row, Table, TableState},
+    Frame,
+};
+use sqlx::SqlitePool;
+
+/// 负载最高的主机只显示前 N 台
+const TOP_HOSTS: usize = 10;
+
+/// 配置统计: world/zone 实例数, 主机负载, FuncID 实例分布和没有部署的主机
+pub struct StatsComponent {
+    world_zones: Vec<CountRow>,
+    top_hosts: Vec<CountRow>,
+    funcs: Vec<CountRow>,
+    idle_hosts: Vec<HostInfo>,
+    host_count: usize,
+    state: TableState,
+    theme: Theme,
+    key_config: KeyConfig,
+}
+
+impl StatsComponent {
+    pub async fn new(db: &SqlitePool, key_config: KeyConfig, theme: Theme) -> Result<Self> {
+        let mut hosts: Vec<String> = select_all_host(db).await?.into_iter().map(|h| h.inner_ip).collect();
+        hosts.sort();
+        hosts.dedup();
+        let idle_hosts = select_idle_hosts(db).await?;
+        let mut state = TableState::default();
+        state.select((!idle_hosts.is_empty()).then_some(0));
+        Ok(Self {
+            world_zones: count_world_zone_instances(db).await?,
+            top_hosts: count_host_instances(db, TOP_HOSTS).await?,
+            funcs: count_func_instances(db).await?,
+            idle_hosts,
+            host_count: hosts.len(),
+            state,
+            theme,
+            key_config,
+        })
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    fn instance_count(&self) -> u64 {
+        self.world_zones.iter().map(|row| row.count).sum()
+    }
+
+    fn step(&mut self, forward: bool) {
+        let count = self.idle_hosts.len();
+        if count == 0 {
+            return;
+        }
+        let i = self.state.selected().map_or(0, |i| {
+            if forward {
+                (i + 1) % count
+            } else {
+                (i + count - 1) % count
+            }
+        });
+        self.state.select(Some(i));
+    }
+
+    fn bar_chart<'a>(&self, title: &'a str, rows: &'a [CountRow], direction: Direction) -> BarChart<'a> {
+        let color = self.theme.accent(tailwind::INDIGO.c400);
+        let bars: Vec<Bar> = rows
+            .iter()
+            .map(|row| {
+                Bar::default()
+                    .label(Line::from(row.label.as_str()))
+                    .value(row.count)
+                    .style(Style::default().fg(color))
+                    .value_style(Style::default().add_modifier(Modifier::REVERSED))
+            })
+            .collect();
+        let chart = BarChart::default()
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .data(BarGroup::default().bars(&bars))
+            .direction(direction);
+        match direction {
+            Direction::Horizontal => chart.bar_width(1).bar_gap(0),
+            Direction::Vertical => chart.bar_width(7).bar_gap(1),
+        }
+    }
+
+    fn draw_idle_hosts(&self, f: &mut Frame, area: Rect) {
+        let colors = self.theme.table_colors(0);
+        let header = Row::new(["InnerIP", "HostName", "WorldID", "ZoneID"])
+            .style(Style::default().fg(colors.header_fg).bg(colors.header_bg));
+        let rows = self.idle_hosts.iter().map(|host| {
+            Row::new([
+                Cell::from(host.inner_ip.as_str()),
+                Cell::from(host.host_name.as_str()),
+                Cell::from(host.world_id.as_str()),
+                Cell::from(host.zone_id.as_str()),
+            ])
+        });
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Min(15),
+                Constraint::Min(10),
+                Constraint::Length(8),
+                Constraint::Length(8),
+            ],
+        )
+        .header(header)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("无部署的主机 ({})", self.idle_hosts.len())),
+        )
+        .style(Style::default().fg(colors.row_fg).bg(colors.buffer_bg))
+        .highlight_style(
+            Style::default()
+                .add_modifier(Modifier::REVERSED)
+                .fg(colors.selected_style_fg),
+        );
+        f.render_stateful_widget(table, area, &mut self.state.clone());
+    }
+}
+
+impl DrawableComponent for StatsComponent {
+    fn draw(&self, f: &mut Frame, area: Rect, _focused: bool) -> Result<()> {
+        let [summary_area, top_area, bottom_area] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Percentage(50),
+            Constraint::Percentage(50),
+        ])
+        .areas(area);
+        let [zone_area, host_area] =
+            Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(top_area);
+        let [func_area, idle_area] =
+            Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(bottom_area);
+
+        let summary = format!(
+            " 主机 {} | 进程 {} | 实例 {} | 无部署主机 {}",
+            self.host_count,
+            self.funcs.len(),
+            self.instance_count(),
+            self.idle_hosts.len()
+        );
+        f.render_widget(Paragraph::new(summary), summary_area);
+        f.render_widget(
+            self.bar_chart("World.Zone 实例数", &self.world_zones, Direction::Vertical),
+            zone_area,
+        );
+        let host_title = format!("实例最多的主机 (前 {})", TOP_HOSTS);
+        f.render_widget(
+            self.bar_chart(&host_title, &self.top_hosts, Direction::Horizontal),
+            host_area,
+        );
+        f.render_widget(
+            self.bar_chart("FuncID 实例数", &self.funcs, Direction::Horizontal),
+            func_area,
+        );
+        self.draw_idle_hosts(f, idle_area);
+        Ok(())
+    }
+}
+
+impl Component for StatsComponent {
+    fn commands(&self, _out: &mut Vec<CommandInfo>) {}
+
+    fn event(&mut self, key: Key) -> Result<EventState> {
+        if key == self.key_config.scroll_down || key == self.key_config.move_down {
+            self.step(true);
+        } else if key == self.key_config.scroll_up || key == self.key_config.move_up {
+            self.step(false);
+        } else {
+            return Ok(EventState::NotConsumed);
+        }
+        Ok(EventState::Consumed)
+    }
+}
@@ -1,33 +1,58 @@
-use super::command::CommandText;
+use super::command::{self, Action, CommandText};
+use super::hex_view::HexView;
 use super::{CommandInfo, Component, DrawableComponent, EventState};
 use crate::ui::scrollbar::{draw_scrollbar, Orientation};
-use crate::ui::stateful_paragraph::{ParagraphState, ScrollPos, StatefulParagraph};
-use crate::utils::{file_content, tabs_to_spaces};
+use crate::ui::stateful_paragraph::{
+    gutter_width, line_rows, ParagraphState, ScrollPos, StatefulParagraph,
+};
+use crate::utils::{file_content, is_binary_file, tabs_to_spaces};
+use crate::theme::Theme;
+use crate::tools::follow::LogFollower;
+use crate::tools::text_search::{compile, find_matches, overlay, TextMatch};
 use crate::{config::KeyConfig, event::Key};
-use anyhow::Result;
-use itertools::Either;
+use anyhow::{Context, Result};
+use crossterm::event::{MouseEvent, MouseEventKind};
 use once_cell::sync::Lazy;
+use regex::Regex;
 use ratatui::{
     layout::Rect,
     style::{Color, Modifier},
     text::{Line, Span, Text},
-    widgets::{Block, Borders, Wrap},
+    widgets::{block::{Position, Title}, Block, Borders, Wrap},
     Frame,
 };
 use scopetime::scope_time;
 use std::cell::Cell;
+use unicode_width::UnicodeWidthStr;
+use std::collections::HashMap;
 use std::ffi::OsStr;
 use std::ops::Range;
 use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 use syntect::{
     highlighting::{
-        FontStyle, HighlightState, Highlighter, RangedHighlightIterator, Style, ThemeSet,
+        FontStyle, HighlightState, Highlighter, RangedHighlightIterator, Style,
+        Theme as SyntectTheme,
     },
-    parsing::{ParseState, ScopeStack, SyntaxSet},
+    parsing::{ParseState, ScopeStack, SyntaxReference, SyntaxSet},
 };
 
+/// 跳到匹配时在匹配行上方保留的行数
+const SEARCH_CONTEXT: usize = 3;
+/// 不换行时每次水平滚动的列数, 以及跳到匹配时左侧保留的列数
+const HSCROLL_STEP: u16 = 8;
+
+/// 跟踪文件时最多保留的行数
+const FOLLOW_LINES: usize = 10_000;
+
+/// 后台按块高亮, 每块开头保存一次解析状态
+const HIGHLIGHT_BLOCK: usize = 256;
+/// 离可见区域超过这么多块的高亮结果会被丢弃
+const CACHED_BLOCKS: usize = 32;
+
 static SYNTAX_SET: Lazy<SyntaxSet> = Lazy::new(two_face::syntax::extra_no_newlines);
-static THEME_SET: Lazy<ThemeSet> = Lazy::new(ThemeSet::load_defaults);
 
 #[derive(Copy, Clone, Debug)]
 pub enum MoveSelection {
@@ -50,140 +75,619 @@ pub fn scroll(key_config: &KeyConfig) -> CommandText {
     )
 }
 
+/// 底部正在输入的内容
+enum Prompt {
+    Search(String),
+    GotoLine(String),
+    Filter(String),
+}
+
+/// tail -f 模式: 文件增长时追加新行, 只显示匹配过滤条件的行
+struct Follow {
+    follower: LogFollower,
+    paused: bool,
+    /// 输入的过滤条件和编译后的正则
+    filter: Option<(String, Regex)>,
+    /// 已经显示的内容, 为 None 时下次刷新重新生成全部内容
+    shown: Option<Shown>,
+}
+
+/// 显示内容对应的读取位置
+#[derive(Clone, Copy)]
+struct Shown {
+    generation: usize,
+    pushed: usize,
+    /// 显示的完整行数, 后面可能还有一行没有换行符的内容
+    lines: usize,
+}
+
+impl Follow {
+    fn is_match(&self, line: &str) -> bool {
+        self.filter.as_ref().is_none_or(|(_, re)| re.is_match(line))
+    }
+
+    /// 最后一行还没有换行符的部分, 不匹配过滤条件时不显示
+    fn partial(&self) -> Option<&str> {
+        let partial = self.follower.partial();
+        (!partial.is_empty() && self.is_match(partial)).then_some(partial)
+    }
+}
+
 pub struct SyntaxTextComponent {
-    content: Option<(String, Either<SyntaxText, String>)>,
+    content: Option<(String, SyntaxText)>,
     key_config: KeyConfig,
     visible: bool,
     // file_path: Option<PathBuf>,
     paragraph_state: Cell<ParagraphState>,
-    theme: Color,
+    /// 第一个可见行, 按原始行计算, 和是否换行无关
+    top: Cell<usize>,
+    /// 上次绘制时 top 的最大值和可见行数
+    max_top: Cell<usize>,
+    rows: Cell<usize>,
+    theme: Theme,
+    prompt: Option<Prompt>,
+    search: String,
+    regex: bool,
+    matches: Vec<TextMatch>,
+    current: usize,
+    search_error: Option<String>,
+    /// 长行自动换行, 关闭时可以水平滚动
+    wrap: bool,
+    /// 标记用来比较的文件, 关闭文件后仍然保留
+    marked: Option<PathBuf>,
+    follow: Option<Follow>,
+    /// 二进制文件用十六进制显示, 这时 content 为空
+    hex: Option<HexView>,
 }
 
 impl SyntaxTextComponent {
     ///
-    pub fn new(key: KeyConfig) -> Self {
+    pub fn new(key: KeyConfig, theme: Theme) -> Self {
         Self {
             content: None,
             key_config: key,
             visible: false,
             // file_path: None,
-            theme: Color::LightMagenta,
+            theme,
             paragraph_state: Cell::new(ParagraphState::default()),
+            top: Cell::new(0),
+            max_top: Cell::new(0),
+            rows: Cell::new(0),
+            marked: None,
+            follow: None,
+            hex: None,
+            prompt: None,
+            search: String::new(),
+            regex: false,
+            matches: Vec::new(),
+            current: 0,
+            search_error: None,
+            wrap: true,
         }
     }
 
     pub fn clear(&mut self) {
-        self.content = None
+        self.content = None;
+        self.follow = None;
+        self.hex = None;
+        self.prompt = None;
+        self.clear_search();
+        self.paragraph_state.set(ParagraphState::default());
+        self.top.set(0);
     }
 
     ///
-    pub fn load_file(&mut self, path: &std::path::PathBuf) -> anyhow::Result<()> {
-        let content = file_content(&path)?;
-        let content = tabs_to_spaces(content);
-        let p = Path::new(&path);
-        let sy = SyntaxText::new(content, p)?;
-        self.content = Some((path.to_str().unwrap().to_string(), Either::Left(sy)));
+    pub fn load_file(&mut self, path: &Path) -> anyhow::Result<()> {
+        if path.exists() && is_binary_file(path)? {
+            self.clear();
+            self.hex = Some(HexView::open(path)?);
+            return Ok(());
+        }
+        self.hex = None;
+        let sy = SyntaxText::open(path, self.theme.syntax_theme())?;
+        self.content = Some((path.display().to_string(), sy));
+        self.update_matches();
         Ok(())
     }
 
-    fn scroll(&self, nav: MoveSelection) -> bool {
-        let state = self.paragraph_state.get();
+    /// 跟踪文件的末尾, 新内容追加到最后并自动滚动到底部
+    pub fn follow_file(&mut self, path: &Path) -> anyhow::Result<()> {
+        let follower = LogFollower::open(path, FOLLOW_LINES)
+            .with_context(|| format!("Follow file -> [{}] failed", path.display()))?;
+        self.follow = Some(Follow {
+            follower,
+            paused: false,
+            filter: None,
+            shown: None,
+        });
+        self.refresh_follow()
+    }
 
-        let new_scroll_pos = match nav {
-            MoveSelection::Down => state.scroll().y.saturating_add(1),
-            MoveSelection::Up => state.scroll().y.saturating_sub(1),
-            MoveSelection::Top => 0,
-            MoveSelection::End => state
-                .lines()
-                .saturating_sub(state.height().saturating_sub(2)),
-            MoveSelection::PageUp => state
-                .scroll()
-                .y
-                .saturating_sub(state.height().saturating_sub(2)),
-            MoveSelection::PageDown => state
-                .scroll()
-                .y
-                .saturating_add(state.height().saturating_sub(2)),
-            _ => state.scroll().y,
+    /// 每个 tick 读取跟踪文件新增的内容, 暂停时只读取不刷新显示
+    pub fn tick(&mut self) -> anyhow::Result<()> {
+        let Some(follow) = self.follow.as_mut() else {
+            return Ok(());
+        };
+        if follow.follower.poll()? && !follow.paused {
+            self.refresh_follow()?;
+        }
+        Ok(())
+    }
+
+    /// 把跟踪到的新行追加到显示的内容后面, 没有暂停时滚动到底部. 文件被截断,
+    /// 轮转, 有行来不及显示就被丢掉或者显示的行太多时重新生成全部内容
+    fn refresh_follow(&mut self) -> anyhow::Result<()> {
+        let Some(follow) = self.follow.as_mut() else {
+            return Ok(());
+        };
+        let follower = &follow.follower;
+        let appended = match (follow.shown, self.content.as_mut()) {
+            (Some(shown), Some((_, sy)))
+                if shown.generation == follower.generation() && shown.lines < FOLLOW_LINES * 2 =>
+            {
+                follower.lines_after(shown.pushed).map(|new| {
+                    let mut lines = new.filter(|line| follow.is_match(line)).collect::<Vec<_>>();
+                    let complete = shown.lines + lines.len();
+                    lines.extend(follow.partial());
+                    sy.replace_lines(shown.lines, &lines.join("\n"));
+                    complete
+                })
+            }
+            _ => None,
+        };
+        let complete = match appended {
+            Some(complete) => complete,
+            None => {
+                let mut lines = follower
+                    .complete_lines()
+                    .filter(|line| follow.is_match(line))
+                    .collect::<Vec<_>>();
+                let complete = lines.len();
+                lines.extend(follow.partial());
+                let path = follower.path();
+                let sy = SyntaxText::new(lines.join("\n"), path, self.theme.syntax_theme())?;
+                self.content = Some((path.display().to_string(), sy));
+                complete
+            }
+        };
+        follow.shown = Some(Shown {
+            generation: follower.generation(),
+            pushed: follower.pushed(),
+            lines: complete,
+        });
+        if !follow.paused {
+            self.goto_line(usize::MAX);
+        }
+        let current = self.current;
+        self.update_matches();
+        self.current = current.min(self.matches.len().saturating_sub(1));
+        Ok(())
+    }
+
+    fn toggle_pause(&mut self) -> anyhow::Result<()> {
+        if let Some(follow) = self.follow.as_mut() {
+            follow.paused = !follow.paused;
+            self.refresh_follow()?;
+        }
+        Ok(())
+    }
+
+    fn set_filter(&mut self, pattern: &str) {
+        let filter = match pattern {
+            "" => Ok(None),
+            _ => compile(pattern, self.regex).map(|re| Some((pattern.to_string(), re))),
+        };
+        match (filter, self.follow.as_mut()) {
+            (Ok(filter), Some(follow)) => {
+                follow.filter = filter;
+                follow.shown = None;
+                self.search_error = None;
+            }
+            (Err(e), _) => self.search_error = Some(format!("{:#}", e)),
+            _ => {}
+        }
+        if let Err(e) = self.refresh_follow() {
+            self.search_error = Some(format!("{:#}", e));
+        }
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        if let Some(hex) = &self.hex {
+            return Some(hex.path());
+        }
+        self.content.as_ref().map(|(_, sy)| sy.path())
+    }
+
+    /// 第一个可见行, 从 1 开始
+    pub fn current_line(&self) -> usize {
+        self.top.get() + 1
+    }
+
+    pub fn is_binary(&self) -> bool {
+        self.hex.is_some()
+    }
+
+    /// 文件在外部被修改后重新读取, 保持滚动位置
+    pub fn reload(&mut self) -> anyhow::Result<()> {
+        if self.follow.is_some() {
+            return self.refresh_follow();
+        }
+        let Some(path) = self.path().map(Path::to_path_buf) else {
+            return Ok(());
         };
+        let top = self.top.get();
+        self.load_file(&path)?;
+        self.top.set(top);
+        Ok(())
+    }
+
+    pub fn marked(&self) -> Option<&Path> {
+        self.marked.as_deref()
+    }
+
+    /// 再次标记同一个文件时取消标记
+    fn toggle_mark(&mut self) {
+        let path = self.path().map(Path::to_path_buf);
+        self.marked = if self.marked == path { None } else { path };
+    }
+
+    fn clear_search(&mut self) {
+        self.search.clear();
+        self.matches.clear();
+        self.current = 0;
+        self.search_error = None;
+    }
+
+    fn update_matches(&mut self) {
+        match find_matches(self.content_text(), &self.search, self.regex) {
+            Ok(matches) => {
+                self.matches = matches;
+                self.search_error = None;
+            }
+            Err(e) => {
+                self.matches.clear();
+                self.search_error = Some(format!("{:#}", e));
+            }
+        }
+        self.current = 0;
+    }
 
-        self.set_scroll(new_scroll_pos)
+    /// 从当前可见的第一行开始找第一个匹配
+    fn jump_first(&mut self) {
+        let top = self.top.get();
+        self.current = self.matches.iter().position(|m| m.line >= top).unwrap_or(0);
+        self.jump_current();
     }
 
-    fn set_scroll(&self, pos: u16) -> bool {
+    fn jump(&mut self, forward: bool) {
+        let count = self.matches.len();
+        if count == 0 {
+            return;
+        }
+        self.current = if forward {
+            (self.current + 1) % count
+        } else {
+            (self.current + count - 1) % count
+        };
+        self.jump_current();
+    }
+
+    /// 滚动到当前匹配, 不换行时匹配不在可见列内则水平滚动过去
+    fn jump_current(&mut self) {
+        let Some(m) = self.matches.get(self.current) else {
+            return;
+        };
+        self.goto_line(m.line.saturating_sub(SEARCH_CONTEXT));
+        if self.wrap {
+            return;
+        }
+        let Some(line) = self.content.as_ref().and_then(|(_, sy)| sy.line(m.line)) else {
+            return;
+        };
+        let column = |end: usize| u16::try_from(line[..end].width()).unwrap_or(u16::MAX);
+        let (start, end) = (column(m.range.start), column(m.range.end));
         let mut state = self.paragraph_state.get();
+        let x = state.scroll().x;
+        if start < x || end > x.saturating_add(state.width()) {
+            state.set_scroll(ScrollPos {
+                x: start.saturating_sub(HSCROLL_STEP),
+                y: state.scroll().y,
+            });
+            self.paragraph_state.set(state);
+        }
+    }
 
-        let new_scroll_pos = pos.min(
-            state
-                .lines()
-                .saturating_sub(state.height().saturating_sub(2)),
-        );
+    /// 行号从 0 开始, 超出范围时在下次绘制时修正
+    fn goto_line(&self, line: usize) {
+        self.top.set(line);
+    }
 
-        if new_scroll_pos == state.scroll().y {
-            return false;
+    /// 第一个可见行不变, 只重置水平滚动
+    fn toggle_wrap(&mut self) {
+        self.wrap = !self.wrap;
+        self.paragraph_state.set(ParagraphState::default());
+    }
+
+    fn content_text(&self) -> &str {
+        self.content.as_ref().map_or("", |(_, sy)| sy.text())
+    }
+
+    fn prompt_event(&mut self, key: Key) {
+        let Some(prompt) = self.prompt.as_mut() else {
+            return;
+        };
+        match (prompt, key) {
+            (Prompt::Search(input) | Prompt::Filter(input), Key::Char(c)) => input.push(c),
+            (Prompt::GotoLine(input), Key::Char(c)) if c.is_ascii_digit() => input.push(c),
+            (Prompt::Search(input) | Prompt::GotoLine(input) | Prompt::Filter(input), Key::Backspace) => {
+                input.pop();
+            }
+            (Prompt::Search(_) | Prompt::Filter(_), _) if key == self.key_config.toggle_focus => {
+                self.regex = !self.regex
+            }
+            _ if key == self.key_config.enter => match self.prompt.take() {
+                Some(Prompt::Search(input)) => {
+                    self.search = input;
+                    self.update_matches();
+                    self.jump_first();
+                }
+                Some(Prompt::GotoLine(input)) => {
+                    if let Ok(line) = input.parse::<usize>() {
+                        self.goto_line(line.saturating_sub(1));
+                    }
+                }
+                Some(Prompt::Filter(input)) => self.set_filter(&input),
+                None => {}
+            },
+            _ if key == self.key_config.exit_popup => self.prompt = None,
+            _ => {}
+        }
+    }
+
+    fn hit_style(&self, current: bool) -> ratatui::style::Style {
+        let style = ratatui::style::Style::default();
+        match (self.theme.is_mono(), current) {
+            (true, false) => style.add_modifier(Modifier::REVERSED),
+            (true, true) => style.add_modifier(Modifier::REVERSED | Modifier::BOLD),
+            (false, false) => style.fg(Color::Black).bg(Color::Yellow),
+            (false, true) => style.fg(Color::Black).bg(Color::LightRed),
         }
+    }
+
+    /// 可见范围内的文本, 搜索匹配叠加在语法高亮之上. 还没高亮的行显示纯文本
+    fn window_text<'a>(&self, sy: &'a SyntaxText, lines: Range<usize>) -> Text<'a> {
+        let first_match = self.matches.partition_point(|m| m.line < lines.start);
+        let mut matches = self.matches.iter().enumerate().skip(first_match).peekable();
+        lines
+            .map(|number| {
+                let content = sy.line(number).unwrap_or_default();
+                let items = sy.styles(number);
+                let mut hits = Vec::new();
+                while let Some((i, m)) = matches.next_if(|(_, m)| m.line == number) {
+                    hits.push((m.range.clone(), self.hit_style(i == self.current)));
+                }
+                let spans: Vec<Span> = overlay(&items, &hits, |a, b| a.patch(b))
+                    .into_iter()
+                    .map(|(style, range)| Span::styled(&content[range], style))
+                    .collect();
+                Line::from(spans)
+            })
+            .collect::<Vec<_>>()
+            .into()
+    }
+
+    /// 最后一屏的第一行, 换行时从文件末尾往前累加每行占用的行数
+    fn last_top(&self, sy: &SyntaxText, rows: usize, width: u16) -> usize {
+        let total = sy.line_count();
+        if !self.wrap {
+            return total.saturating_sub(rows);
+        }
+        let mut used = 0;
+        for number in (0..total).rev() {
+            let line = Line::raw(sy.line(number).unwrap_or_default());
+            used += line_rows(&line, width, true);
+            if used > rows {
+                return number + 1;
+            }
+        }
+        0
+    }
+
+    fn title(&self) -> String {
+        let name = self
+            .content
+            .as_ref()
+            .map(|(name, _)| name.clone())
+            .unwrap_or_default();
+        let x = self.paragraph_state.get().scroll().x;
+        let name = if self.wrap || x == 0 {
+            name
+        } else {
+            format!("{} [col {}]", name, x + 1)
+        };
+        let name = match self.marked() {
+            Some(marked) if Some(marked) == self.path() => format!("{} [已标记]", name),
+            Some(marked) => format!(
+                "{} [{} 与 {} 比较]",
+                name,
+                self.key_config.diff_marked,
+                marked.display()
+            ),
+            None => name,
+        };
+        let name = match &self.follow {
+            Some(follow) => {
+                let state = if follow.paused { "已暂停" } else { "跟踪中" };
+                match &follow.filter {
+                    Some((pattern, _)) => format!("{} [{}] [过滤 {}]", name, state, pattern),
+                    None => format!("{} [{}]", name, state),
+                }
+            }
+            None => name,
+        };
+        if self.search.is_empty() {
+            name
+        } else if self.matches.is_empty() {
+            format!("{} [无匹配]", name)
+        } else {
+            format!("{} [{}/{}]", name, self.current + 1, self.matches.len())
+        }
+    }
+
+    fn bottom_title(&self) -> String {
+        let mode = if self.regex { "正则" } else { "文本" };
+        match &self.prompt {
+            Some(Prompt::Search(input)) => format!(
+                " {}{}█ [{}] {} 切换 ",
+                self.key_config.filter, input, mode, self.key_config.toggle_focus
+            ),
+            Some(Prompt::GotoLine(input)) => format!(" {}{}█ ", self.key_config.goto_line, input),
+            Some(Prompt::Filter(input)) => format!(
+                " {}{}█ [{}] {} 切换, 留空取消过滤 ",
+                self.key_config.filter_follow, input, mode, self.key_config.toggle_focus
+            ),
+            None => match &self.search_error {
+                Some(error) => format!(" {} ", error),
+                None if self.follow.is_some() => format!(
+                    " [{}] search [{}] filter [{}] pause [{}] wrap {} ",
+                    self.key_config.filter,
+                    self.key_config.filter_follow,
+                    self.key_config.pause_follow,
+                    self.key_config.toggle_wrap,
+                    if self.wrap { "on" } else { "off" }
+                ),
+                None => format!(
+                    " [{}] search [{}/{}] next/prev [{}] goto line [{}] wrap {} ",
+                    self.key_config.filter,
+                    self.key_config.search_next,
+                    self.key_config.search_prev,
+                    self.key_config.goto_line,
+                    self.key_config.toggle_wrap,
+                    if self.wrap { "on" } else { "off" }
+                ),
+            },
+        }
+    }
 
+    /// 切换主题后按新的语法高亮主题重新加载当前文件
+    pub fn set_theme(&mut self, theme: Theme) -> anyhow::Result<()> {
+        self.theme = theme;
+        if self.hex.is_some() {
+            return Ok(());
+        }
+        if let Some(follow) = self.follow.as_mut() {
+            follow.shown = None;
+            return self.refresh_follow();
+        }
+        let path = match &self.content {
+            Some((_, sy)) => sy.path().to_path_buf(),
+            _ => return Ok(()),
+        };
+        self.load_file(&path)
+    }
+
+    fn scroll(&self, nav: MoveSelection) -> bool {
+        if let Some(hex) = &self.hex {
+            return hex.scroll(nav);
+        }
+        let top = self.top.get();
+        let rows = self.rows.get();
+
+        let new_top = match nav {
+            MoveSelection::Down => top.saturating_add(1),
+            MoveSelection::Up => top.saturating_sub(1),
+            MoveSelection::Top => 0,
+            MoveSelection::End => self.max_top.get(),
+            MoveSelection::PageUp => top.saturating_sub(rows),
+            MoveSelection::PageDown => top.saturating_add(rows),
+            MoveSelection::Left | MoveSelection::Right => return self.scroll_horizontal(nav),
+        };
+
+        self.set_scroll(new_top)
+    }
+
+    /// 换行时没有水平滚动, 最多滚到最长一行的末尾
+    fn scroll_horizontal(&self, nav: MoveSelection) -> bool {
+        let mut state = self.paragraph_state.get();
+        let x = state.scroll().x;
+        let max = state.max_width().saturating_sub(state.width());
+        let new_x = match nav {
+            _ if self.wrap => 0,
+            MoveSelection::Left => x.saturating_sub(HSCROLL_STEP),
+            _ => x.saturating_add(HSCROLL_STEP).min(max),
+        };
+        if new_x == x {
+            return false;
+        }
         state.set_scroll(ScrollPos {
-            x: 0,
-            y: new_scroll_pos,
+            x: new_x,
+            y: state.scroll().y,
         });
         self.paragraph_state.set(state);
+        true
+    }
 
+    fn set_scroll(&self, top: usize) -> bool {
+        let new_top = top.min(self.max_top.get());
+        if new_top == self.top.get() {
+            return false;
+        }
+        self.top.set(new_top);
         true
     }
 }
 
 impl DrawableComponent for SyntaxTextComponent {
     fn draw(&self, f: &mut Frame, area: Rect, _foced: bool) -> Result<()> {
-        if self.visible {
-            let text = self.content.as_ref().map_or_else(
-                || Text::from(""),
-                |(_, content)| match content {
-                    Either::Left(syn) => syn.into(),
-                    Either::Right(s) => Text::from(s.as_str()),
-                },
-            );
-
-            let title = format!(
-                "{}",
-                self.content
-                    .as_ref()
-                    .map(|(name, _)| name.clone())
-                    .unwrap_or_default(),
-            );
-
-            let content = StatefulParagraph::new(text)
-                .wrap(Wrap { trim: false })
-                .block(
-                    Block::default()
-                        .title(title)
-                        .borders(Borders::ALL)
-                        .border_style(return_title_theme(self.visible)),
-                );
+        if !self.visible {
+            return Ok(());
+        }
+        if let Some(hex) = &self.hex {
+            return hex.draw(f, area, &self.theme, return_title_theme(self.visible, &self.theme));
+        }
+        let Some((_, sy)) = &self.content else {
+            return Ok(());
+        };
 
-            let mut state = self.paragraph_state.get();
+        // 只构造可见的行, 大文件也不会每帧处理整个文件
+        let rows = usize::from(area.height.saturating_sub(2));
+        let total = sy.line_count();
+        let width = area.width.saturating_sub(2 + gutter_width(total));
+        let max_top = self.last_top(sy, rows, width);
+        let top = self.top.get().min(max_top);
+        let end = top.saturating_add(rows).min(total);
+        self.max_top.set(max_top);
+        self.rows.set(rows);
+        self.top.set(top);
+        sy.request(top..end);
 
-            f.render_stateful_widget(content, area, &mut state);
+        let gutter_style = ratatui::style::Style::default().fg(Color::DarkGray);
+        let mut content = StatefulParagraph::new(self.window_text(sy, top..end))
+            .line_numbers(gutter_style)
+            .window(top, total)
+            .block(
+                Block::default()
+                    .title(self.title())
+                    .title(Title::from(self.bottom_title()).position(Position::Bottom))
+                    .borders(Borders::ALL)
+                    .border_style(return_title_theme(self.visible, &self.theme)),
+            );
+        if self.wrap {
+            content = content.wrap(Wrap { trim: false });
+        }
 
-            self.paragraph_state.set(state);
+        let mut state = self.paragraph_state.get();
+        f.render_stateful_widget(content, area, &mut state);
+        self.paragraph_state.set(state);
 
-            self.set_scroll(state.scroll().y);
-
-            if self.visible {
-                draw_scrollbar(
-                    f,
-                    area,
-                    &self.theme,
-                    usize::from(
-                        state
-                            .lines()
-                            .saturating_sub(state.height().saturating_sub(2)),
-                    ),
-                    usize::from(state.scroll().y),
-                    Orientation::Vertical,
-                );
-            }
-        }
+        draw_scrollbar(
+            f,
+            area,
+            &self.theme.accent(Color::LightMagenta),
+            max_top,
+            top,
+            Orientation::Vertical,
+        );
 
         Ok(())
     }
@@ -191,29 +695,75 @@ impl DrawableComponent for SyntaxTextComponent {
 
 impl Component for SyntaxTextComponent {
     fn commands(&self, out: &mut Vec<CommandInfo>) {
-        out.push(CommandInfo::new(scroll(&self.key_config)))
+        out.push(CommandInfo::new(scroll(&self.key_config)));
+        out.push(
+            CommandInfo::new(command::file_search(&self.key_config))
+                .action("Search in file", Action::Keys(vec![self.key_config.filter])),
+        );
+        out.push(
+            CommandInfo::new(command::toggle_wrap(&self.key_config))
+                .action("Toggle wrap", Action::Keys(vec![self.key_config.toggle_wrap])),
+        );
+        out.push(
+            CommandInfo::new(command::diff_file(&self.key_config))
+                .action("Mark file for diff", Action::Keys(vec![self.key_config.mark]))
+                .action("Diff with marked file", Action::Keys(vec![self.key_config.diff_marked])),
+        );
     }
 
+    /// 搜索和跳转行号的输入框打开时接管所有按键, Esc 先清除搜索高亮
     fn event(&mut self, key: crate::event::Key) -> anyhow::Result<EventState> {
-        let nva: MoveSelection;
+        if self.prompt.is_some() {
+            self.prompt_event(key);
+            return Ok(EventState::Consumed);
+        }
+        // 十六进制视图只能滚动
+        if self.hex.is_some() {
+            return self.scroll_event(key);
+        }
+        if key == self.key_config.filter {
+            self.prompt = Some(Prompt::Search(String::new()));
+            return Ok(EventState::Consumed);
+        }
         match key {
-            Key::Char('k') | Key::Up => nva = MoveSelection::Up,
-            Key::Char('j') | Key::Down => nva = MoveSelection::Down,
-            Key::Char('l') | Key::Left => nva = MoveSelection::Left,
-            Key::Char('h') | Key::Right => nva = MoveSelection::Right,
-            Key::PageDown | Key::Ctrl('f') => nva = MoveSelection::PageDown,
-            Key::PageUp | Key::Ctrl('b') => nva = MoveSelection::PageUp,
-            Key::End => nva = MoveSelection::End,
-            // Key::
-            // MoveSelection::End => self.selection_end(selection),
-            _ => nva = MoveSelection::Top,
+            _ if key == self.key_config.goto_line => {
+                self.prompt = Some(Prompt::GotoLine(String::new()))
+            }
+            _ if key == self.key_config.search_next => self.jump(true),
+            _ if key == self.key_config.search_prev => self.jump(false),
+            _ if key == self.key_config.toggle_wrap => self.toggle_wrap(),
+            _ if key == self.key_config.mark => self.toggle_mark(),
+            _ if key == self.key_config.pause_follow && self.follow.is_some() => self.toggle_pause()?,
+            _ if key == self.key_config.filter_follow && self.follow.is_some() => {
+                let input = self
+                    .follow
+                    .as_ref()
+                    .and_then(|follow| follow.filter.as_ref())
+                    .map(|(pattern, _)| pattern.clone())
+                    .unwrap_or_default();
+                self.prompt = Some(Prompt::Filter(input));
+            }
+            _ if key == self.key_config.exit_popup && !self.search.is_empty() => self.clear_search(),
+            _ => return self.scroll_event(key),
+        }
+        Ok(EventState::Consumed)
+    }
+
+    /// 滚轮每次滚动三行
+    fn mouse_event(&mut self, mouse: MouseEvent) -> anyhow::Result<EventState> {
+        let nav = match mouse.kind {
+            MouseEventKind::ScrollDown => MoveSelection::Down,
+            MouseEventKind::ScrollUp => MoveSelection::Up,
+            _ => return Ok(EventState::NotConsumed),
         };
-        if self.scroll(nva) {
-            return Ok(EventState::Consumed);
-        } else {
-            return Ok(EventState::NotConsumed);
+        for _ in 0..3 {
+            self.scroll(nav);
         }
+        Ok(EventState::Consumed)
+    }
 
+    fn is_visible(&self) -> bool {
+        self.visible
     }
 
     ///
@@ -227,89 +777,326 @@ impl Component for SyntaxTextComponent {
         Ok(())
     }
 }
-#[derive(Debug)]
-struct SyntaxLine {
-    items: Vec<(Style, usize, Range<usize>)>,
-}
 
-#[derive(Debug)]
-pub struct SyntaxText {
-    text: String,
-    lines: Vec<SyntaxLine>,
-    path: PathBuf,
+impl SyntaxTextComponent {
+    fn scroll_event(&mut self, key: Key) -> anyhow::Result<EventState> {
+        let nva = if key == self.key_config.scroll_up || key == self.key_config.move_up {
+            MoveSelection::Up
+        } else if key == self.key_config.scroll_down || key == self.key_config.move_down {
+            MoveSelection::Down
+        } else if key == self.key_config.scroll_left || key == Key::Left {
+            MoveSelection::Left
+        } else if key == self.key_config.scroll_right || key == Key::Right {
+            MoveSelection::Right
+        } else if key == self.key_config.scroll_down_multiple_lines || key == Key::PageDown {
+            MoveSelection::PageDown
+        } else if key == self.key_config.scroll_up_multiple_lines || key == Key::PageUp {
+            MoveSelection::PageUp
+        } else if key == self.key_config.scroll_to_top || key == Key::Home {
+            MoveSelection::Top
+        } else if key == self.key_config.scroll_to_bottom || key == Key::End {
+            MoveSelection::End
+        } else {
+            return Ok(EventState::NotConsumed);
+        };
+        self.scroll(nva);
+        Ok(EventState::Consumed)
+    }
 }
 
-impl SyntaxText {
-    pub fn new(text: String, file_path: &Path) -> anyhow::Result<Self> {
-        scope_time!("syntax_highlighting");
-
-        let mut state = {
-            scope_time!("syntax_highlighting.0");
-            let syntax = file_path.extension().and_then(OsStr::to_str).map_or_else(
-                || SYNTAX_SET.find_syntax_by_path(file_path.to_str().unwrap_or_default()),
-                |ext| SYNTAX_SET.find_syntax_by_extension(ext),
-            );
+/// 一行中每一段的样式和字节范围
+pub type LineStyle = Vec<(ratatui::style::Style, Range<usize>)>;
 
-            ParseState::new(syntax.unwrap_or_else(|| SYNTAX_SET.find_syntax_plain_text()))
-        };
+/// 每块开头的解析状态
+type Checkpoint = (ParseState, HighlightState);
 
-        let highlighter = Highlighter::new(&THEME_SET.themes["base16-eighties.dark"]);
+type BlockCache = Arc<Mutex<HashMap<usize, Arc<Vec<LineStyle>>>>>;
 
-        let mut syntax_lines: Vec<SyntaxLine> = Vec::new();
+/// 打开文件时只建立行索引, 语法高亮在后台线程里按可见区域逐块完成
+pub struct SyntaxText {
+    text: Arc<String>,
+    /// 每行在 text 中的字节范围, 不含换行符
+    lines: Arc<Vec<Range<usize>>>,
+    path: PathBuf,
+    /// 没有语法高亮主题时为 None
+    worker: Option<HighlightWorker>,
+}
 
-        let mut highlight_state = HighlightState::new(&highlighter, ScopeStack::new());
+struct HighlightWorker {
+    /// 线程在 SyntaxText 释放后退出
+    requests: Sender<HighlightRequest>,
+    blocks: BlockCache,
+    /// 每次修改文本加一, 后台线程只保存和当前版本一致的高亮结果
+    version: Arc<AtomicUsize>,
+}
 
-        {
-            // let total_count = text.lines().count();
+enum HighlightRequest {
+    /// 当前可见的行
+    Viewport(Range<usize>),
+    /// 保留前 keep 行, 后面换成 text
+    Replace {
+        keep: usize,
+        text: String,
+        version: usize,
+    },
+}
 
-            for (number, line) in text.lines().enumerate() {
-                let ops = state.parse_line(line, &SYNTAX_SET)?;
-                let iter = RangedHighlightIterator::new(
-                    &mut highlight_state,
-                    &ops[..],
-                    line,
-                    &highlighter,
-                );
+/// 启动后台高亮线程, `theme` 为 None 时不做语法高亮
+fn highlight_worker(
+    text: &Arc<String>,
+    lines: &Arc<Vec<Range<usize>>>,
+    file_path: &Path,
+    theme: Option<&SyntectTheme>,
+) -> Option<HighlightWorker> {
+    let theme = theme?;
+    let syntax = file_path.extension().and_then(OsStr::to_str).map_or_else(
+        || SYNTAX_SET.find_syntax_by_path(file_path.to_str().unwrap_or_default()),
+        |ext| SYNTAX_SET.find_syntax_by_extension(ext),
+    );
+    let syntax = syntax.unwrap_or_else(|| SYNTAX_SET.find_syntax_plain_text());
+    let (requests, receiver) = channel();
+    let blocks = BlockCache::default();
+    let version = Arc::new(AtomicUsize::new(0));
+    let job = HighlightJob {
+        text: text.clone(),
+        lines: lines.clone(),
+        blocks: blocks.clone(),
+        version: version.clone(),
+        current: 0,
+    };
+    let theme = theme.clone();
+    std::thread::spawn(move || job.run(syntax, &theme, receiver));
+    Some(HighlightWorker {
+        requests,
+        blocks,
+        version,
+    })
+}
 
-                syntax_lines.push(SyntaxLine {
-                    items: iter
-                        .map(|(style, _, range)| (style, number, range))
-                        .collect(),
-                });
-            }
-        }
+impl SyntaxText {
+    /// `theme` 为 None 时不做语法高亮, 整行使用默认样式
+    pub fn new(
+        text: String,
+        file_path: &Path,
+        theme: Option<&SyntectTheme>,
+    ) -> anyhow::Result<Self> {
+        scope_time!("syntax_text_index");
+
+        let text = Arc::new(text);
+        let lines = Arc::new(line_ranges(&text));
+        let worker = highlight_worker(&text, &lines, file_path, theme);
 
         Ok(Self {
             text,
-            lines: syntax_lines,
+            lines,
             path: file_path.into(),
+            worker,
         })
     }
 
+    /// 换一个语法高亮主题, 内容不变, 只重启后台高亮线程
+    pub fn set_theme(&mut self, theme: Option<&SyntectTheme>) {
+        self.worker = highlight_worker(&self.text, &self.lines, &self.path, theme);
+    }
+
+    /// 读取文件并把 tab 换成空格
+    pub fn open(path: &Path, theme: Option<&SyntectTheme>) -> anyhow::Result<Self> {
+        let content = tabs_to_spaces(file_content(path)?);
+        Self::new(content, path, theme)
+    }
+
     ///
     pub fn path(&self) -> &Path {
         &self.path
     }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn line(&self, number: usize) -> Option<&str> {
+        self.lines.get(number).map(|range| &self.text[range.clone()])
+    }
+
+    /// 保留前 keep 行, 后面换成 text. 后台线程继续使用 keep 之前的高亮结果和解析状态
+    pub fn replace_lines(&mut self, keep: usize, text: &str) {
+        replace_lines(&mut self.text, &mut self.lines, keep, text);
+        let Some(worker) = &self.worker else {
+            return;
+        };
+        // 在锁里更新版本, 后台线程不会再保存按旧内容高亮的块
+        let Ok(mut blocks) = worker.blocks.lock() else {
+            return;
+        };
+        let version = worker.version.fetch_add(1, Ordering::SeqCst) + 1;
+        blocks.retain(|block, _| *block < keep / HIGHLIGHT_BLOCK);
+        drop(blocks);
+        let _ = worker.requests.send(HighlightRequest::Replace {
+            keep,
+            text: text.to_string(),
+            version,
+        });
+    }
+
+    /// 已经高亮完的块
+    fn block(&self, block: usize) -> Option<Arc<Vec<LineStyle>>> {
+        let worker = self.worker.as_ref()?;
+        worker.blocks.lock().ok()?.get(&block).cloned()
+    }
+
+    /// 一行的样式, 还没高亮完时整行使用默认样式
+    pub fn styles(&self, number: usize) -> LineStyle {
+        self.block(number / HIGHLIGHT_BLOCK)
+            .and_then(|block| block.get(number % HIGHLIGHT_BLOCK).cloned())
+            .unwrap_or_else(|| {
+                let len = self.line(number).map_or(0, str::len);
+                vec![(ratatui::style::Style::default(), 0..len)]
+            })
+    }
+
+    /// 可见区域有没高亮的块时通知后台线程
+    pub fn request(&self, lines: Range<usize>) {
+        let Some(worker) = &self.worker else {
+            return;
+        };
+        let first = lines.start / HIGHLIGHT_BLOCK;
+        let last = lines.end.saturating_sub(1) / HIGHLIGHT_BLOCK;
+        let missing = worker
+            .blocks
+            .lock()
+            .is_ok_and(|blocks| (first..=last).any(|block| !blocks.contains_key(&block)));
+        if missing {
+            let _ = worker.requests.send(HighlightRequest::Viewport(lines));
+        }
+    }
 }
 
-impl<'a> From<&'a SyntaxText> for ratatui::text::Text<'a> {
-    fn from(v: &'a SyntaxText) -> Self {
-        let mut result_lines: Vec<Line> = Vec::with_capacity(v.lines.len());
+/// 和 str::lines 一样的切分, 去掉行尾的 \n 和 \r\n
+fn line_ranges(text: &str) -> Vec<Range<usize>> {
+    let mut lines = Vec::new();
+    let mut start = 0;
+    for (end, _) in text.match_indices('\n') {
+        let line_end = if text[..end].ends_with('\r') { end - 1 } else { end };
+        lines.push(start..line_end.max(start));
+        start = end + 1;
+    }
+    if start < text.len() {
+        lines.push(start..text.len());
+    }
+    lines
+}
+
+/// 保留 text 的前 keep 行, 后面换成 new, 同时更新行索引
+fn replace_lines(text: &mut Arc<String>, lines: &mut Arc<Vec<Range<usize>>>, keep: usize, new: &str) {
+    let text = Arc::make_mut(text);
+    let lines = Arc::make_mut(lines);
+    let end = lines.get(keep).map_or(text.len(), |range| range.start);
+    text.truncate(end);
+    lines.truncate(keep);
+    if keep > 0 && !text.ends_with('\n') {
+        text.push('\n');
+    }
+    let start = text.len();
+    text.push_str(new);
+    lines.extend(line_ranges(new).into_iter().map(|range| range.start + start..range.end + start));
+}
 
-        for (syntax_line, line_content) in v.lines.iter().zip(v.text.lines()) {
-            let mut line_span: Line = Vec::with_capacity(syntax_line.items.len()).into();
+/// 后台高亮线程的数据
+struct HighlightJob {
+    text: Arc<String>,
+    lines: Arc<Vec<Range<usize>>>,
+    blocks: BlockCache,
+    version: Arc<AtomicUsize>,
+    /// 已经处理到的文本版本
+    current: usize,
+}
 
-            for (style, _, range) in &syntax_line.items {
-                let item_content = &line_content[range.clone()];
-                let item_style = syntact_style_to_tui(style);
+impl HighlightJob {
+    /// 语法解析只能从文件开头顺序进行, 所以沿途在每块开头保存解析状态,
+    /// 之后跳回前面的位置时从最近的检查点开始. 只保留可见区域附近的高亮结果
+    fn run(
+        mut self,
+        syntax: &SyntaxReference,
+        theme: &SyntectTheme,
+        requests: Receiver<HighlightRequest>,
+    ) -> Option<()> {
+        let highlighter = Highlighter::new(theme);
+        let mut checkpoints = vec![(
+            ParseState::new(syntax),
+            HighlightState::new(&highlighter, ScopeStack::new()),
+        )];
 
-                line_span.spans.push(Span::styled(item_content, item_style));
+        while let Ok(request) = requests.recv() {
+            let mut wanted = None;
+            for request in std::iter::once(request).chain(requests.try_iter()) {
+                match request {
+                    HighlightRequest::Viewport(lines) => wanted = Some(lines),
+                    // 修改位置所在块开头之前的检查点仍然有效
+                    HighlightRequest::Replace { keep, text, version } => {
+                        replace_lines(&mut self.text, &mut self.lines, keep, &text);
+                        checkpoints.truncate(keep / HIGHLIGHT_BLOCK + 1);
+                        self.current = version;
+                    }
+                }
+            }
+            let Some(wanted) = wanted else {
+                continue;
+            };
+            let block_count = self.lines.len().div_ceil(HIGHLIGHT_BLOCK);
+            // 多高亮前后各一块, 翻页时不会先看到纯文本
+            let first = (wanted.start / HIGHLIGHT_BLOCK).saturating_sub(1);
+            let last = (wanted.end / HIGHLIGHT_BLOCK + 1).min(block_count.saturating_sub(1));
+            for block in first..=last {
+                if self.blocks.lock().ok()?.contains_key(&block) {
+                    continue;
+                }
+                // 先补齐到这一块的检查点, 中间块的结果不保存
+                while checkpoints.len() <= block {
+                    let previous = checkpoints.len() - 1;
+                    let (_, next) =
+                        self.highlight_block(previous, checkpoints[previous].clone(), &highlighter)?;
+                    checkpoints.push(next);
+                }
+                let (styles, next) =
+                    self.highlight_block(block, checkpoints[block].clone(), &highlighter)?;
+                if checkpoints.len() == block + 1 {
+                    checkpoints.push(next);
+                }
+                let mut blocks = self.blocks.lock().ok()?;
+                if self.version.load(Ordering::SeqCst) != self.current {
+                    break;
+                }
+                blocks.insert(block, Arc::new(styles));
+                blocks.retain(|b, _| *b + CACHED_BLOCKS >= first && *b <= last + CACHED_BLOCKS);
             }
-
-            result_lines.push(line_span);
         }
+        Some(())
+    }
 
-        result_lines.into()
+    /// 解析出错时返回 None, 文件保持纯文本显示
+    fn highlight_block(
+        &self,
+        block: usize,
+        (mut parse, mut highlight): Checkpoint,
+        highlighter: &Highlighter,
+    ) -> Option<(Vec<LineStyle>, Checkpoint)> {
+        let start = block * HIGHLIGHT_BLOCK;
+        let end = (start + HIGHLIGHT_BLOCK).min(self.lines.len());
+        let mut styles = Vec::with_capacity(end - start);
+        for range in &self.lines[start..end] {
+            let line = &self.text[range.clone()];
+            let ops = parse.parse_line(line, &SYNTAX_SET).ok()?;
+            let iter = RangedHighlightIterator::new(&mut highlight, &ops[..], line, highlighter);
+            styles.push(
+                iter.map(|(style, _, range)| (syntact_style_to_tui(&style), range))
+                    .collect(),
+            );
+        }
+        Some((styles, (parse, highlight)))
     }
 }
 
@@ -333,12 +1120,93 @@ fn syntact_style_to_tui(style: &Style) -> ratatui::style::Style {
     res
 }
 
-fn return_title_theme(focused: bool) -> ratatui::style::Style {
+fn return_title_theme(focused: bool, theme: &Theme) -> ratatui::style::Style {
     if focused {
         ratatui::style::Style::default()
-            .fg(Color::Green)
+            .fg(theme.accent(Color::Green))
             .add_modifier(Modifier::BOLD)
     } else {
         ratatui::style::Style::default().fg(Color::DarkGray)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+    use syntect::highlighting::ThemeSet;
+
+    #[test]
+    fn test_line_ranges() {
+        for text in ["", "a", "a\n", "a\r\nbb\n\nc", "\n\n", "x\r\n"] {
+            let ranges = line_ranges(text);
+            let lines: Vec<&str> = ranges.into_iter().map(|r| &text[r]).collect();
+            assert_eq!(lines, text.lines().collect::<Vec<_>>(), "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn test_replace_lines() {
+        let mut text = Arc::new("a\nbb\npartial".to_string());
+        let mut lines = Arc::new(line_ranges(&text));
+        replace_lines(&mut text, &mut lines, 2, "partial line\nc");
+        assert_eq!(text.as_str(), "a\nbb\npartial line\nc");
+        assert_eq!(*lines, line_ranges(&text));
+        replace_lines(&mut text, &mut lines, 4, "d");
+        assert_eq!(text.as_str(), "a\nbb\npartial line\nc\nd");
+        assert_eq!(*lines, line_ranges(&text));
+        replace_lines(&mut text, &mut lines, 0, "x");
+        assert_eq!(text.as_str(), "x");
+        assert_eq!(*lines, line_ranges(&text));
+    }
+
+    #[test]
+    fn test_highlight_worker() {
+        let text = (0..HIGHLIGHT_BLOCK * 3)
+            .map(|i| format!("key{} = \"{}\"", i, i))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let theme = &ThemeSet::load_defaults().themes["base16-ocean.dark"];
+        let sy = SyntaxText::new(text, Path::new("svr.toml"), Some(theme)).unwrap();
+        assert_eq!(sy.line_count(), HIGHLIGHT_BLOCK * 3);
+        assert!(sy.block(2).is_none());
+
+        // 跳到文件末尾时也要从头解析, 但只保存可见区域附近的块
+        let start = Instant::now();
+        sy.request(HIGHLIGHT_BLOCK * 2 + 10..HIGHLIGHT_BLOCK * 2 + 20);
+        let block = loop {
+            if let Some(block) = sy.block(2) {
+                break block;
+            }
+            assert!(start.elapsed() < Duration::from_secs(10));
+            std::thread::sleep(Duration::from_millis(10));
+        };
+        assert_eq!(block.len(), HIGHLIGHT_BLOCK);
+        for (i, styles) in block.iter().enumerate() {
+            let line = sy.line(HIGHLIGHT_BLOCK * 2 + i).unwrap();
+            assert_eq!(styles.last().map(|(_, r)| r.end), Some(line.len()));
+        }
+
+        // 追加内容后保留前面的块, 新的行按追加后的内容高亮
+        let mut sy = sy;
+        let keep = HIGHLIGHT_BLOCK * 3 - 1;
+        sy.replace_lines(keep, "last = 1\nappended = \"x\"");
+        assert_eq!(sy.line_count(), HIGHLIGHT_BLOCK * 3 + 1);
+        assert!(sy.block(2).is_none());
+        sy.request(HIGHLIGHT_BLOCK * 3 - 5..HIGHLIGHT_BLOCK * 3 + 1);
+        let block = loop {
+            if let Some(block) = sy.block(3) {
+                break block;
+            }
+            assert!(start.elapsed() < Duration::from_secs(10));
+            std::thread::sleep(Duration::from_millis(10));
+        };
+        let line = sy.line(HIGHLIGHT_BLOCK * 3).unwrap();
+        assert_eq!(line, "appended = \"x\"");
+        assert_eq!(block[0].last().map(|(_, r)| r.end), Some(line.len()));
+
+        let plain = SyntaxText::new("a\nb".to_string(), Path::new("a.toml"), None).unwrap();
+        plain.request(0..2);
+        assert!(plain.block(0).is_none());
+    }
+}
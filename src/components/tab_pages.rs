@@ -0,0 +1,610 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::Color,
+    Frame,
+};
+use sqlx::SqlitePool;
+
+use crate::clipboard::copy_string;
+use crate::config::KeyConfig;
+use crate::database::{query_hosts_sql, select_all_host};
+use crate::event::Key;
+use crate::theme::Theme;
+use crate::tools::host::HostInfo;
+use crate::tools::search::search_vec;
+
+use super::{
+    columns::ColumnLayouts,
+    command::{self, Action, CommandInfo},
+    data_table::{DataTable, Density, TableRow, YankFormat},
+    host::HostInfoComponent,
+    proc::ProcInfoComponent,
+    query_input::{QueryInputComponents, QueryType},
+    stats::StatsComponent,
+    tabs::{TabContent, TabInfo, TabRequest},
+    tcm_command::CommandTarget,
+    topology::{TopologyComponent, TopologyTarget},
+    total_proc::{TotalProc, TotalProcInfoComponent},
+    Component, DrawableComponent, EventState,
+};
+
+/// 带查询框的标签页中的焦点, toggle_focus 在两者之间切换
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum QueryFocus {
+    Table,
+    Filter,
+}
+
+/// 表格没有处理的 toggle_focus 和过滤键切换焦点, 查询框有焦点时标红
+fn move_query_focus(
+    focus: &mut QueryFocus,
+    query_input: &mut QueryInputComponents,
+    key: Key,
+    config: &KeyConfig,
+) -> EventState {
+    let next = if key == config.toggle_focus {
+        match focus {
+            QueryFocus::Table => QueryFocus::Filter,
+            QueryFocus::Filter => QueryFocus::Table,
+        }
+    } else if key == config.filter && *focus == QueryFocus::Table {
+        QueryFocus::Filter
+    } else {
+        return EventState::NotConsumed;
+    };
+    *focus = next;
+    query_input.color = if next == QueryFocus::Filter { Color::Red } else { Color::Reset };
+    EventState::Consumed
+}
+
+/// 查询框在上, 表格在下
+fn query_layout(area: Rect) -> [Rect; 2] {
+    Layout::vertical([Constraint::Length(5), Constraint::Min(5)]).areas(area)
+}
+
+fn is_left_click(mouse: MouseEvent) -> bool {
+    mouse.kind == MouseEventKind::Down(MouseButton::Left)
+}
+
+/// 主机搜索: 查询框和主机表格
+pub struct HostTab {
+    info: TabInfo,
+    pool: SqlitePool,
+    host: HostInfoComponent,
+    query_input: QueryInputComponents,
+    focus: QueryFocus,
+    /// 按下 copy 后等待选择复制格式
+    pending_yank: bool,
+    request: Option<TabRequest>,
+    key_config: KeyConfig,
+}
+
+impl HostTab {
+    pub async fn new(
+        info: TabInfo,
+        pool: &SqlitePool,
+        layouts: &ColumnLayouts,
+        key_config: KeyConfig,
+        theme: Theme,
+        query_type: QueryType,
+    ) -> Result<Self> {
+        Ok(Self {
+            info,
+            pool: pool.clone(),
+            host: HostInfoComponent::load(pool, layouts, key_config.clone(), theme).await?,
+            query_input: QueryInputComponents::new(key_config.clone(), query_type),
+            focus: QueryFocus::Table,
+            pending_yank: false,
+            request: None,
+            key_config,
+        })
+    }
+
+    async fn query(&mut self, query: &str) -> Result<()> {
+        let hosts = match self.query_input.query_type {
+            QueryType::Tcm => query_hosts_sql(query, &self.pool).await?.iter().map(HostInfo::from).collect(),
+            QueryType::Text => search_vec(&select_all_host(&self.pool).await?, query),
+        };
+        self.host.set_items(hosts);
+        Ok(())
+    }
+
+    async fn reset_query(&mut self) -> Result<()> {
+        self.host.set_items(select_all_host(&self.pool).await?);
+        self.query_input.history.clear();
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl TabContent for HostTab {
+    fn info(&self) -> &TabInfo {
+        &self.info
+    }
+
+    fn draw(&self, f: &mut Frame, area: Rect) -> Result<()> {
+        let [query_area, table_area] = query_layout(area);
+        self.query_input.draw(f, query_area, false)?;
+        self.host.draw(f, table_area, false)
+    }
+
+    fn commands(&self, out: &mut Vec<CommandInfo>) {
+        match self.focus {
+            QueryFocus::Table => {
+                self.host.commands(out);
+                out.push(CommandInfo::new(command::host_detail(&self.key_config)));
+            }
+            QueryFocus::Filter => self.query_input.commands(out),
+        }
+    }
+
+    async fn event(&mut self, key: Key) -> Result<EventState> {
+        match self.focus {
+            QueryFocus::Table => {
+                // 先处理复制, 等待选择复制格式时的按键不能被其他操作拿走
+                if let Some(request) = batch_action(&self.host, key, &self.key_config, &mut self.pending_yank)? {
+                    self.request = Some(request);
+                    return Ok(EventState::Consumed);
+                }
+                if key == self.key_config.enter {
+                    if let Some(host) = self.host.selected_item().cloned() {
+                        self.request = Some(TabRequest::HostDetail(host));
+                        return Ok(EventState::Consumed);
+                    }
+                }
+                if self.host.event(key)?.is_consumed() {
+                    return Ok(EventState::Consumed);
+                }
+            }
+            QueryFocus::Filter => {
+                let state = self.query_input.event(key)?;
+                if key == self.key_config.enter {
+                    if let Some(query) = self.query_input.history.first().cloned() {
+                        self.query(&query).await?;
+                    }
+                    return Ok(EventState::Consumed);
+                }
+                if key == self.key_config.move_up || key == self.key_config.move_down {
+                    self.reset_query().await?;
+                }
+                if state.is_consumed() {
+                    return Ok(state);
+                }
+            }
+        }
+        Ok(move_query_focus(&mut self.focus, &mut self.query_input, key, &self.key_config))
+    }
+
+    fn mouse_event(&mut self, mouse: MouseEvent) -> Result<EventState> {
+        let state = self.host.mouse_event(mouse)?;
+        if state.is_consumed() && is_left_click(mouse) {
+            self.focus = QueryFocus::Table;
+            self.query_input.color = Color::Reset;
+        }
+        Ok(state)
+    }
+
+    fn take_request(&mut self) -> Option<TabRequest> {
+        self.request.take()
+    }
+
+    fn reset(&mut self) {
+        self.query_input.history.clear();
+        self.query_input.color = Color::Reset;
+        self.focus = QueryFocus::Table;
+    }
+
+    /// 显示全部主机并选中 target 的主机
+    async fn reveal(&mut self, target: &TopologyTarget) -> Result<bool> {
+        let TopologyTarget::Host(host) = target else {
+            return Ok(false);
+        };
+        self.reset();
+        self.reset_query().await?;
+        self.host.select(host);
+        Ok(true)
+    }
+
+    fn marked_count(&self) -> usize {
+        self.host.marked_count()
+    }
+
+    fn set_theme(&mut self, theme: Theme) {
+        self.host.set_theme(theme);
+    }
+
+    fn set_density(&mut self, density: Density) {
+        self.host.set_density(density);
+    }
+
+    fn set_query_type(&mut self, query_type: QueryType) {
+        self.query_input.query_type = query_type;
+    }
+}
+
+/// 进程搜索: 没有查询时显示全部进程, 有查询时显示查询到的进程实例
+pub struct ProcTab {
+    info: TabInfo,
+    pool: SqlitePool,
+    proc: ProcInfoComponent,
+    total_proc: TotalProcInfoComponent,
+    query_input: QueryInputComponents,
+    focus: QueryFocus,
+    /// 按下 copy 后等待选择复制格式
+    pending_yank: bool,
+    request: Option<TabRequest>,
+    key_config: KeyConfig,
+}
+
+impl ProcTab {
+    pub async fn new(
+        info: TabInfo,
+        pool: &SqlitePool,
+        layouts: &ColumnLayouts,
+        key_config: KeyConfig,
+        theme: Theme,
+        query_type: QueryType,
+    ) -> Result<Self> {
+        Ok(Self {
+            info,
+            pool: pool.clone(),
+            proc: ProcInfoComponent::load(pool, layouts, key_config.clone(), theme.clone()).await?,
+            total_proc: TotalProcInfoComponent::load(pool, layouts, key_config.clone(), theme).await?,
+            query_input: QueryInputComponents::new(key_config.clone(), query_type),
+            focus: QueryFocus::Table,
+            pending_yank: false,
+            request: None,
+            key_config,
+        })
+    }
+
+    /// 有查询时显示查询结果
+    fn searching(&self) -> bool {
+        !self.query_input.history.is_empty()
+    }
+
+    fn selected_func_id(&self) -> Result<Option<i32>> {
+        if self.searching() {
+            Ok(self.total_proc.selected_item().map(|item| item.func_id.parse()).transpose()?)
+        } else {
+            Ok(self.proc.selected_item().map(|item| item.func_id))
+        }
+    }
+
+    async fn query(&mut self, query: &str) -> Result<()> {
+        let procs = match self.query_input.query_type {
+            QueryType::Tcm => select_procs(&self.pool, query).await?,
+            QueryType::Text => search_vec(&select_procs(&self.pool, "*.*.*.*").await?, query),
+        };
+        self.total_proc.set_items(procs);
+        Ok(())
+    }
+
+    fn tcm_command_request(&self) -> TabRequest {
+        let (selected, result) = if self.searching() {
+            (
+                Some(self.total_proc.targets())
+                    .filter(|items| !items.is_empty())
+                    .map(CommandTarget::Procs),
+                CommandTarget::Procs(self.total_proc.items().to_vec()),
+            )
+        } else {
+            (
+                Some(self.proc.targets())
+                    .filter(|items| !items.is_empty())
+                    .map(CommandTarget::Funcs),
+                CommandTarget::Funcs(self.proc.items().to_vec()),
+            )
+        };
+        // TCM 查询直接复用查询语句, 关键词搜索则使用搜索结果
+        let query = match (self.query_input.history.first(), self.query_input.query_type) {
+            (Some(query), QueryType::Tcm) => CommandTarget::Query(query.clone()),
+            _ => result,
+        };
+        TabRequest::TcmCommand(selected, query)
+    }
+
+    async fn table_event(&mut self, key: Key) -> Result<EventState> {
+        // 先处理复制, 等待选择复制格式时的按键不能被其他操作拿走
+        let request = if self.searching() {
+            batch_action(&self.total_proc, key, &self.key_config, &mut self.pending_yank)?
+        } else {
+            batch_action(&self.proc, key, &self.key_config, &mut self.pending_yank)?
+        };
+        if request.is_some() {
+            self.request = request;
+            return Ok(EventState::Consumed);
+        }
+        if key == self.key_config.tcm_command {
+            self.request = Some(self.tcm_command_request());
+            return Ok(EventState::Consumed);
+        }
+        if key == self.key_config.open_editor || key == self.key_config.browse_files || key == self.key_config.enter {
+            if let Some(func_id) = self.selected_func_id()? {
+                self.request = Some(if key == self.key_config.open_editor {
+                    TabRequest::EditProcXml(func_id)
+                } else if key == self.key_config.browse_files {
+                    TabRequest::FileTree(func_id)
+                } else {
+                    let item = self.total_proc.selected_item().filter(|_| self.searching()).cloned();
+                    TabRequest::ProcDetail(func_id, item)
+                });
+                return Ok(EventState::Consumed);
+            }
+        }
+        if self.searching() {
+            self.total_proc.event(key)
+        } else {
+            self.proc.event(key)
+        }
+    }
+}
+
+#[async_trait]
+impl TabContent for ProcTab {
+    fn info(&self) -> &TabInfo {
+        &self.info
+    }
+
+    fn draw(&self, f: &mut Frame, area: Rect) -> Result<()> {
+        let [query_area, table_area] = query_layout(area);
+        self.query_input.draw(f, query_area, false)?;
+        if self.searching() {
+            self.total_proc.draw(f, table_area, false)
+        } else {
+            self.proc.draw(f, table_area, false)
+        }
+    }
+
+    fn commands(&self, out: &mut Vec<CommandInfo>) {
+        if self.focus == QueryFocus::Filter {
+            self.query_input.commands(out);
+            return;
+        }
+        if self.searching() {
+            self.total_proc.commands(out);
+        } else {
+            self.proc.commands(out);
+        }
+        out.push(
+            CommandInfo::new(command::tcm_command(&self.key_config))
+                .action("Generate tcm command", Action::Keys(vec![self.key_config.tcm_command])),
+        );
+        // 从表格直接打开选中进程的文件: 先打开详情再按对应的键
+        out.push(
+            CommandInfo::new(command::open_proc_file(&self.key_config))
+                .action(
+                    "Open config file",
+                    Action::Keys(vec![self.key_config.enter, self.key_config.open_config]),
+                )
+                .action(
+                    "Open binary file",
+                    Action::Keys(vec![self.key_config.enter, self.key_config.open_binary]),
+                ),
+        );
+        out.push(
+            CommandInfo::new(command::browse_files(&self.key_config))
+                .action("Browse proc files", Action::Keys(vec![self.key_config.browse_files])),
+        );
+    }
+
+    async fn event(&mut self, key: Key) -> Result<EventState> {
+        match self.focus {
+            QueryFocus::Table => {
+                if self.table_event(key).await?.is_consumed() {
+                    return Ok(EventState::Consumed);
+                }
+            }
+            QueryFocus::Filter => {
+                let state = self.query_input.event(key)?;
+                if key == self.key_config.enter {
+                    if let Some(query) = self.query_input.history.first().cloned() {
+                        self.query(&query).await?;
+                    }
+                    return Ok(EventState::Consumed);
+                }
+                if key == self.key_config.move_up || key == self.key_config.move_down {
+                    self.query_input.history.clear();
+                }
+                if state.is_consumed() {
+                    return Ok(state);
+                }
+            }
+        }
+        Ok(move_query_focus(&mut self.focus, &mut self.query_input, key, &self.key_config))
+    }
+
+    fn mouse_event(&mut self, mouse: MouseEvent) -> Result<EventState> {
+        let state = if self.searching() {
+            self.total_proc.mouse_event(mouse)?
+        } else {
+            self.proc.mouse_event(mouse)?
+        };
+        if state.is_consumed() && is_left_click(mouse) {
+            self.focus = QueryFocus::Table;
+            self.query_input.color = Color::Reset;
+        }
+        Ok(state)
+    }
+
+    fn take_request(&mut self) -> Option<TabRequest> {
+        self.request.take()
+    }
+
+    fn reset(&mut self) {
+        self.query_input.history.clear();
+        self.query_input.color = Color::Reset;
+        self.focus = QueryFocus::Table;
+    }
+
+    /// 进程详情由 App 打开, 这里只清除查询
+    async fn reveal(&mut self, target: &TopologyTarget) -> Result<bool> {
+        if !matches!(target, TopologyTarget::Proc(_)) {
+            return Ok(false);
+        }
+        self.reset();
+        Ok(true)
+    }
+
+    fn marked_count(&self) -> usize {
+        if self.searching() {
+            self.total_proc.marked_count()
+        } else {
+            self.proc.marked_count()
+        }
+    }
+
+    fn set_theme(&mut self, theme: Theme) {
+        self.proc.set_theme(theme.clone());
+        self.total_proc.set_theme(theme);
+    }
+
+    fn set_density(&mut self, density: Density) {
+        self.proc.set_density(density);
+        self.total_proc.set_density(density);
+    }
+
+    fn set_query_type(&mut self, query_type: QueryType) {
+        self.query_input.query_type = query_type;
+    }
+}
+
+async fn select_procs(pool: &SqlitePool, tcm_sql: &str) -> Result<Vec<TotalProc>> {
+    Ok(query_hosts_sql(tcm_sql, pool).await?.iter().map(TotalProc::from).collect())
+}
+
+/// 部署拓扑: 过滤由 TopologyComponent 自己处理
+pub struct TopologyTab {
+    info: TabInfo,
+    topology: TopologyComponent,
+    request: Option<TabRequest>,
+    key_config: KeyConfig,
+}
+
+impl TopologyTab {
+    pub async fn new(info: TabInfo, pool: &SqlitePool, key_config: KeyConfig, theme: Theme) -> Result<Self> {
+        Ok(Self {
+            info,
+            topology: TopologyComponent::new(pool, key_config.clone(), theme).await?,
+            request: None,
+            key_config,
+        })
+    }
+}
+
+#[async_trait]
+impl TabContent for TopologyTab {
+    fn info(&self) -> &TabInfo {
+        &self.info
+    }
+
+    fn draw(&self, f: &mut Frame, area: Rect) -> Result<()> {
+        self.topology.draw(f, area, false)
+    }
+
+    fn commands(&self, out: &mut Vec<CommandInfo>) {
+        self.topology.commands(out);
+        out.push(CommandInfo::new(command::host_detail(&self.key_config)));
+        out.push(CommandInfo::new(command::proc_detail(&self.key_config)));
+    }
+
+    async fn event(&mut self, key: Key) -> Result<EventState> {
+        if key == self.key_config.enter && !self.topology.is_filtering() {
+            match self.topology.target() {
+                Some(TopologyTarget::Host(host)) => self.request = Some(TabRequest::HostDetail(host)),
+                Some(TopologyTarget::Proc(func_id)) => self.request = Some(TabRequest::ShowProc(func_id)),
+                None => self.topology.toggle(),
+            }
+            return Ok(EventState::Consumed);
+        }
+        self.topology.event(key)
+    }
+
+    fn take_request(&mut self) -> Option<TabRequest> {
+        self.request.take()
+    }
+
+    fn set_theme(&mut self, theme: Theme) {
+        self.topology.set_theme(theme);
+    }
+}
+
+/// 配置统计
+pub struct StatsTab {
+    info: TabInfo,
+    stats: StatsComponent,
+}
+
+impl StatsTab {
+    pub async fn new(info: TabInfo, pool: &SqlitePool, key_config: KeyConfig, theme: Theme) -> Result<Self> {
+        Ok(Self {
+            info,
+            stats: StatsComponent::new(pool, key_config, theme).await?,
+        })
+    }
+}
+
+#[async_trait]
+impl TabContent for StatsTab {
+    fn info(&self) -> &TabInfo {
+        &self.info
+    }
+
+    fn draw(&self, f: &mut Frame, area: Rect) -> Result<()> {
+        self.stats.draw(f, area, false)
+    }
+
+    fn commands(&self, out: &mut Vec<CommandInfo>) {
+        self.stats.commands(out);
+    }
+
+    async fn event(&mut self, key: Key) -> Result<EventState> {
+        self.stats.event(key)
+    }
+
+    fn set_theme(&mut self, theme: Theme) {
+        self.stats.set_theme(theme);
+    }
+}
+
+/// 表格上对标记行 (没有标记时是选中行) 的批量操作, 返回给底栏的提示,
+/// 复制和导出完成时返回 Notice. 按下 copy 后等待下一个按键选择复制格式
+fn batch_action<T: TableRow>(
+    table: &DataTable<T>,
+    key: Key,
+    config: &KeyConfig,
+    pending_yank: &mut bool,
+) -> Result<Option<TabRequest>> {
+    let format = if std::mem::take(pending_yank) {
+        match YankFormat::from_key(key, config) {
+            Some(format) => format,
+            None => return Ok(Some(TabRequest::Message("已取消复制".to_string()))),
+        }
+    } else if key == config.copy {
+        *pending_yank = true;
+        return Ok(Some(TabRequest::Message(YankFormat::hint(config))));
+    } else if key == config.copy_ips {
+        YankFormat::Ips
+    } else if key == config.export_rows {
+        let count = table.targets().len();
+        let path = table.export()?;
+        return Ok(Some(TabRequest::Notice(format!("已导出 {} 行到 {}", count, path.display()))));
+    } else {
+        return Ok(None);
+    };
+
+    let lines = table.yank(format);
+    if lines.is_empty() {
+        return Ok(Some(TabRequest::Message(format!("没有可复制的{}", format.name()))));
+    }
+    let copied = copy_string(&lines.join("\n"))?;
+    Ok(Some(TabRequest::Notice(format!(
+        "已复制 {} ({} 行) 到{}",
+        format.name(),
+        lines.len(),
+        copied
+    ))))
+}
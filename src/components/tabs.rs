@@ -1,55 +1,154 @@
 #![allow(clippy::wildcard_imports, clippy::enum_glob_use)]
 
+use anyhow::Result;
+use async_trait::async_trait;
+use crossterm::event::MouseEvent;
 use ratatui::{prelude::*, style::palette::tailwind, widgets::*};
-use strum::{Display, EnumIter, FromRepr, IntoEnumIterator};
+use ratatui::layout::Position;
+use std::cell::Cell;
+use unicode_width::UnicodeWidthStr;
 
-use crate::config::KeyConfig;
+use crate::event::Key;
+use crate::theme::Theme;
+use crate::tools::host::HostInfo;
 
-use super::{command::{self, CommandInfo}, Component, DrawableComponent, EventState};
+use super::{
+    command::CommandInfo,
+    data_table::Density,
+    query_input::QueryType,
+    tcm_command::CommandTarget,
+    topology::TopologyTarget,
+    total_proc::TotalProc,
+    DrawableComponent, EventState,
+};
 
-#[derive(Default)]
 pub struct TabComponent {
-    state: TabState,
-    pub selected_tab: SelectedTab,
-    key_config: KeyConfig,
+    /// 注册的标签页, 按显示顺序, 至少有一个
+    pages: Vec<Box<dyn TabContent>>,
+    selected: usize,
+    theme: Theme,
+    /// 上次绘制标签栏的区域, 鼠标点击时使用
+    tabs_area: Cell<Rect>,
 }
 
-#[derive(Default, Clone, Copy, PartialEq, Eq)]
-enum TabState {
-    #[default]
-    Running,
-    Quitting,
+/// 标签页的内容. 标签页持有自己的组件, 绘制标签栏和底栏之间的区域, 自己在组件之间切换焦点
+#[async_trait]
+pub trait TabContent: Send {
+    fn info(&self) -> &TabInfo;
+
+    fn draw(&self, f: &mut Frame, area: Rect) -> Result<()>;
+
+    fn commands(&self, out: &mut Vec<CommandInfo>);
+
+    async fn event(&mut self, key: Key) -> Result<EventState>;
+
+    fn mouse_event(&mut self, _mouse: MouseEvent) -> Result<EventState> {
+        Ok(EventState::NotConsumed)
+    }
+
+    /// 事件中需要 App 处理的请求, App 在每次事件后取出
+    fn take_request(&mut self) -> Option<TabRequest> {
+        None
+    }
+
+    /// 切换到这个标签页时调用, 清除查询, 焦点回到第一个组件
+    fn reset(&mut self) {}
+
+    /// 重置后显示 target, 不能显示时返回 false
+    async fn reveal(&mut self, _target: &TopologyTarget) -> Result<bool> {
+        Ok(false)
+    }
+
+    fn marked_count(&self) -> usize {
+        0
+    }
+
+    fn set_theme(&mut self, _theme: Theme) {}
+
+    fn set_density(&mut self, _density: Density) {}
+
+    fn set_query_type(&mut self, _query_type: QueryType) {}
 }
 
-#[derive(Default, Clone, Copy, Display, FromRepr, EnumIter)]
-pub enum SelectedTab {
-    #[default]
-    #[strum(to_string = "主机搜索 [1]")]
-    Tab1,
-    #[strum(to_string = "进程搜索 [2]")]
-    Tab2,
+/// 标签页请求 App 打开的弹窗和界面
+pub enum TabRequest {
+    HostDetail(HostInfo),
+    /// 在当前标签页打开进程详情, 查询结果中的进程带上实例
+    ProcDetail(i32, Option<TotalProc>),
+    /// 切到进程标签页打开进程详情
+    ShowProc(i32),
+    FileTree(i32),
+    EditProcXml(i32),
+    /// 选中的目标和查询条件
+    TcmCommand(Option<CommandTarget>, CommandTarget),
+    /// 显示在底栏的提示
+    Message(String),
+    /// 复制/导出完成, 显示在底栏并记到消息日志
+    Notice(String),
 }
 
-impl DrawableComponent for TabComponent {
-    fn draw(&self, f: &mut Frame, _area: Rect, _focused: bool) -> anyhow::Result<()> {
-        f.render_widget(self, _area);
-        Ok(())
-    }
+/// 标签页的标识和标签栏上的标题, 切换按键, 颜色.
+/// id 用在配置文件的 default_tab 中
+pub struct TabInfo {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub key: Key,
+    description: &'static str,
+    palette: &'static tailwind::Palette,
 }
 
-impl SelectedTab {
-    /// Get the previous tab, if there is no previous tab return the current tab.
-    fn previous(self) -> Self {
-        let current_index: usize = self as usize;
-        let previous_index = current_index.saturating_sub(1);
-        Self::from_repr(previous_index).unwrap_or(self)
+impl TabInfo {
+    pub fn new(id: &'static str, name: &'static str, key: Key, palette: &'static tailwind::Palette) -> Self {
+        Self {
+            id,
+            name,
+            key,
+            description: "",
+            palette,
+        }
+    }
+
+    pub fn description(mut self, description: &'static str) -> Self {
+        self.description = description;
+        self
+    }
+
+    /// 标签栏上显示的标题, 带上切换按键
+    pub fn title(&self) -> String {
+        format!("{} [{}]", self.name, self.key)
+    }
+
+    fn tab_title(&self, theme: &Theme) -> Line<'static> {
+        let title = format!("  {}  ", self.title());
+        match theme.tab_palette(self.palette) {
+            Some(palette) => title.fg(tailwind::SLATE.c200).bg(palette.c900).into(),
+            None => title.into(),
+        }
+    }
+
+    fn render_content(&self, area: Rect, buf: &mut Buffer, theme: &Theme) {
+        Paragraph::new(self.description)
+            .block(self.block(theme))
+            .render(area, buf);
+    }
+
+    /// A block surrounding the tab's content
+    fn block(&self, theme: &Theme) -> Block<'static> {
+        let border_style = theme
+            .tab_palette(self.palette)
+            .map_or(Style::new(), |palette| Style::new().fg(palette.c700));
+        Block::default()
+            .borders(Borders::ALL)
+            .border_set(symbols::border::PROPORTIONAL_TALL)
+            .padding(Padding::horizontal(1))
+            .border_style(border_style)
     }
+}
 
-    /// Get the next tab, if there is no next tab return the current tab.
-    fn next(self) -> Self {
-        let current_index = self as usize;
-        let next_index = current_index.saturating_add(1);
-        Self::from_repr(next_index).unwrap_or(self)
+impl DrawableComponent for TabComponent {
+    fn draw(&self, f: &mut Frame, _area: Rect, _focused: bool) -> anyhow::Result<()> {
+        f.render_widget(self, _area);
+        Ok(())
     }
 }
 
@@ -63,104 +162,129 @@ impl Widget for &TabComponent {
         let [tabs_area, title_area] = horizontal.areas(header_area);
 
         render_title(title_area, buf);
+        self.tabs_area.set(tabs_area);
         self.render_tabs(tabs_area, buf);
-        self.selected_tab.render(inner_area, buf);
-        // render_footer(footer_area, buf);
+        self.page().info().render_content(inner_area, buf, &self.theme);
     }
 }
 
 impl TabComponent {
-    pub fn new(key_config: KeyConfig) -> Self {
+    /// 标签栏至少有一个标签页, 其余的用 register 加在后面
+    pub fn new(first: Box<dyn TabContent>, theme: Theme) -> Self {
         Self {
-            state: TabState::Running,
-            selected_tab: SelectedTab::Tab1,
-            key_config,
+            pages: vec![first],
+            selected: 0,
+            theme,
+            tabs_area: Cell::new(Rect::default()),
         }
     }
-    fn render_tabs(&self, area: Rect, buf: &mut Buffer) {
-        let titles = SelectedTab::iter().map(SelectedTab::title);
-        let highlight_style = (Color::default(), self.selected_tab.palette().c700);
-        let selected_tab_index = self.selected_tab as usize;
-        Tabs::new(titles)
-            .highlight_style(highlight_style)
-            .select(selected_tab_index)
-            .padding("", "")
-            .divider(" ")
-            .render(area, buf);
-    }
-    pub fn next_tab(&mut self) {
-        self.selected_tab = self.selected_tab.next();
+
+    /// 注册一个标签页, 显示在已有标签页的后面
+    pub fn register(&mut self, page: Box<dyn TabContent>) {
+        self.pages.push(page);
     }
 
-    pub fn previous_tab(&mut self) {
-        self.selected_tab = self.selected_tab.previous();
+    pub fn pages(&self) -> impl Iterator<Item = &TabInfo> {
+        self.pages.iter().map(|page| page.info())
     }
 
-    pub fn quit(&mut self) {
-        self.state = TabState::Quitting;
+    /// 当前标签页, selected 总是指向已注册的标签页
+    pub fn page(&self) -> &dyn TabContent {
+        self.pages[self.selected].as_ref()
     }
-}
 
-fn render_title(area: Rect, buf: &mut Buffer) {
-    "Tcm Baby Walker".bold().render(area, buf);
-}
+    pub fn page_mut(&mut self) -> &mut dyn TabContent {
+        self.pages[self.selected].as_mut()
+    }
 
+    pub fn selected_id(&self) -> &'static str {
+        self.page().info().id
+    }
 
-impl Widget for SelectedTab {
-    fn render(self, area: Rect, buf: &mut Buffer) {
-        // in a real app these might be separate widgets
-        match self {
-            Self::Tab1 => self.render_tab0(area, buf),
-            Self::Tab2 => self.render_tab1(area, buf),
+    /// 选中 id 对应的标签页, 没有注册时不变并返回 false
+    pub fn select(&mut self, id: &str) -> bool {
+        match self.pages.iter().position(|page| page.info().id == id) {
+            Some(index) => {
+                self.selected = index;
+                true
+            }
+            None => false,
         }
     }
-}
 
-impl SelectedTab {
-    /// Return tab's name as a styled `Line`
-    fn title(self) -> Line<'static> {
-        format!("  {self}  ")
-            .fg(tailwind::SLATE.c200)
-            .bg(self.palette().c900)
-            .into()
+    /// 按键对应的标签页
+    pub fn id_for_key(&self, key: Key) -> Option<&'static str> {
+        self.pages().find(|info| info.key == key).map(|info| info.id)
     }
 
-    fn render_tab0(self, area: Rect, buf: &mut Buffer) {
-        Paragraph::new("检索主机信息, Tab 键切换选中主机模式")
-            .block(self.block())
-            .render(area, buf);
+    /// 标签栏上某一点对应的标签页, 和 render_tabs 的 padding/divider 一致
+    pub fn tab_at(&self, column: u16, row: u16) -> Option<&'static str> {
+        let area = self.tabs_area.get();
+        if !area.contains(Position::new(column, row)) {
+            return None;
+        }
+        let mut x = area.x;
+        for info in self.pages() {
+            let width = format!("  {}  ", info.title()).width() as u16;
+            if column < x + width {
+                return Some(info.id);
+            }
+            // divider 占一列
+            x += width + 1;
+        }
+        None
     }
 
-    fn render_tab1(self, area: Rect, buf: &mut Buffer) {
-        Paragraph::new("更详细的 Tcm 进程信息检索, 支持文件查看, Tab 键切换到检索内容选中模式")
-            .block(self.block())
-            .render(area, buf);
+    /// 依次让标签页显示 target, 选中第一个能显示的标签页
+    pub async fn reveal(&mut self, target: &TopologyTarget) -> Result<bool> {
+        for index in 0..self.pages.len() {
+            if self.pages[index].reveal(target).await? {
+                self.selected = index;
+                return Ok(true);
+            }
+        }
+        Ok(false)
     }
 
-
-    /// A block surrounding the tab's content
-    fn block(self) -> Block<'static> {
-        Block::default()
-            .borders(Borders::ALL)
-            .border_set(symbols::border::PROPORTIONAL_TALL)
-            .padding(Padding::horizontal(1))
-            .border_style(self.palette().c700)
+    pub fn set_theme(&mut self, theme: Theme) {
+        for page in &mut self.pages {
+            page.set_theme(theme.clone());
+        }
+        self.theme = theme;
     }
 
-    const fn palette(self) -> tailwind::Palette {
-        match self {
-            Self::Tab1 => tailwind::BLUE,
-            Self::Tab2 => tailwind::EMERALD,
+    pub fn set_density(&mut self, density: Density) {
+        for page in &mut self.pages {
+            page.set_density(density);
         }
     }
-}
 
+    fn render_tabs(&self, area: Rect, buf: &mut Buffer) {
+        let titles = self.pages().map(|info| info.tab_title(&self.theme));
+        let highlight_style = match self.theme.tab_palette(self.page().info().palette) {
+            Some(palette) => Style::new().bg(palette.c700),
+            None => Style::new().add_modifier(Modifier::REVERSED),
+        };
+        Tabs::new(titles)
+            .highlight_style(highlight_style)
+            .select(self.selected)
+            .padding("", "")
+            .divider(" ")
+            .render(area, buf);
+    }
 
-impl Component for TabComponent {
-    fn commands(&self, out: &mut Vec<CommandInfo>) {
-        out.push(CommandInfo::new(command::expand_collapse(&self.key_config)))
+    /// 下一个标签页, 最后一个之后回到第一个
+    pub fn next_id(&self) -> &'static str {
+        self.pages[(self.selected + 1) % self.pages.len()].info().id
     }
-    fn event(&mut self, _key: crate::event::Key) -> anyhow::Result<EventState> {
-        Ok(EventState::NotConsumed)
+
+    /// 上一个标签页, 第一个之前回到最后一个
+    pub fn previous_id(&self) -> &'static str {
+        self.pages[(self.selected + self.pages.len() - 1) % self.pages.len()].info().id
     }
 }
+
+fn render_title(area: Rect, buf: &mut Buffer) {
+    "Tcm Baby Walker".bold().render(area, buf);
+}
+
@@ -0,0 +1,255 @@
+use super::{Component, DrawableComponent, EventState};
+use crate::clipboard::copy_string;
+use crate::components::command::{self, Action, CommandInfo};
+use crate::components::total_proc::TotalProc;
+use crate::config::KeyConfig;
+use crate::event::Key;
+use crate::tools::proc::{ProcAction, ProcInfo};
+use crate::tools::tcm_command::{func_commands, proc_commands, query_command};
+use crate::ui::centered_rect;
+use anyhow::Result;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+    Frame,
+};
+
+/// 生成命令所用的目标
+#[derive(Clone)]
+pub enum CommandTarget {
+    Procs(Vec<TotalProc>),
+    Funcs(Vec<ProcInfo>),
+    Query(String),
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum CommandScope {
+    Selected,
+    Query,
+}
+
+pub struct TcmCommandComponent {
+    visible: bool,
+    actions: Vec<ProcAction>,
+    action_index: usize,
+    scope: CommandScope,
+    selected: Option<CommandTarget>,
+    query: Option<CommandTarget>,
+    message: String,
+    /// 退出程序后打印到终端的命令
+    pub output: Vec<String>,
+    key_config: KeyConfig,
+}
+
+impl TcmCommandComponent {
+    pub fn new(key_config: KeyConfig) -> Self {
+        Self {
+            visible: false,
+            actions: ProcAction::POPUP.to_vec(),
+            action_index: 0,
+            scope: CommandScope::Selected,
+            selected: None,
+            query: None,
+            message: String::new(),
+            output: Vec::new(),
+            key_config,
+        }
+    }
+
+    pub fn set_targets(&mut self, selected: Option<CommandTarget>, query: Option<CommandTarget>) {
+        self.scope = if selected.is_none() && query.is_some() {
+            CommandScope::Query
+        } else {
+            CommandScope::Selected
+        };
+        self.selected = selected;
+        self.query = query;
+        self.message = String::new();
+    }
+
+    fn target(&self) -> Option<&CommandTarget> {
+        match self.scope {
+            CommandScope::Selected => self.selected.as_ref(),
+            CommandScope::Query => self.query.as_ref(),
+        }
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        let Some(target) = self.target() else {
+            return vec![];
+        };
+        self.actions[self.action_index]
+            .tcm_commands()
+            .iter()
+            .flat_map(|&cmd| match target {
+                CommandTarget::Procs(procs) => proc_commands(cmd, procs),
+                CommandTarget::Funcs(procs) => func_commands(cmd, procs),
+                CommandTarget::Query(query) => vec![query_command(cmd, query)],
+            })
+            .collect()
+    }
+
+    fn next_action(&mut self) {
+        self.action_index = (self.action_index + 1) % self.actions.len();
+    }
+
+    fn previous_action(&mut self) {
+        let count = self.actions.len();
+        self.action_index = (self.action_index + count - 1) % count;
+    }
+
+    fn toggle_scope(&mut self) {
+        self.scope = match self.scope {
+            CommandScope::Selected => CommandScope::Query,
+            CommandScope::Query => CommandScope::Selected,
+        };
+    }
+
+    fn copy(&mut self) -> Result<()> {
+        let lines = self.lines();
+        if lines.is_empty() {
+            return Ok(());
+        }
+        let copied = copy_string(&lines.join("\n"))?;
+        self.message = format!("已复制 {} 条命令到{}", lines.len(), copied);
+        Ok(())
+    }
+
+    fn print_on_exit(&mut self) {
+        let lines = self.lines();
+        self.message = format!("退出后打印 {} 条命令", lines.len());
+        self.output.extend(lines);
+    }
+}
+
+impl DrawableComponent for TcmCommandComponent {
+    fn draw(&self, f: &mut Frame, _area: Rect, _focused: bool) -> Result<()> {
+        if !self.visible {
+            return Ok(());
+        }
+        const SIZE: (u16, u16) = (80, 20);
+        let area = centered_rect(SIZE, f.size());
+
+        f.render_widget(Clear, area);
+        f.render_widget(
+            Block::default()
+                .title("Tcm Command")
+                .borders(Borders::ALL)
+                .border_type(BorderType::Thick),
+            area,
+        );
+
+        let chunks = Layout::default()
+            .vertical_margin(1)
+            .horizontal_margin(1)
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Min(1),
+                Constraint::Length(1),
+            ])
+            .split(area);
+
+        let cmds = self
+            .actions
+            .iter()
+            .enumerate()
+            .flat_map(|(i, action)| {
+                let style = if i == self.action_index {
+                    Style::default().add_modifier(Modifier::REVERSED)
+                } else {
+                    Style::default()
+                };
+                [Span::styled(format!(" {} ", action), style), Span::raw(" ")]
+            })
+            .collect::<Vec<_>>();
+        f.render_widget(Paragraph::new(Line::from(cmds)), chunks[0]);
+
+        let scope = match self.scope {
+            CommandScope::Selected => "范围: [选中行] 当前查询",
+            CommandScope::Query => "范围: 选中行 [当前查询]",
+        };
+        f.render_widget(
+            Paragraph::new(Span::styled(scope, Style::default().fg(Color::Yellow))),
+            chunks[1],
+        );
+
+        let lines = self.lines();
+        let content = if lines.is_empty() {
+            vec![Line::from("没有可用的目标")]
+        } else {
+            lines.into_iter().map(Line::from).collect()
+        };
+        f.render_widget(
+            Paragraph::new(content).block(Block::default().borders(Borders::TOP)),
+            chunks[2],
+        );
+
+        let help = if self.message.is_empty() {
+            format!(
+                "[{}/{}] command [{}] scope [{}] copy [{}] print on exit [{}] close",
+                self.key_config.scroll_up,
+                self.key_config.scroll_down,
+                self.key_config.toggle_focus,
+                self.key_config.copy,
+                self.key_config.enter,
+                self.key_config.exit_popup,
+            )
+        } else {
+            self.message.clone()
+        };
+        f.render_widget(
+            Paragraph::new(Span::styled(help, Style::default().fg(Color::DarkGray))),
+            chunks[3],
+        );
+
+        Ok(())
+    }
+}
+
+impl Component for TcmCommandComponent {
+    fn commands(&self, out: &mut Vec<CommandInfo>) {
+        out.push(
+            CommandInfo::new(command::tcm_command(&self.key_config))
+                .action("Generate tcm command", Action::Keys(vec![self.key_config.tcm_command])),
+        )
+    }
+
+    fn event(&mut self, key: Key) -> Result<EventState> {
+        if !self.visible {
+            return Ok(EventState::NotConsumed);
+        }
+        if key == self.key_config.exit_popup {
+            self.hide();
+        } else if key == self.key_config.scroll_down || key == self.key_config.move_down {
+            self.next_action();
+        } else if key == self.key_config.scroll_up || key == self.key_config.move_up {
+            self.previous_action();
+        } else if key == self.key_config.toggle_focus {
+            self.toggle_scope();
+        } else if key == self.key_config.copy {
+            self.copy()?;
+        } else if key == self.key_config.enter {
+            self.print_on_exit();
+            self.hide();
+        }
+        // 弹窗打开时吞掉所有按键
+        Ok(EventState::Consumed)
+    }
+
+    fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    fn hide(&mut self) {
+        self.visible = false;
+    }
+
+    fn show(&mut self) -> Result<()> {
+        self.visible = true;
+        Ok(())
+    }
+}
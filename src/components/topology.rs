@@ -0,0 +1,225 @@
+use super::{Component, DrawableComponent, EventState};
+use crate::components::command::{self, CommandInfo};
+use crate::config::KeyConfig;
+use crate::database::select_all_deploys;
+use crate::event::Key;
+use crate::theme::Theme;
+use crate::tools::topology::{NodeKind, Topology};
+use crate::ui::{wrap_next, wrap_previous};
+use anyhow::Result;
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+use sqlx::SqlitePool;
+
+/// 拓扑树上选中节点后 App 需要执行的跳转
+pub enum TopologyTarget {
+    Host(crate::tools::host::HostInfo),
+    Proc(i32),
+}
+
+pub struct TopologyComponent {
+    topology: Topology,
+    /// 当前可见节点在 topology 里的下标
+    visible: Vec<usize>,
+    state: ListState,
+    filter: String,
+    filtering: bool,
+    theme: Theme,
+    key_config: KeyConfig,
+}
+
+impl TopologyComponent {
+    pub async fn new(db: &SqlitePool, key_config: KeyConfig, theme: Theme) -> Result<Self> {
+        let topology = Topology::build(&select_all_deploys(db).await?);
+        let mut component = Self {
+            topology,
+            visible: Vec::new(),
+            state: ListState::default(),
+            filter: String::new(),
+            filtering: false,
+            theme,
+            key_config,
+        };
+        component.refresh(Some(0));
+        Ok(component)
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    /// 过滤框正在输入时, 所有按键都交给过滤框
+    pub fn is_filtering(&self) -> bool {
+        self.filtering
+    }
+
+    fn selected_node(&self) -> Option<usize> {
+        self.visible.get(self.state.selected()?).copied()
+    }
+
+    /// 重新计算可见节点, 尽量保持选中原来的节点
+    fn refresh(&mut self, keep: Option<usize>) {
+        self.visible = self.topology.visible(&self.filter);
+        let selected = keep
+            .and_then(|node| self.visible.iter().position(|&i| i == node))
+            .or((!self.visible.is_empty()).then_some(0));
+        self.state.select(selected);
+    }
+
+    pub fn target(&self) -> Option<TopologyTarget> {
+        let node = self.topology.node(self.selected_node()?)?;
+        match &node.kind {
+            NodeKind::Host(host) => Some(TopologyTarget::Host(host.clone())),
+            NodeKind::Proc { func_id, .. } => Some(TopologyTarget::Proc(*func_id)),
+            _ => None,
+        }
+    }
+
+    fn next(&mut self) {
+        if let Some(i) = wrap_next(self.state.selected(), self.visible.len()) {
+            self.state.select(Some(i));
+        }
+    }
+
+    fn previous(&mut self) {
+        if let Some(i) = wrap_previous(self.state.selected(), self.visible.len()) {
+            self.state.select(Some(i));
+        }
+    }
+
+    fn expand(&mut self) {
+        if let Some(node) = self.selected_node() {
+            self.topology.set_expanded(node, true);
+            self.refresh(Some(node));
+        }
+    }
+
+    /// 已展开的节点折叠, 否则跳到父节点
+    fn collapse(&mut self) {
+        let Some(node) = self.selected_node() else {
+            return;
+        };
+        let expanded = self.topology.node(node).is_some_and(|n| n.expanded);
+        if expanded && self.filter.is_empty() {
+            self.topology.set_expanded(node, false);
+            self.refresh(Some(node));
+        } else {
+            let parent = self.topology.parent(node);
+            self.refresh(parent.or(Some(node)));
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        if let Some(node) = self.selected_node() {
+            let expanded = self.topology.node(node).is_some_and(|n| n.expanded);
+            self.topology.set_expanded(node, !expanded);
+            self.refresh(Some(node));
+        }
+    }
+
+    fn filter_event(&mut self, key: Key) {
+        let keep = self.selected_node();
+        match key {
+            Key::Char(c) => self.filter.push(c),
+            Key::Backspace => {
+                self.filter.pop();
+            }
+            _ if key == self.key_config.enter => self.filtering = false,
+            _ if key == self.key_config.exit_popup => {
+                self.filtering = false;
+                self.filter.clear();
+            }
+            _ => return,
+        }
+        self.refresh(keep);
+    }
+}
+
+impl DrawableComponent for TopologyComponent {
+    fn draw(&self, f: &mut Frame, area: Rect, _focused: bool) -> Result<()> {
+        let colors = self.theme.table_colors(0);
+        let [tree_area, filter_area] =
+            Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(area);
+
+        let items = self.visible.iter().filter_map(|&i| {
+            let node = self.topology.node(i)?;
+            let marker = if node.is_leaf() {
+                "  "
+            } else if node.expanded || !self.filter.is_empty() {
+                "▾ "
+            } else {
+                "▸ "
+            };
+            Some(ListItem::new(Line::from(vec![
+                Span::raw("  ".repeat(node.depth)),
+                Span::raw(marker),
+                Span::raw(node.label()),
+            ])))
+        });
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!("Topology ({})", self.visible.len())),
+            )
+            .style(Style::default().fg(colors.row_fg).bg(colors.buffer_bg))
+            .highlight_style(
+                Style::default()
+                    .add_modifier(Modifier::REVERSED)
+                    .fg(colors.selected_style_fg),
+            );
+        f.render_stateful_widget(list, tree_area, &mut self.state.clone());
+
+        let filter = if self.filtering {
+            format!("{}{}█", self.key_config.filter, self.filter)
+        } else if self.filter.is_empty() {
+            format!(
+                "[{}] filter [{}/{}] expand/collapse [{}] detail",
+                self.key_config.filter,
+                self.key_config.scroll_right,
+                self.key_config.scroll_left,
+                self.key_config.enter,
+            )
+        } else {
+            format!("{}{}", self.key_config.filter, self.filter)
+        };
+        f.render_widget(Paragraph::new(filter), filter_area);
+        Ok(())
+    }
+}
+
+impl Component for TopologyComponent {
+    fn commands(&self, out: &mut Vec<CommandInfo>) {
+        out.push(CommandInfo::new(command::expand_collapse(&self.key_config)))
+    }
+
+    fn event(&mut self, key: Key) -> Result<EventState> {
+        if self.filtering {
+            self.filter_event(key);
+            return Ok(EventState::Consumed);
+        }
+        if key == self.key_config.filter {
+            self.filtering = true;
+        } else if key == self.key_config.scroll_down || key == self.key_config.move_down {
+            self.next();
+        } else if key == self.key_config.scroll_up || key == self.key_config.move_up {
+            self.previous();
+        } else if key == self.key_config.scroll_right || key == Key::Right {
+            self.expand();
+        } else if key == self.key_config.scroll_left || key == Key::Left {
+            self.collapse();
+        } else if key == self.key_config.exit_popup && !self.filter.is_empty() {
+            let keep = self.selected_node();
+            self.filter.clear();
+            self.refresh(keep);
+        } else {
+            return Ok(EventState::NotConsumed);
+        }
+        Ok(EventState::Consumed)
+    }
+}
@@ -1,6 +1,98 @@
+use crate::components::data_table::Density;
+use crate::components::query_input::QueryType;
 use crate::event::Key;
+use crate::theme::ThemeName;
+use anyhow::anyhow;
+use hashbrown::HashMap;
 use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+const CONFIG_DIR: &str = "tcmhelper";
+const CONFIG_FILE: &str = "config.toml";
+
+/// 用户配置, 默认从 `$XDG_CONFIG_HOME/tcmhelper/config.toml` 读取
 #[derive(Debug, Deserialize, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    #[serde(rename = "keys")]
+    pub key_config: KeyConfig,
+    /// 事件循环的 tick 间隔, 单位毫秒
+    pub tick_rate: u64,
+    /// 启动时选中的标签页: host | proc | tree | stats
+    pub default_tab: String,
+    pub default_query_type: QueryType,
+    pub theme: ThemeName,
+    /// 文件查看使用的 .tmTheme 语法高亮主题
+    pub syntax_theme: Option<PathBuf>,
+    /// 关闭后不捕获鼠标, 可以直接用终端选择文字
+    pub mouse: bool,
+    /// 表格行高: compact | normal | spacious
+    pub density: Density,
+    /// 其它 TCM 配置目录, 名称 -> 路径, 可以在命令面板中切换
+    pub profiles: BTreeMap<String, PathBuf>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            key_config: KeyConfig::default(),
+            tick_rate: 250,
+            default_tab: "host".to_string(),
+            default_query_type: QueryType::default(),
+            theme: ThemeName::default(),
+            syntax_theme: None,
+            mouse: true,
+            density: Density::default(),
+            profiles: BTreeMap::new(),
+        }
+    }
+}
+
+impl Config {
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join(CONFIG_DIR).join(CONFIG_FILE))
+    }
+
+    /// 指定路径时文件必须存在, 否则默认路径下没有配置文件就使用默认值
+    pub fn load(path: Option<PathBuf>) -> anyhow::Result<Self> {
+        match path {
+            Some(path) => Self::from_file(&path),
+            None => match Self::default_path() {
+                Some(path) if path.exists() => Self::from_file(&path),
+                _ => Ok(Self::default()),
+            },
+        }
+    }
+
+    fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("Read config -> [{}] failed: {}", path.display(), e))?;
+        toml::from_str(&content)
+            .map_err(|e| anyhow!("Parse config -> [{}] failed: {}", path.display(), e))
+    }
+}
+
+/// 按键生效的范围. 全局按键在表格和文件查看中都生效, 复制格式只在按下 copy 之后生效
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum KeyScope {
+    Global,
+    Table,
+    File,
+    Yank,
+}
+
+impl KeyScope {
+    /// 两个范围的按键是否会同时生效
+    fn overlaps(self, other: KeyScope) -> bool {
+        self == other
+            || (self == KeyScope::Global && other != KeyScope::Yank)
+            || (other == KeyScope::Global && self != KeyScope::Yank)
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default, deny_unknown_fields)]
 pub struct KeyConfig {
     pub scroll_up: Key,
     pub scroll_down: Key,
@@ -13,29 +105,54 @@ pub struct KeyConfig {
     pub exit: Key,
     pub quit: Key,
     pub exit_popup: Key,
-    pub focus_right: Key,
-    pub focus_left: Key,
-    pub focus_above: Key,
-    pub focus_connections: Key,
+    pub toggle_focus: Key,
     pub open_help: Key,
     pub filter: Key,
     pub scroll_down_multiple_lines: Key,
     pub scroll_up_multiple_lines: Key,
     pub scroll_to_top: Key,
     pub scroll_to_bottom: Key,
-    pub extend_selection_by_one_cell_left: Key,
-    pub extend_selection_by_one_cell_right: Key,
-    pub extend_selection_by_one_cell_up: Key,
-    pub extend_selection_by_one_cell_down: Key,
-    pub tab_records: Key,
-    pub tab_columns: Key,
-    pub tab_constraints: Key,
-    pub tab_foreign_keys: Key,
-    pub tab_indexes: Key,
-    pub tab_sql_editor: Key,
-    pub tab_properties: Key,
-    pub extend_or_shorten_widget_width_to_right: Key,
-    pub extend_or_shorten_widget_width_to_left: Key,
+    pub tab_host: Key,
+    pub tab_proc: Key,
+    pub tab_tree: Key,
+    pub tab_stats: Key,
+    pub next_tab: Key,
+    pub prev_tab: Key,
+    pub tcm_command: Key,
+    pub switch_theme: Key,
+    pub open_config: Key,
+    pub open_binary: Key,
+    pub browse_files: Key,
+    pub sort_column: Key,
+    pub hide_column: Key,
+    pub show_columns: Key,
+    pub move_column_left: Key,
+    pub move_column_right: Key,
+    pub switch_palette: Key,
+    pub mark: Key,
+    pub visual_mark: Key,
+    pub narrow_marked: Key,
+    pub copy_ips: Key,
+    pub export_rows: Key,
+    pub switch_density: Key,
+    pub toggle_wrap: Key,
+    pub goto_line: Key,
+    pub search_next: Key,
+    pub search_prev: Key,
+    pub diff_marked: Key,
+    pub toggle_diff_layout: Key,
+    pub follow_file: Key,
+    pub pause_follow: Key,
+    pub filter_follow: Key,
+    pub open_editor: Key,
+    pub command_palette: Key,
+    pub message_log: Key,
+    /// 按下 copy 之后选择复制格式的按键
+    pub yank_cell: Key,
+    pub yank_row: Key,
+    pub yank_column: Key,
+    pub yank_ips: Key,
+    pub yank_selector: Key,
 }
 
 impl Default for KeyConfig {
@@ -52,29 +169,223 @@ impl Default for KeyConfig {
             exit: Key::Ctrl('c'),
             quit: Key::Char('q'),
             exit_popup: Key::Esc,
-            focus_right: Key::Right,
-            focus_left: Key::Left,
-            focus_above: Key::Up,
-            focus_connections: Key::Char('c'),
+            toggle_focus: Key::Tab,
             open_help: Key::Char('?'),
             filter: Key::Char('/'),
             scroll_down_multiple_lines: Key::Ctrl('d'),
             scroll_up_multiple_lines: Key::Ctrl('u'),
             scroll_to_top: Key::Char('g'),
             scroll_to_bottom: Key::Char('G'),
-            extend_selection_by_one_cell_left: Key::Char('H'),
-            extend_selection_by_one_cell_right: Key::Char('L'),
-            extend_selection_by_one_cell_down: Key::Char('J'),
-            extend_selection_by_one_cell_up: Key::Char('K'),
-            tab_records: Key::Char('1'),
-            tab_properties: Key::Char('2'),
-            tab_sql_editor: Key::Char('3'),
-            tab_columns: Key::Char('4'),
-            tab_constraints: Key::Char('5'),
-            tab_foreign_keys: Key::Char('6'),
-            tab_indexes: Key::Char('7'),
-            extend_or_shorten_widget_width_to_right: Key::Char('>'),
-            extend_or_shorten_widget_width_to_left: Key::Char('<'),
+            tab_host: Key::Char('1'),
+            tab_proc: Key::Char('2'),
+            tab_tree: Key::Char('3'),
+            tab_stats: Key::Char('4'),
+            next_tab: Key::Char(']'),
+            prev_tab: Key::Char('['),
+            tcm_command: Key::Char('t'),
+            switch_theme: Key::Char('T'),
+            open_config: Key::Char('o'),
+            open_binary: Key::Char('b'),
+            browse_files: Key::Char('f'),
+            sort_column: Key::Char('s'),
+            hide_column: Key::Char('x'),
+            show_columns: Key::Char('X'),
+            move_column_left: Key::Char('H'),
+            move_column_right: Key::Char('L'),
+            switch_palette: Key::Char('c'),
+            mark: Key::Char(' '),
+            visual_mark: Key::Char('v'),
+            narrow_marked: Key::Char('n'),
+            copy_ips: Key::Char('Y'),
+            export_rows: Key::Char('e'),
+            switch_density: Key::Char('D'),
+            toggle_wrap: Key::Char('w'),
+            goto_line: Key::Char(':'),
+            search_next: Key::Char('n'),
+            search_prev: Key::Char('N'),
+            diff_marked: Key::Char('d'),
+            toggle_diff_layout: Key::Char('s'),
+            follow_file: Key::Char('F'),
+            pause_follow: Key::Char('p'),
+            filter_follow: Key::Char('&'),
+            open_editor: Key::Char('E'),
+            command_palette: Key::Ctrl('p'),
+            message_log: Key::Char('M'),
+            yank_cell: Key::Char('c'),
+            yank_row: Key::Char('r'),
+            yank_column: Key::Char('C'),
+            yank_ips: Key::Char('i'),
+            yank_selector: Key::Char('t'),
         }
     }
 }
+
+impl KeyConfig {
+    fn bindings(&self) -> Vec<(&'static str, KeyScope, Key)> {
+        vec![
+            ("scroll_up", KeyScope::Global, self.scroll_up),
+            ("scroll_down", KeyScope::Global, self.scroll_down),
+            ("scroll_right", KeyScope::Global, self.scroll_right),
+            ("scroll_left", KeyScope::Global, self.scroll_left),
+            ("move_up", KeyScope::Global, self.move_up),
+            ("move_down", KeyScope::Global, self.move_down),
+            ("copy", KeyScope::Global, self.copy),
+            ("enter", KeyScope::Global, self.enter),
+            ("exit", KeyScope::Global, self.exit),
+            ("quit", KeyScope::Global, self.quit),
+            ("exit_popup", KeyScope::Global, self.exit_popup),
+            ("toggle_focus", KeyScope::Global, self.toggle_focus),
+            ("open_help", KeyScope::Global, self.open_help),
+            ("filter", KeyScope::Global, self.filter),
+            ("scroll_down_multiple_lines", KeyScope::Global, self.scroll_down_multiple_lines),
+            ("scroll_up_multiple_lines", KeyScope::Global, self.scroll_up_multiple_lines),
+            ("scroll_to_top", KeyScope::Global, self.scroll_to_top),
+            ("scroll_to_bottom", KeyScope::Global, self.scroll_to_bottom),
+            ("tab_host", KeyScope::Global, self.tab_host),
+            ("tab_proc", KeyScope::Global, self.tab_proc),
+            ("tab_tree", KeyScope::Global, self.tab_tree),
+            ("tab_stats", KeyScope::Global, self.tab_stats),
+            ("next_tab", KeyScope::Global, self.next_tab),
+            ("prev_tab", KeyScope::Global, self.prev_tab),
+            ("tcm_command", KeyScope::Table, self.tcm_command),
+            ("switch_theme", KeyScope::Global, self.switch_theme),
+            ("open_config", KeyScope::Table, self.open_config),
+            ("open_binary", KeyScope::Table, self.open_binary),
+            ("browse_files", KeyScope::Table, self.browse_files),
+            ("sort_column", KeyScope::Table, self.sort_column),
+            ("hide_column", KeyScope::Table, self.hide_column),
+            ("show_columns", KeyScope::Table, self.show_columns),
+            ("move_column_left", KeyScope::Table, self.move_column_left),
+            ("move_column_right", KeyScope::Table, self.move_column_right),
+            ("switch_palette", KeyScope::Table, self.switch_palette),
+            ("mark", KeyScope::Global, self.mark),
+            ("visual_mark", KeyScope::Table, self.visual_mark),
+            ("narrow_marked", KeyScope::Table, self.narrow_marked),
+            ("copy_ips", KeyScope::Table, self.copy_ips),
+            ("export_rows", KeyScope::Table, self.export_rows),
+            ("switch_density", KeyScope::Global, self.switch_density),
+            ("toggle_wrap", KeyScope::File, self.toggle_wrap),
+            ("goto_line", KeyScope::File, self.goto_line),
+            ("search_next", KeyScope::File, self.search_next),
+            ("search_prev", KeyScope::File, self.search_prev),
+            ("diff_marked", KeyScope::File, self.diff_marked),
+            ("toggle_diff_layout", KeyScope::File, self.toggle_diff_layout),
+            ("follow_file", KeyScope::File, self.follow_file),
+            ("pause_follow", KeyScope::File, self.pause_follow),
+            ("filter_follow", KeyScope::File, self.filter_follow),
+            ("open_editor", KeyScope::Global, self.open_editor),
+            ("command_palette", KeyScope::Global, self.command_palette),
+            ("message_log", KeyScope::Global, self.message_log),
+            ("yank_cell", KeyScope::Yank, self.yank_cell),
+            ("yank_row", KeyScope::Yank, self.yank_row),
+            ("yank_column", KeyScope::Yank, self.yank_column),
+            ("yank_ips", KeyScope::Yank, self.yank_ips),
+            ("yank_selector", KeyScope::Yank, self.yank_selector),
+        ]
+    }
+
+    /// 返回绑定到同一个按键上并且会同时生效的配置项, 每个冲突一行
+    pub fn conflicts(&self) -> Vec<String> {
+        let bindings = self.bindings();
+        let mut by_key: HashMap<Key, Vec<(&str, KeyScope)>> = HashMap::new();
+        for (name, scope, key) in &bindings {
+            by_key.entry(*key).or_default().push((name, *scope));
+        }
+        let mut conflicts = Vec::new();
+        for (_, _, key) in &bindings {
+            let Some(entries) = by_key.remove(key) else {
+                continue;
+            };
+            let names = entries
+                .iter()
+                .filter(|(name, scope)| {
+                    entries
+                        .iter()
+                        .any(|(other, other_scope)| other != name && scope.overlaps(*other_scope))
+                })
+                .map(|(name, _)| *name)
+                .collect::<Vec<_>>();
+            if !names.is_empty() {
+                conflicts.push(format!("{} -> {}", key, names.join(", ")));
+            }
+        }
+        conflicts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() {
+        let config: Config = toml::from_str(
+            r#"
+            tick_rate = 100
+            default_tab = "proc"
+            default_query_type = "text"
+            theme = "light"
+            mouse = false
+            density = "compact"
+
+            [profiles]
+            live = "/data/tcm/live"
+
+            [keys]
+            scroll_down = "n"
+            scroll_up_multiple_lines = "<Ctrl+b>"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.tick_rate, 100);
+        assert_eq!(config.default_tab, "proc");
+        assert_eq!(config.default_query_type, QueryType::Text);
+        assert_eq!(config.theme, ThemeName::Light);
+        assert!(!config.mouse);
+        assert_eq!(config.density, Density::Compact);
+        assert_eq!(config.profiles["live"], PathBuf::from("/data/tcm/live"));
+        assert_eq!(config.key_config.scroll_down, Key::Char('n'));
+        assert_eq!(config.key_config.scroll_up_multiple_lines, Key::Ctrl('b'));
+        assert_eq!(config.key_config.scroll_up, Key::Char('k'));
+
+        assert!(toml::from_str::<Config>("[keys]\nscrol_down = \"n\"").is_err());
+    }
+
+    #[test]
+    fn test_key_conflicts() {
+        assert!(KeyConfig::default().conflicts().is_empty());
+
+        let key_config = KeyConfig {
+            filter: Key::Char('j'),
+            tcm_command: Key::Char('j'),
+            copy: Key::Tab,
+            ..KeyConfig::default()
+        };
+        assert_eq!(
+            key_config.conflicts(),
+            vec![
+                "j -> scroll_down, filter, tcm_command",
+                "<Tab> -> copy, toggle_focus"
+            ]
+        );
+
+        // 不同范围的同一个按键不冲突, 复制格式只和复制格式冲突
+        let key_config = KeyConfig {
+            sort_column: Key::Char('w'),
+            yank_row: Key::Char('c'),
+            yank_ips: Key::Char('j'),
+            ..KeyConfig::default()
+        };
+        assert_eq!(key_config.conflicts(), vec!["c -> yank_cell, yank_row"]);
+
+        // 全局按键和表格, 文件查看里的按键都会冲突
+        let key_config = KeyConfig {
+            scroll_down: Key::Char('n'),
+            ..KeyConfig::default()
+        };
+        assert_eq!(
+            key_config.conflicts(),
+            vec!["n -> scroll_down, narrow_marked, search_next"]
+        );
+    }
+}
@@ -1,7 +1,11 @@
 use serde::{Deserialize, Serialize};
-use sqlx::{sqlite::SqliteConnectOptions, FromRow, SqlitePool};
+use sqlx::{sqlite::SqliteConnectOptions, FromRow, SqliteConnection, SqlitePool};
 
-use crate::tools::{deploy::DeployInfo, host::HostInfo, proc::ProcInfo};
+use crate::tools::{
+    deploy::DeployInfo,
+    host::HostInfo,
+    proc::{ProcAttributes, ProcInfo},
+};
 use sqlx::Row;
 use tracing::log::info;
 
@@ -38,10 +42,15 @@ pub async fn init_sqlx_table() -> Result<SqlitePool, sqlx::Error> {
     std::fs::File::create(SQLX_DATABASE_URL)?;
     let options = SqliteConnectOptions::new().filename(SQLX_DATABASE_URL);
     let pool = SqlitePool::connect_with(options).await?;
+    create_tables(&pool).await?;
+    Ok(pool)
+}
+
+pub async fn create_tables(pool: &SqlitePool) -> Result<(), sqlx::Error> {
     for sql in return_init_sqls() {
-        sqlx::query(sql).execute(&pool).await?;
+        sqlx::query(sql).execute(pool).await?;
     }
-    Ok(pool)
+    Ok(())
 }
 
 fn return_init_sqls() -> Vec<&'static str> {
@@ -59,7 +68,14 @@ fn return_init_sqls() -> Vec<&'static str> {
             work_path TEXT NOT NULL,
             func_name TEXT NOT NULL,
             proc_name TEXT NOT NULL,
-            proc_group_name TEXT NOT NULL
+            proc_group_name TEXT NOT NULL,
+            flag TEXT NOT NULL,
+            is_common TEXT,
+            config_path TEXT,
+            seq TEXT,
+            auto_script TEXT,
+            restart_cmd TEXT,
+            args TEXT
         )",
         "CREATE TABLE deploy (
             id INTEGER PRIMARY KEY,
@@ -71,7 +87,15 @@ fn return_init_sqls() -> Vec<&'static str> {
     ]
 }
 
-pub async fn insert_hosts(pool: &SqlitePool, hosts: &Vec<HostInfo>) -> Result<(), sqlx::Error> {
+/// 重新导入前清空所有数据, 和导入在同一个事务里执行
+pub async fn clear_tables(conn: &mut SqliteConnection) -> Result<(), sqlx::Error> {
+    for table in ["deploy", "procs", "hosts"] {
+        sqlx::query(&format!("DELETE FROM {}", table)).execute(&mut *conn).await?;
+    }
+    Ok(())
+}
+
+pub async fn insert_hosts(conn: &mut SqliteConnection, hosts: &Vec<HostInfo>) -> Result<(), sqlx::Error> {
     let mut values = String::new();
 
     for (i, host_info) in hosts.iter().enumerate() {
@@ -88,32 +112,53 @@ pub async fn insert_hosts(pool: &SqlitePool, hosts: &Vec<HostInfo>) -> Result<()
         "INSERT INTO hosts (inner_ip, host_name, world_id, zone_id) VALUES {}",
         values
     );
-    sqlx::query(&sql).execute(pool).await?;
+    sqlx::query(&sql).execute(conn).await?;
     Ok(())
 }
 
-pub async fn insert_procs(pool: &SqlitePool, procs: &Vec<ProcInfo>) -> Result<(), sqlx::Error> {
+pub async fn insert_procs(conn: &mut SqliteConnection, procs: &Vec<ProcInfo>) -> Result<(), sqlx::Error> {
     let mut values = String::new();
 
     for (i, proc) in procs.iter().enumerate() {
         if i > 0 {
             values.push_str(", ");
         }
+        let attrs = &proc.attributes;
         values.push_str(&format!(
-            "('{}', '{}', '{}', '{}', '{}', '{}')",
-            proc.func_id, proc.layer, proc.work_path, proc.funcname, proc.proc_name, proc.group_name
+            "('{}', '{}', '{}', '{}', '{}', '{}', {}, {}, {}, {}, {}, {}, {})",
+            proc.func_id,
+            proc.layer,
+            proc.work_path,
+            proc.funcname,
+            proc.proc_name,
+            proc.group_name,
+            sql_text(Some(&attrs.flag)),
+            sql_text(attrs.is_common.as_ref()),
+            sql_text(attrs.config_path.as_ref()),
+            sql_text(attrs.seq.as_ref()),
+            sql_text(attrs.auto_script.as_ref()),
+            sql_text(attrs.restart_cmd.as_ref()),
+            sql_text(attrs.args.as_ref()),
         ));
     }
 
     let sql = format!(
-        "INSERT INTO procs (func_id, proc_type, work_path, func_name, proc_name, proc_group_name) VALUES {}",
+        "INSERT INTO procs (func_id, proc_type, work_path, func_name, proc_name, proc_group_name, flag, is_common, config_path, seq, auto_script, restart_cmd, args) VALUES {}",
         values
     );
 
-    sqlx::query(&sql).execute(pool).await?;
+    sqlx::query(&sql).execute(conn).await?;
     Ok(())
 }
-pub async fn insert_deploy(pool: &SqlitePool, procs: &Vec<DeployInfo>) -> Result<(), sqlx::Error> {
+/// 可选属性里可能带引号 (比如 ReStartCmd), 需要转义
+fn sql_text(value: Option<&String>) -> String {
+    match value {
+        Some(v) => format!("'{}'", v.replace('\'', "''")),
+        None => "NULL".to_string(),
+    }
+}
+
+pub async fn insert_deploy(conn: &mut SqliteConnection, procs: &Vec<DeployInfo>) -> Result<(), sqlx::Error> {
     let mut values = String::new();
 
     for (i, proc) in procs.iter().enumerate() {
@@ -131,7 +176,7 @@ pub async fn insert_deploy(pool: &SqlitePool, procs: &Vec<DeployInfo>) -> Result
         values
     );
 
-    sqlx::query(&sql).execute(pool).await?;
+    sqlx::query(&sql).execute(conn).await?;
     Ok(())
 }
 
@@ -146,6 +191,125 @@ pub async fn select_all_proc(pool: &SqlitePool) -> Result<Vec<ProcInfo>, sqlx::E
     Ok(procs)
 }
 
+pub async fn select_proc(pool: &SqlitePool, func_id: i32) -> Result<Option<ProcInfo>, sqlx::Error> {
+    let sql = format!("select * from procs WHERE func_id = '{}'", func_id);
+    let row = sqlx::query(&sql).fetch_optional(pool).await?;
+    row.map(|r| ProcInfo::from_row(&r)).transpose()
+}
+
+pub async fn select_group_procs(
+    pool: &SqlitePool,
+    group_name: &str,
+) -> Result<Vec<ProcInfo>, sqlx::Error> {
+    let sql = format!(
+        "select * from procs WHERE proc_group_name = '{}' ORDER BY func_id",
+        group_name
+    );
+    let query = sqlx::query(&sql).fetch_all(pool).await?;
+    query.iter().map(ProcInfo::from_row).collect()
+}
+
+/// 进程所在的 ProcGroup 在每个 world/zone/主机上的部署
+pub async fn select_proc_deploys(
+    pool: &SqlitePool,
+    func_id: i32,
+) -> Result<Vec<TcmQueryResult>, sqlx::Error> {
+    let sql = format!(
+        "select * from hosts JOIN deploy ON hosts.id = deploy.host_id \
+         JOIN procs ON procs.proc_group_name = deploy.group_name \
+         WHERE procs.func_id = '{}' \
+         ORDER BY CAST(hosts.world_id AS INTEGER), CAST(hosts.zone_id AS INTEGER), hosts.host_name, deploy.inst_id",
+        func_id
+    );
+    let query = sqlx::query(&sql).fetch_all(pool).await?;
+    query.iter().map(TcmQueryResult::from_row).collect()
+}
+
+pub async fn select_all_deploys(pool: &SqlitePool) -> Result<Vec<TcmQueryResult>, sqlx::Error> {
+    let sql = "select * from hosts JOIN deploy ON hosts.id = deploy.host_id \
+               JOIN procs ON procs.proc_group_name = deploy.group_name";
+    let query = sqlx::query(sql).fetch_all(pool).await?;
+    query.iter().map(TcmQueryResult::from_row).collect()
+}
+
+/// 部署在同一个内网 IP 上的全部进程实例, 跨所有 world/zone
+pub async fn select_host_deploys(
+    pool: &SqlitePool,
+    inner_ip: &str,
+) -> Result<Vec<TcmQueryResult>, sqlx::Error> {
+    let sql = format!(
+        "select * from hosts JOIN deploy ON hosts.id = deploy.host_id \
+         JOIN procs ON procs.proc_group_name = deploy.group_name \
+         WHERE hosts.inner_ip = '{}' \
+         ORDER BY CAST(hosts.world_id AS INTEGER), CAST(hosts.zone_id AS INTEGER), deploy.group_name, procs.func_id, deploy.inst_id",
+        inner_ip
+    );
+    let query = sqlx::query(&sql).fetch_all(pool).await?;
+    query.iter().map(TcmQueryResult::from_row).collect()
+}
+
+/// 统计查询的一行: 分组名和数量
+#[derive(Debug, Clone, PartialEq)]
+pub struct CountRow {
+    pub label: String,
+    pub count: u64,
+}
+
+async fn select_counts(pool: &SqlitePool, sql: &str) -> Result<Vec<CountRow>, sqlx::Error> {
+    let rows = sqlx::query(sql).fetch_all(pool).await?;
+    rows.iter()
+        .map(|row| {
+            Ok(CountRow {
+                label: row.try_get("label")?,
+                count: row.try_get::<i64, _>("count")?.max(0) as u64,
+            })
+        })
+        .collect()
+}
+
+const DEPLOY_JOIN: &str = "FROM hosts JOIN deploy ON hosts.id = deploy.host_id \
+                           JOIN procs ON procs.proc_group_name = deploy.group_name";
+
+/// 每个 world.zone 下的进程实例数
+pub async fn count_world_zone_instances(pool: &SqlitePool) -> Result<Vec<CountRow>, sqlx::Error> {
+    let sql = format!(
+        "SELECT hosts.world_id || '.' || hosts.zone_id AS label, COUNT(*) AS count {} \
+         GROUP BY hosts.world_id, hosts.zone_id \
+         ORDER BY CAST(hosts.world_id AS INTEGER), CAST(hosts.zone_id AS INTEGER)",
+        DEPLOY_JOIN
+    );
+    select_counts(pool, &sql).await
+}
+
+/// 进程实例最多的 limit 台主机
+pub async fn count_host_instances(pool: &SqlitePool, limit: usize) -> Result<Vec<CountRow>, sqlx::Error> {
+    let sql = format!(
+        "SELECT hosts.inner_ip AS label, COUNT(*) AS count {} \
+         GROUP BY hosts.inner_ip ORDER BY count DESC, hosts.inner_ip LIMIT {}",
+        DEPLOY_JOIN, limit
+    );
+    select_counts(pool, &sql).await
+}
+
+/// 每个 FuncID 的进程实例数
+pub async fn count_func_instances(pool: &SqlitePool) -> Result<Vec<CountRow>, sqlx::Error> {
+    let sql = format!(
+        "SELECT procs.func_name || '(' || procs.func_id || ')' AS label, COUNT(*) AS count {} \
+         GROUP BY procs.func_id ORDER BY count DESC, procs.func_id",
+        DEPLOY_JOIN
+    );
+    select_counts(pool, &sql).await
+}
+
+/// 没有任何部署的主机
+pub async fn select_idle_hosts(pool: &SqlitePool) -> Result<Vec<HostInfo>, sqlx::Error> {
+    let sql = "SELECT DISTINCT inner_ip, host_name, world_id, zone_id FROM hosts \
+               WHERE inner_ip NOT IN (SELECT hosts.inner_ip FROM hosts JOIN deploy ON hosts.id = deploy.host_id) \
+               ORDER BY CAST(world_id AS INTEGER), CAST(zone_id AS INTEGER), inner_ip";
+    let query = sqlx::query(sql).fetch_all(pool).await?;
+    query.iter().map(HostInfo::from_row).collect()
+}
+
 impl<'r> sqlx::FromRow<'r, sqlx::sqlite::SqliteRow> for HostInfo {
     fn from_row(row: &'r sqlx::sqlite::SqliteRow) -> Result<Self, sqlx::Error> {
         let inner_ip = row.try_get("inner_ip")?;
@@ -180,6 +344,15 @@ impl<'r> sqlx::FromRow<'r, sqlx::sqlite::SqliteRow> for ProcInfo {
         let func_id = row.try_get("func_id")?;
         let work_path = row.try_get("work_path")?;
         let proc_name = row.try_get("proc_name")?;
+        let attributes = ProcAttributes {
+            flag: row.try_get("flag")?,
+            is_common: row.try_get("is_common")?,
+            config_path: row.try_get("config_path")?,
+            seq: row.try_get("seq")?,
+            auto_script: row.try_get("auto_script")?,
+            restart_cmd: row.try_get("restart_cmd")?,
+            args: row.try_get("args")?,
+        };
         Ok(Self {
             layer,
             funcname,
@@ -187,6 +360,7 @@ impl<'r> sqlx::FromRow<'r, sqlx::sqlite::SqliteRow> for ProcInfo {
             func_id,
             work_path,
             proc_name,
+            attributes,
         })
     }
 }
@@ -283,7 +457,7 @@ pub async fn query_hosts_sql(sql: &str, db: &SqlitePool) -> anyhow::Result<Vec<T
 }
 
 pub async fn get_host_id(
-    pool: &SqlitePool,
+    conn: &mut SqliteConnection,
     inner_ip: &str,
     world_id: &str,
     zone_id: &str,
@@ -292,7 +466,7 @@ pub async fn get_host_id(
         "select id from hosts WHERE inner_ip = '{}' and world_id = '{}' and zone_id = '{}';",
         inner_ip, world_id, zone_id
     );
-    let row = sqlx::query(&sql).fetch_one(pool).await?;
+    let row = sqlx::query(&sql).fetch_one(conn).await?;
     Ok(row.get(0))
 }
 
@@ -334,4 +508,58 @@ mod tests {
             "select * from hosts JOIN procs ON procs.proc_group_name = deploy.group_name and procs.func_id = '201' JOIN deploy ON hosts.zone_id = '200' WHERE deploy.inst_id = '1' and hosts.id = deploy.host_id"
         );
     }
+
+    #[tokio::test]
+    async fn test_count_instances() {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        for sql in return_init_sqls() {
+            sqlx::query(sql).execute(&pool).await.unwrap();
+        }
+        let inserts = [
+            "INSERT INTO hosts VALUES (1, '10.0.0.1', 'Main', '2', '70'), (2, '10.0.0.2', 'DB', '2', '70'), \
+             (3, '10.0.0.10', 'Main', '2', '71'), (4, '10.0.0.9', 'Idle', '2', '71')",
+            "INSERT INTO procs (func_id, proc_type, work_path, func_name, proc_name, proc_group_name, flag) VALUES \
+             (201, 'Zone', '/data', 'gamesvr', 'gamesvr', 'Game', '1'), \
+             (202, 'Zone', '/data', 'dbsvr', 'dbsvr', 'DB', '1')",
+            "INSERT INTO deploy (host_id, group_name, inst_id) VALUES \
+             (1, 'Game', 1), (1, 'Game', 2), (2, 'DB', 1), (3, 'Game', 1)",
+        ];
+        for sql in inserts {
+            sqlx::query(sql).execute(&pool).await.unwrap();
+        }
+
+        let count = |label: &str, count: u64| CountRow {
+            label: label.to_string(),
+            count,
+        };
+        assert_eq!(
+            count_world_zone_instances(&pool).await.unwrap(),
+            vec![count("2.70", 3), count("2.71", 1)]
+        );
+        assert_eq!(
+            count_host_instances(&pool, 2).await.unwrap(),
+            vec![count("10.0.0.1", 2), count("10.0.0.10", 1)]
+        );
+        assert_eq!(
+            count_func_instances(&pool).await.unwrap(),
+            vec![count("gamesvr(201)", 3), count("dbsvr(202)", 1)]
+        );
+        let idle = select_idle_hosts(&pool).await.unwrap();
+        assert_eq!(idle.len(), 1);
+        assert_eq!(idle[0].host_name, "Idle");
+    }
+
+    #[test]
+    fn test_sql_text() {
+        assert_eq!(sql_text(None), "NULL");
+        assert_eq!(sql_text(Some(&"cfg".to_string())), "'cfg'");
+        assert_eq!(
+            sql_text(Some(&"kill -9 '$pid'".to_string())),
+            "'kill -9 ''$pid'''"
+        );
+    }
 }
@@ -57,17 +57,17 @@ pub struct Proc {
     #[serde(rename = "@WorkPath")]
     pub work_path: Option<String>,
     #[serde(rename = "@Flag")]
-    flag: String,
+    pub flag: String,
     #[serde(rename = "@IsCommon")]
-    is_common: Option<String>,
+    pub is_common: Option<String>,
     #[serde(rename = "@ConfigPath")]
     pub config_path: Option<String>,
     #[serde(rename = "@Seq")]
-    seq: Option<String>,
+    pub seq: Option<String>,
     #[serde(rename = "@AutoScript")]
-    auto_script: Option<String>,
+    pub auto_script: Option<String>,
     #[serde(rename = "@ReStartCmd")]
-    restart_cmd: Option<String>,
+    pub restart_cmd: Option<String>,
 }
 
 #[derive(Debug, PartialEq, Default, Deserialize, Clone)]
@@ -83,15 +83,15 @@ pub struct WorldProc {
     #[serde(rename = "@Flag")]
     pub flag: String,
     #[serde(rename = "@IsCommon")]
-    is_common: Option<String>,
+    pub is_common: Option<String>,
     #[serde(rename = "@ConfigPath")]
-    config_path: Option<String>,
+    pub config_path: Option<String>,
     #[serde(rename = "@Seq")]
-    seq: Option<String>,
+    pub seq: Option<String>,
     #[serde(rename = "@AutoScript")]
-    auto_script: Option<String>,
+    pub auto_script: Option<String>,
     #[serde(rename = "@ReStartCmd")]
-    restart_cmd: Option<String>,
+    pub restart_cmd: Option<String>,
 }
 
 // TODO: 这里的序列应该是无序的
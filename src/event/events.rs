@@ -1,6 +1,8 @@
 use crate::event::Key;
-use crossterm::event;
-use std::{sync::mpsc, thread, time::Duration};
+use crossterm::event::{self, MouseEvent, MouseEventKind};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex, MutexGuard};
+use std::{thread, time::Duration};
 
 #[derive(Debug, Clone, Copy)]
 pub struct EventConfig {
@@ -20,12 +22,29 @@ impl Default for EventConfig {
 #[derive(Copy, Clone)]
 pub enum Event<I> {
     Input(I),
+    Mouse(MouseEvent),
+    Resize,
     Tick,
 }
 
 pub struct Events {
     rx: mpsc::Receiver<Event<Key>>,
     _tx: mpsc::Sender<Event<Key>>,
+    /// 读取终端输入时持有, 暂停时由主线程拿走
+    input: Arc<Mutex<()>>,
+    suspended: Arc<AtomicBool>,
+}
+
+/// 暂停期间输入线程不读取终端, 外部程序 (编辑器) 可以独占标准输入. drop 时恢复
+pub struct Suspended<'a> {
+    _input: MutexGuard<'a, ()>,
+    suspended: &'a AtomicBool,
+}
+
+impl Drop for Suspended<'_> {
+    fn drop(&mut self) {
+        self.suspended.store(false, Ordering::SeqCst);
+    }
 }
 
 impl Events {
@@ -40,19 +59,52 @@ impl Events {
         let (tx, rx) = mpsc::channel();
 
         let event_tx = tx.clone();
+        let input = Arc::new(Mutex::new(()));
+        let suspended = Arc::new(AtomicBool::new(false));
+        let (thread_input, thread_suspended) = (input.clone(), suspended.clone());
         thread::spawn(move || loop {
+            if thread_suspended.load(Ordering::SeqCst) {
+                thread::sleep(config.tick_rate);
+                continue;
+            }
+            let _input = thread_input.lock().unwrap();
+            if thread_suspended.load(Ordering::SeqCst) {
+                continue;
+            }
             if event::poll(config.tick_rate).unwrap() {
-                if let event::Event::Key(key) = event::read().unwrap() {
-                    let key = Key::from(key);
+                match event::read().unwrap() {
+                    event::Event::Key(key) => {
+                        let key = Key::from(key);
 
-                    event_tx.send(Event::Input(key)).unwrap();
+                        event_tx.send(Event::Input(key)).unwrap();
+                    }
+                    // 鼠标移动事件太多, 没有组件需要, 直接丢掉
+                    event::Event::Mouse(mouse) if mouse.kind != MouseEventKind::Moved => {
+                        event_tx.send(Event::Mouse(mouse)).unwrap();
+                    }
+                    event::Event::Resize(_, _) => event_tx.send(Event::Resize).unwrap(),
+                    _ => {}
                 }
             }
 
             event_tx.send(Event::Tick).unwrap();
         });
 
-        Events { rx, _tx: tx }
+        Events {
+            rx,
+            _tx: tx,
+            input,
+            suspended,
+        }
+    }
+
+    /// 等待输入线程读完当前这一轮后暂停, 最多等待一个 tick
+    pub fn suspend(&self) -> Suspended<'_> {
+        self.suspended.store(true, Ordering::SeqCst);
+        Suspended {
+            _input: self.input.lock().unwrap(),
+            suspended: &self.suspended,
+        }
     }
 
     pub fn next(&self) -> Result<Event<Key>, mpsc::RecvError> {
@@ -1,12 +1,13 @@
 use crossterm::event;
-use serde::Deserialize;
+use serde::{Deserialize, Deserializer};
 use std::fmt;
+use std::str::FromStr;
 
 #[cfg(test)]
 use serde::Serialize;
 
 /// Represents a key.
-#[derive(PartialEq, Eq, Clone, Copy, Hash, Debug, Deserialize)]
+#[derive(PartialEq, Eq, Clone, Copy, Hash, Debug)]
 #[cfg_attr(test, derive(Serialize))]
 pub enum Key {
     /// Both Enter (or Return) and numpad Enter
@@ -128,6 +129,78 @@ impl fmt::Display for Key {
     }
 }
 
+/// Parses the same notation `Display` produces, e.g. `j`, `<Ctrl+d>`,
+/// `<Enter>`, plus bare names such as `Ctrl-d`, `Tab` or `F5`.
+impl FromStr for Key {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let raw = s.trim();
+        let inner = raw
+            .strip_prefix('<')
+            .and_then(|k| k.strip_suffix('>'))
+            .unwrap_or(raw);
+
+        let mut chars = inner.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            return Ok(match c {
+                '\u{2190}' => Key::Left,
+                '\u{2192}' => Key::Right,
+                '\u{2191}' => Key::Up,
+                '\u{2193}' => Key::Down,
+                c => Key::Char(c),
+            });
+        }
+
+        if let Some((modifier, rest)) = inner.split_once(['+', '-']) {
+            let mut chars = rest.chars();
+            let c = match (chars.next(), chars.next()) {
+                (Some(c), None) => Some(c),
+                _ if rest.eq_ignore_ascii_case("space") => Some(' '),
+                _ => None,
+            };
+            match (modifier.to_ascii_lowercase().as_str(), c) {
+                ("ctrl", Some(c)) => return Ok(Key::Ctrl(c)),
+                ("alt", Some(c)) => return Ok(Key::Alt(c)),
+                _ => {}
+            }
+        }
+
+        let key = match inner.to_ascii_lowercase().as_str() {
+            "enter" => Key::Enter,
+            "tab" => Key::Tab,
+            "backspace" => Key::Backspace,
+            "esc" => Key::Esc,
+            "space" => Key::Char(' '),
+            "left" => Key::Left,
+            "right" => Key::Right,
+            "up" => Key::Up,
+            "down" => Key::Down,
+            "ins" | "insert" => Key::Ins,
+            "delete" => Key::Delete,
+            "home" => Key::Home,
+            "end" => Key::End,
+            "pageup" => Key::PageUp,
+            "pagedown" => Key::PageDown,
+            f => match f.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                Some(n) if n <= 12 => Key::from_f(n),
+                _ => return Err(format!("unknown key: {}", s)),
+            },
+        };
+        Ok(key)
+    }
+}
+
+impl<'de> Deserialize<'de> for Key {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
 impl From<event::KeyEvent> for Key {
     fn from(key_event: event::KeyEvent) -> Self {
         match key_event {
@@ -193,6 +266,16 @@ impl From<event::KeyEvent> for Key {
             } => Key::Tab,
 
             // First check for char + modifier
+            event::KeyEvent {
+                code: event::KeyCode::Char(c),
+                modifiers: event::KeyModifiers::CONTROL,
+                ..
+            } => Key::Ctrl(c),
+            event::KeyEvent {
+                code: event::KeyCode::Char(c),
+                modifiers: event::KeyModifiers::ALT,
+                ..
+            } => Key::Alt(c),
 
             event::KeyEvent {
                 code: event::KeyCode::Char(c),
@@ -203,3 +286,29 @@ impl From<event::KeyEvent> for Key {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_key() {
+        assert_eq!("j".parse(), Ok(Key::Char('j')));
+        assert_eq!("G".parse(), Ok(Key::Char('G')));
+        assert_eq!("<Ctrl+d>".parse(), Ok(Key::Ctrl('d')));
+        assert_eq!("ctrl-u".parse(), Ok(Key::Ctrl('u')));
+        assert_eq!("Alt+Space".parse(), Ok(Key::Alt(' ')));
+        assert_eq!("<Enter>".parse(), Ok(Key::Enter));
+        assert_eq!("PageDown".parse(), Ok(Key::PageDown));
+        assert_eq!("F5".parse(), Ok(Key::F5));
+        assert_eq!("\u{2191}".parse(), Ok(Key::Up));
+        assert!("Hyper+x".parse::<Key>().is_err());
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        for key in [Key::Char('q'), Key::Ctrl('c'), Key::Tab, Key::Esc, Key::Down, Key::End] {
+            assert_eq!(key.to_string().parse(), Ok(key));
+        }
+    }
+}
@@ -1,35 +1,31 @@
 use clap::Parser;
-use quick_xml::de::from_str;
 mod app;
+mod clipboard;
 mod components;
 mod config;
 mod database;
 mod description;
 mod event;
+mod theme;
 mod tools;
 pub mod ui;
 mod utils;
 #[macro_use]
 extern crate lazy_static;
 use app::start_app;
+use config::Config;
+use theme::Theme;
 use database::init_sqlx_table;
 use hashbrown::HashMap;
+use std::sync::RwLock;
 use tracing::{error, level_filters::LevelFilter};
 use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, Layer};
 use utils::{drop_app, init_data};
 lazy_static! {
-    static ref HOST_HASHMAP: HashMap<String, String> = {
+    /// 重新导入配置时整个替换
+    static ref HOST_HASHMAP: RwLock<HashMap<String, String>> = {
         let args = utils::Args::parse();
-        let mut m = HashMap::new();
-        let xml = args.config_path.join("host.xml");
-        let host_xml: String = std::fs::read_to_string(xml).unwrap();
-        let host_xml_content: description::host::HostTcmCenter = from_str(&host_xml).unwrap();
-        for host_info in host_xml_content.host_tab.hosts {
-            // TODO: 这里 IP 应该是唯一的 对应多个 Name
-            m.insert(host_info.name, host_info.inner_ip);
-        }
-        m.insert("TcmHost".to_string(), "127.0.0.1".to_string());
-        m
+        RwLock::new(utils::load_host_map(&args.config_path).unwrap())
     };
 }
 
@@ -43,14 +39,28 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             LevelFilter::INFO
         }))
         .init();
+    let config = match Config::load(args.user_config) {
+        Ok(config) => config,
+        Err(e) => {
+            error!("Load user config failed, error ->[{}]", e);
+            std::process::exit(1);
+        }
+    };
+    let theme = match Theme::from_config(&config) {
+        Ok(theme) => theme,
+        Err(e) => {
+            error!("Load theme failed, error ->[{}]", e);
+            std::process::exit(1);
+        }
+    };
     let db = init_sqlx_table().await?;
-    if let Err(e) = init_data(&db, args.config_path).await {
+    if let Err(e) = init_data(&db, args.config_path.clone()).await {
         error!("Init data filed, error ->[{}]", e);
         drop_app();
     }
 
     // UI
-    start_app(&db).await?;
+    start_app(&db, config, theme, args.config_path).await?;
 
     // drop resouce
     Ok(())
@@ -0,0 +1,151 @@
+use anyhow::anyhow;
+use once_cell::sync::Lazy;
+use ratatui::style::{palette::tailwind, Color};
+use serde::Deserialize;
+use std::path::Path;
+use std::sync::Arc;
+use strum::{Display, EnumIter, IntoEnumIterator};
+use syntect::highlighting::{Theme as SyntectTheme, ThemeSet};
+
+use crate::config::Config;
+
+static THEME_SET: Lazy<ThemeSet> = Lazy::new(ThemeSet::load_defaults);
+
+/// 表格高亮色可以用 switch_palette 键 (默认 c) 在这几组之间切换
+const PALETTES: [tailwind::Palette; 4] = [
+    tailwind::BLUE,
+    tailwind::EMERALD,
+    tailwind::INDIGO,
+    tailwind::RED,
+];
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Display, EnumIter, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum ThemeName {
+    #[default]
+    Dark,
+    Light,
+    /// 不输出任何颜色, 对应 NO_COLOR
+    Mono,
+}
+
+#[derive(Clone)]
+pub struct TableColors {
+    pub buffer_bg: Color,
+    pub header_bg: Color,
+    pub header_fg: Color,
+    pub row_fg: Color,
+    pub selected_style_fg: Color,
+    pub normal_row_color: Color,
+    pub alt_row_color: Color,
+    pub footer_border_color: Color,
+}
+
+#[derive(Clone, Default)]
+pub struct Theme {
+    pub name: ThemeName,
+    /// 用户指定的 .tmTheme, 优先于内置的语法高亮主题
+    custom_syntax: Option<Arc<SyntectTheme>>,
+}
+
+impl Theme {
+    /// 设置了 NO_COLOR 环境变量时强制使用 mono
+    pub fn from_config(config: &Config) -> anyhow::Result<Self> {
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+        let custom_syntax = match &config.syntax_theme {
+            Some(path) => Some(Arc::new(load_tm_theme(path)?)),
+            None => None,
+        };
+        Ok(Self {
+            name: if no_color {
+                ThemeName::Mono
+            } else {
+                config.theme
+            },
+            custom_syntax,
+        })
+    }
+
+    pub fn next(&self) -> Self {
+        let names = ThemeName::iter().collect::<Vec<_>>();
+        let index = names.iter().position(|n| *n == self.name).unwrap_or(0);
+        Self {
+            name: names[(index + 1) % names.len()],
+            custom_syntax: self.custom_syntax.clone(),
+        }
+    }
+
+    pub fn is_mono(&self) -> bool {
+        self.name == ThemeName::Mono
+    }
+
+    pub fn palette_count(&self) -> usize {
+        PALETTES.len()
+    }
+
+    pub fn table_colors(&self, palette_index: usize) -> TableColors {
+        let color = &PALETTES[palette_index % PALETTES.len()];
+        match self.name {
+            ThemeName::Dark => TableColors {
+                buffer_bg: tailwind::SLATE.c950,
+                header_bg: color.c900,
+                header_fg: tailwind::SLATE.c200,
+                row_fg: tailwind::SLATE.c200,
+                selected_style_fg: color.c400,
+                normal_row_color: tailwind::SLATE.c950,
+                alt_row_color: tailwind::SLATE.c900,
+                footer_border_color: color.c400,
+            },
+            ThemeName::Light => TableColors {
+                buffer_bg: tailwind::SLATE.c50,
+                header_bg: color.c200,
+                header_fg: tailwind::SLATE.c900,
+                row_fg: tailwind::SLATE.c900,
+                selected_style_fg: color.c700,
+                normal_row_color: tailwind::SLATE.c50,
+                alt_row_color: tailwind::SLATE.c200,
+                footer_border_color: color.c600,
+            },
+            ThemeName::Mono => TableColors {
+                buffer_bg: Color::Reset,
+                header_bg: Color::Reset,
+                header_fg: Color::Reset,
+                row_fg: Color::Reset,
+                selected_style_fg: Color::Reset,
+                normal_row_color: Color::Reset,
+                alt_row_color: Color::Reset,
+                footer_border_color: Color::Reset,
+            },
+        }
+    }
+
+    /// 文件查看使用的语法高亮主题, mono 模式下不高亮
+    pub fn syntax_theme(&self) -> Option<&SyntectTheme> {
+        match (self.name, &self.custom_syntax) {
+            (ThemeName::Mono, _) => None,
+            (_, Some(custom)) => Some(custom),
+            (ThemeName::Dark, None) => Some(&THEME_SET.themes["base16-eighties.dark"]),
+            (ThemeName::Light, None) => Some(&THEME_SET.themes["InspiredGitHub"]),
+        }
+    }
+
+    /// 标签页的配色, mono 模式下返回 None
+    pub fn tab_palette<'a>(&self, palette: &'a tailwind::Palette) -> Option<&'a tailwind::Palette> {
+        (!self.is_mono()).then_some(palette)
+    }
+
+    /// 除表格之外的强调色, 例如文件查看的滚动条
+    pub fn accent(&self, color: Color) -> Color {
+        if self.is_mono() {
+            Color::Reset
+        } else {
+            color
+        }
+    }
+}
+
+fn load_tm_theme(path: &Path) -> anyhow::Result<SyntectTheme> {
+    ThemeSet::get_theme(path)
+        .map_err(|e| anyhow!("Load syntax theme -> [{}] failed: {}", path.display(), e))
+}
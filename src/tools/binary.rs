@@ -0,0 +1,310 @@
+use sha2::{Digest, Sha256};
+use std::fmt::Write as _;
+use std::io::{Read, Seek, SeekFrom};
+
+/// 十六进制视图每行的字节数
+pub const HEX_WIDTH: usize = 16;
+
+/// 一行十六进制视图: 偏移, 十六进制 (每 8 个字节隔开) 和可打印的 ASCII
+pub fn hex_row(offset: u64, bytes: &[u8]) -> (String, String, String) {
+    let mut hex = String::with_capacity(HEX_WIDTH * 3 + 1);
+    for i in 0..HEX_WIDTH {
+        if i == HEX_WIDTH / 2 {
+            hex.push(' ');
+        }
+        match bytes.get(i) {
+            Some(b) => {
+                let _ = write!(hex, "{:02x} ", b);
+            }
+            None => hex.push_str("   "),
+        }
+    }
+    let ascii = bytes
+        .iter()
+        .map(|b| if b.is_ascii_graphic() || *b == b' ' { *b as char } else { '.' })
+        .collect();
+    (format!("{:08x}", offset), hex, ascii)
+}
+
+/// ELF 头中用来确认部署版本的信息
+#[derive(Debug, Clone, PartialEq)]
+pub struct ElfInfo {
+    /// 32 或 64
+    pub class: u8,
+    pub kind: String,
+    pub machine: String,
+    pub build_id: Option<String>,
+}
+
+impl std::fmt::Display for ElfInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ELF{} {} {}", self.class, self.kind, self.machine)
+    }
+}
+
+/// 不是 ELF 文件, 或者段表超出文件范围时返回 None.
+/// build-id 从 SHT_NOTE 段中的 NT_GNU_BUILD_ID 读取
+pub fn read_elf<R: Read + Seek>(reader: &mut R) -> std::io::Result<Option<ElfInfo>> {
+    let file_len = reader.seek(SeekFrom::End(0))?;
+    let mut header = [0u8; 64];
+    reader.seek(SeekFrom::Start(0))?;
+    let len = read_full(reader, &mut header)?;
+    if len < 52 || header[..4] != *b"\x7fELF" {
+        return Ok(None);
+    }
+    let is_64 = header[4] == 2;
+    if is_64 && len < 64 {
+        return Ok(None);
+    }
+    let le = header[5] != 2;
+    let u16_at = |b: &[u8], at: usize| {
+        let bytes = [b[at], b[at + 1]];
+        if le { u16::from_le_bytes(bytes) } else { u16::from_be_bytes(bytes) }
+    };
+    let u32_at = |b: &[u8], at: usize| {
+        let bytes = [b[at], b[at + 1], b[at + 2], b[at + 3]];
+        if le { u32::from_le_bytes(bytes) } else { u32::from_be_bytes(bytes) }
+    };
+    let u64_at = |b: &[u8], at: usize| {
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&b[at..at + 8]);
+        if le { u64::from_le_bytes(bytes) } else { u64::from_be_bytes(bytes) }
+    };
+    // 32 位和 64 位的字段偏移不同
+    let addr_at = |b: &[u8], at32: usize, at64: usize| {
+        if is_64 { u64_at(b, at64) } else { u64::from(u32_at(b, at32)) }
+    };
+
+    let kind = match u16_at(&header, 16) {
+        1 => "REL".to_string(),
+        2 => "EXEC".to_string(),
+        3 => "DYN".to_string(),
+        4 => "CORE".to_string(),
+        other => format!("type {:#x}", other),
+    };
+    let machine = match u16_at(&header, 18) {
+        3 => "x86".to_string(),
+        8 => "MIPS".to_string(),
+        20 => "PowerPC".to_string(),
+        40 => "ARM".to_string(),
+        62 => "x86-64".to_string(),
+        183 => "AArch64".to_string(),
+        243 => "RISC-V".to_string(),
+        other => format!("machine {}", other),
+    };
+
+    let shoff = addr_at(&header, 0x20, 0x28);
+    let (shentsize, shnum) = if is_64 {
+        (u16_at(&header, 0x3A), u16_at(&header, 0x3C))
+    } else {
+        (u16_at(&header, 0x2E), u16_at(&header, 0x30))
+    };
+    // 段表的位置和大小来自文件头, 不可信
+    let table_size = u64::from(shentsize) * u64::from(shnum);
+    if shoff.checked_add(table_size).is_none_or(|end| end > file_len) {
+        return Ok(None);
+    }
+    let mut build_id = None;
+    if shoff > 0 && usize::from(shentsize) >= if is_64 { 0x28 } else { 0x20 } {
+        let mut sections = vec![0u8; table_size as usize];
+        reader.seek(SeekFrom::Start(shoff))?;
+        let len = read_full(reader, &mut sections)?;
+        for section in sections[..len].chunks_exact(usize::from(shentsize)) {
+            // SHT_NOTE
+            if u32_at(section, 4) != 7 {
+                continue;
+            }
+            let offset = addr_at(section, 0x10, 0x18);
+            if offset >= file_len {
+                continue;
+            }
+            let size = addr_at(section, 0x14, 0x20).min(file_len - offset).min(1 << 16) as usize;
+            let mut notes = vec![0u8; size];
+            reader.seek(SeekFrom::Start(offset))?;
+            let len = read_full(reader, &mut notes)?;
+            build_id = find_build_id(&notes[..len], u32_at);
+            if build_id.is_some() {
+                break;
+            }
+        }
+    }
+
+    Ok(Some(ElfInfo {
+        class: if is_64 { 64 } else { 32 },
+        kind,
+        machine,
+        build_id,
+    }))
+}
+
+/// note 的格式: namesz, descsz, type, name 和 desc 都按 4 字节对齐
+fn find_build_id(notes: &[u8], u32_at: impl Fn(&[u8], usize) -> u32) -> Option<String> {
+    let align = |n: usize| (n + 3) & !3;
+    let mut at = 0;
+    while at + 12 <= notes.len() {
+        let name_size = u32_at(notes, at) as usize;
+        let desc_size = u32_at(notes, at + 4) as usize;
+        let note_type = u32_at(notes, at + 8);
+        let name_at = at + 12;
+        let desc_at = name_at + align(name_size);
+        let end = desc_at + align(desc_size);
+        if desc_at + desc_size > notes.len() {
+            return None;
+        }
+        // NT_GNU_BUILD_ID
+        if note_type == 3 && notes.get(name_at..name_at + name_size) == Some(b"GNU\0") {
+            let desc = &notes[desc_at..desc_at + desc_size];
+            return Some(desc.iter().map(|b| format!("{:02x}", b)).collect());
+        }
+        at = end;
+    }
+    None
+}
+
+/// 读满 buf 或者读到文件末尾, 返回读到的字节数
+fn read_full<R: Read>(reader: &mut R, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut len = 0;
+    while len < buf.len() {
+        match reader.read(&mut buf[len..])? {
+            0 => break,
+            n => len += n,
+        }
+    }
+    Ok(len)
+}
+
+/// 和 sha256sum 的输出相同
+pub fn sha256<R: Read>(reader: &mut R) -> std::io::Result<String> {
+    let mut hasher = Sha256::new();
+    std::io::copy(reader, &mut hasher)?;
+    Ok(hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// 例如 1.5 MiB (1572864)
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {} ({})", size, UNITS[unit], bytes)
+}
+
+/// Unix 时间戳转换成 UTC 时间, 不依赖时区数据库
+pub fn format_utc(secs: u64) -> String {
+    let days = (secs / 86400) as i64;
+    let rest = secs % 86400;
+    // 按 0000-03-01 开始的 400 年周期换算年月日
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        rest / 3600,
+        rest % 3600 / 60,
+        rest % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_hex_row() {
+        let (offset, hex, ascii) = hex_row(0x10, b"\x7fELF\x02\x01 ab");
+        assert_eq!(offset, "00000010");
+        assert_eq!(hex, format!("7f 45 4c 46 02 01 20 61  62 {}", " ".repeat(21)));
+        assert_eq!(ascii, ".ELF.. ab");
+    }
+
+    /// 最小的 64 位小端 ELF: 文件头, 一个 note 段和两个段头
+    fn elf64(build_id: &[u8]) -> Vec<u8> {
+        let mut data = vec![0u8; 64];
+        data[..4].copy_from_slice(b"\x7fELF");
+        data[4] = 2;
+        data[5] = 1;
+        data[16..18].copy_from_slice(&3u16.to_le_bytes());
+        data[18..20].copy_from_slice(&62u16.to_le_bytes());
+
+        let note_offset = data.len() as u64;
+        data.extend(4u32.to_le_bytes());
+        data.extend((build_id.len() as u32).to_le_bytes());
+        data.extend(3u32.to_le_bytes());
+        data.extend(b"GNU\0");
+        data.extend(build_id);
+        let note_size = data.len() as u64 - note_offset;
+
+        let shoff = data.len() as u64;
+        data.extend([0u8; 0x40]);
+        let mut note = [0u8; 0x40];
+        note[4..8].copy_from_slice(&7u32.to_le_bytes());
+        note[0x18..0x20].copy_from_slice(&note_offset.to_le_bytes());
+        note[0x20..0x28].copy_from_slice(&note_size.to_le_bytes());
+        data.extend(note);
+
+        data[0x28..0x30].copy_from_slice(&shoff.to_le_bytes());
+        data[0x3A..0x3C].copy_from_slice(&0x40u16.to_le_bytes());
+        data[0x3C..0x3E].copy_from_slice(&2u16.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn test_read_elf() {
+        let info = read_elf(&mut Cursor::new(elf64(&[0xde, 0xad, 0xbe, 0xef])))
+            .unwrap()
+            .unwrap();
+        assert_eq!(info.to_string(), "ELF64 DYN x86-64");
+        assert_eq!(info.build_id.as_deref(), Some("deadbeef"));
+        assert_eq!(read_elf(&mut Cursor::new(b"#!/bin/sh\n".to_vec())).unwrap(), None);
+    }
+
+    #[test]
+    fn test_read_elf_bogus_header() {
+        // 文件被截断, 段表不完整
+        let data = elf64(&[0xde, 0xad]);
+        let truncated = data[..data.len() - 1].to_vec();
+        assert_eq!(read_elf(&mut Cursor::new(truncated)).unwrap(), None);
+
+        // 段表的偏移和数量都是最大值
+        let mut bogus = data.clone();
+        bogus[0x28..0x30].copy_from_slice(&u64::MAX.to_le_bytes());
+        bogus[0x3A..0x3C].copy_from_slice(&u16::MAX.to_le_bytes());
+        bogus[0x3C..0x3E].copy_from_slice(&u16::MAX.to_le_bytes());
+        assert_eq!(read_elf(&mut Cursor::new(bogus)).unwrap(), None);
+
+        // note 段的偏移超出文件
+        let mut note = data;
+        let at = note.len() - 0x40 + 0x18;
+        note[at..at + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+        let info = read_elf(&mut Cursor::new(note)).unwrap().unwrap();
+        assert_eq!(info.build_id, None);
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(
+            sha256(&mut Cursor::new(b"abc")).unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536 * 1024), "1.5 MiB (1572864)");
+        assert_eq!(format_utc(0), "1970-01-01 00:00:00 UTC");
+        assert_eq!(format_utc(951_782_400 + 3661), "2000-02-29 01:01:01 UTC");
+    }
+}
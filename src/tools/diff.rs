@@ -0,0 +1,170 @@
+use similar::{capture_diff_slices, group_diff_ops, Algorithm};
+use std::ops::Range;
+
+pub use similar::DiffTag;
+
+/// 差异中的一行, old/new 是两个文件中的行号 (从 0 开始).
+/// 统一视图中只有 Equal/Delete/Insert, 并排视图中修改的行配成一对时为 Replace
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DiffLine {
+    pub tag: DiffTag,
+    pub old: Option<usize>,
+    pub new: Option<usize>,
+}
+
+/// 一段连续的修改和前后的上下文
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hunk {
+    pub old: Range<usize>,
+    pub new: Range<usize>,
+    pub lines: Vec<DiffLine>,
+}
+
+impl Hunk {
+    /// 和 diff -u 一样, 行号从 1 开始
+    pub fn header(&self) -> String {
+        format!(
+            "@@ -{},{} +{},{} @@",
+            self.old.start + 1,
+            self.old.len(),
+            self.new.start + 1,
+            self.new.len()
+        )
+    }
+
+    /// 并排显示: 删除和紧跟着的插入按顺序配对, 多出来的一边留空
+    pub fn side_by_side(&self) -> Vec<DiffLine> {
+        let mut rows = Vec::with_capacity(self.lines.len());
+        let mut deleted: Vec<usize> = Vec::new();
+        let mut inserted: Vec<usize> = Vec::new();
+        let flush = |rows: &mut Vec<DiffLine>, deleted: &mut Vec<usize>, inserted: &mut Vec<usize>| {
+            for i in 0..deleted.len().max(inserted.len()) {
+                let (old, new) = (deleted.get(i).copied(), inserted.get(i).copied());
+                let tag = match (old, new) {
+                    (Some(_), Some(_)) => DiffTag::Replace,
+                    (Some(_), None) => DiffTag::Delete,
+                    _ => DiffTag::Insert,
+                };
+                rows.push(DiffLine { tag, old, new });
+            }
+            deleted.clear();
+            inserted.clear();
+        };
+        for line in &self.lines {
+            match line.tag {
+                DiffTag::Delete => {
+                    if !inserted.is_empty() {
+                        flush(&mut rows, &mut deleted, &mut inserted);
+                    }
+                    deleted.extend(line.old);
+                }
+                DiffTag::Insert => inserted.extend(line.new),
+                _ => {
+                    flush(&mut rows, &mut deleted, &mut inserted);
+                    rows.push(*line);
+                }
+            }
+        }
+        flush(&mut rows, &mut deleted, &mut inserted);
+        rows
+    }
+}
+
+/// 按行比较两个文件, 每段修改前后保留 context 行上下文. 行的切分和 str::lines 一致
+pub fn diff_hunks(old: &str, new: &str, context: usize) -> Vec<Hunk> {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let ops = capture_diff_slices(Algorithm::Myers, &old_lines, &new_lines);
+    group_diff_ops(ops, context)
+        .into_iter()
+        .filter_map(|group| {
+            let (first, last) = (group.first()?, group.last()?);
+            let mut lines = Vec::new();
+            for op in &group {
+                let (tag, old_range, new_range) = op.as_tag_tuple();
+                if tag == DiffTag::Equal {
+                    lines.extend(old_range.zip(new_range).map(|(old, new)| DiffLine {
+                        tag,
+                        old: Some(old),
+                        new: Some(new),
+                    }));
+                    continue;
+                }
+                lines.extend(old_range.map(|old| DiffLine {
+                    tag: DiffTag::Delete,
+                    old: Some(old),
+                    new: None,
+                }));
+                lines.extend(new_range.map(|new| DiffLine {
+                    tag: DiffTag::Insert,
+                    old: None,
+                    new: Some(new),
+                }));
+            }
+            Some(Hunk {
+                old: first.old_range().start..last.old_range().end,
+                new: first.new_range().start..last.new_range().end,
+                lines,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(tag: DiffTag, old: Option<usize>, new: Option<usize>) -> DiffLine {
+        DiffLine { tag, old, new }
+    }
+
+    #[test]
+    fn test_diff_hunks() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\n";
+        let new = "a\nB\nc\nd\ne\nf\ng\nh\ni\nj\n";
+        let hunks = diff_hunks(old, new, 1);
+        assert_eq!(hunks.len(), 2);
+        assert_eq!(hunks[0].header(), "@@ -1,3 +1,3 @@");
+        assert_eq!(
+            hunks[0].lines,
+            vec![
+                line(DiffTag::Equal, Some(0), Some(0)),
+                line(DiffTag::Delete, Some(1), None),
+                line(DiffTag::Insert, None, Some(1)),
+                line(DiffTag::Equal, Some(2), Some(2)),
+            ]
+        );
+        assert_eq!(hunks[1].header(), "@@ -9,1 +9,2 @@");
+        assert_eq!(hunks[1].lines.last(), Some(&line(DiffTag::Insert, None, Some(9))));
+
+        assert!(diff_hunks(old, old, 3).is_empty());
+        // \r\n 和 \n 结尾的同一行相同
+        assert!(diff_hunks("a\r\nb", "a\nb\n", 3).is_empty());
+    }
+
+    #[test]
+    fn test_side_by_side() {
+        let hunk = Hunk {
+            old: 0..4,
+            new: 0..3,
+            lines: vec![
+                line(DiffTag::Equal, Some(0), Some(0)),
+                line(DiffTag::Delete, Some(1), None),
+                line(DiffTag::Delete, Some(2), None),
+                line(DiffTag::Insert, None, Some(1)),
+                line(DiffTag::Equal, Some(3), Some(2)),
+                line(DiffTag::Insert, None, Some(3)),
+            ],
+        };
+        assert_eq!(
+            hunk.side_by_side(),
+            vec![
+                line(DiffTag::Equal, Some(0), Some(0)),
+                line(DiffTag::Replace, Some(1), Some(1)),
+                line(DiffTag::Delete, Some(2), None),
+                line(DiffTag::Equal, Some(3), Some(2)),
+                line(DiffTag::Insert, None, Some(3)),
+            ]
+        );
+    }
+}
@@ -0,0 +1,65 @@
+use std::ffi::OsString;
+use std::path::Path;
+
+/// 没有设置 $VISUAL 和 $EDITOR 时使用
+const DEFAULT_EDITOR: &str = "vi";
+
+/// 依次读取 $VISUAL 和 $EDITOR, 值可以带参数, 例如 "code -w"
+pub fn editor_from_env() -> String {
+    ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_EDITOR.to_string())
+}
+
+/// 拼出打开 path 并跳到 line 行 (从 1 开始) 的命令. 大多数编辑器 (vi, nano, emacs...)
+/// 支持 +行号, VS Code 和 helix 用 path:行号
+pub fn editor_command(editor: &str, path: &Path, line: usize) -> Option<(String, Vec<OsString>)> {
+    let mut words = editor.split_whitespace();
+    let program = words.next()?.to_string();
+    let mut args: Vec<OsString> = words.map(OsString::from).collect();
+    let name = Path::new(&program)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let with_line = || {
+        let mut arg = path.as_os_str().to_owned();
+        arg.push(format!(":{}", line));
+        arg
+    };
+    match name.as_str() {
+        "code" | "codium" => {
+            args.push("-g".into());
+            args.push(with_line());
+        }
+        "hx" | "helix" => args.push(with_line()),
+        _ => {
+            args.push(format!("+{}", line).into());
+            args.push(path.into());
+        }
+    }
+    Some((program, args))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_editor_command() {
+        let path = Path::new("/data/proc.xml");
+        let (program, args) = editor_command("vim", path, 12).unwrap();
+        assert_eq!(program, "vim");
+        assert_eq!(args, ["+12", "/data/proc.xml"]);
+
+        let (program, args) = editor_command("/usr/bin/code -w", path, 3).unwrap();
+        assert_eq!(program, "/usr/bin/code");
+        assert_eq!(args, ["-w", "-g", "/data/proc.xml:3"]);
+
+        let (_, args) = editor_command("hx", path, 1).unwrap();
+        assert_eq!(args, ["/data/proc.xml:1"]);
+
+        assert!(editor_command("  ", path, 1).is_none());
+    }
+}
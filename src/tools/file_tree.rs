@@ -0,0 +1,146 @@
+use crate::components::columns::natural_cmp;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileNode {
+    pub path: PathBuf,
+    pub name: String,
+    pub depth: usize,
+    pub is_dir: bool,
+    pub expanded: bool,
+}
+
+impl FileNode {
+    fn new(path: PathBuf, name: String, depth: usize) -> Self {
+        let is_dir = path.is_dir();
+        Self {
+            path,
+            name,
+            depth,
+            is_dir,
+            expanded: false,
+        }
+    }
+}
+
+/// 进程目录的文件树, 目录在展开时才读取. 节点按先序平铺存放,
+/// 展开时把子节点插到目录后面, 折叠时删掉后面更深的节点
+#[derive(Debug, Clone, Default)]
+pub struct FileTree {
+    nodes: Vec<FileNode>,
+}
+
+impl FileTree {
+    /// roots 是 (名称, 目录), 根目录默认展开, 不存在的目录也会列出来
+    pub fn new(roots: Vec<(String, PathBuf)>) -> std::io::Result<Self> {
+        let mut tree = Self {
+            nodes: roots
+                .into_iter()
+                .map(|(name, path)| FileNode::new(path, name, 0))
+                .collect(),
+        };
+        for index in (0..tree.nodes.len()).rev() {
+            if tree.nodes[index].is_dir {
+                tree.expand(index)?;
+            }
+        }
+        Ok(tree)
+    }
+
+    pub fn nodes(&self) -> &[FileNode] {
+        &self.nodes
+    }
+
+    pub fn node(&self, index: usize) -> Option<&FileNode> {
+        self.nodes.get(index)
+    }
+
+    /// 子树结束的位置 (不含)
+    fn subtree_end(&self, index: usize) -> usize {
+        let depth = self.nodes[index].depth;
+        self.nodes[index + 1..]
+            .iter()
+            .position(|node| node.depth <= depth)
+            .map_or(self.nodes.len(), |i| index + 1 + i)
+    }
+
+    pub fn expand(&mut self, index: usize) -> std::io::Result<()> {
+        let Some(node) = self.nodes.get(index) else {
+            return Ok(());
+        };
+        if !node.is_dir || node.expanded {
+            return Ok(());
+        }
+        let children = read_children(&node.path, node.depth + 1)?;
+        self.nodes[index].expanded = true;
+        self.nodes.splice(index + 1..index + 1, children);
+        Ok(())
+    }
+
+    pub fn collapse(&mut self, index: usize) {
+        if !self.nodes.get(index).is_some_and(|node| node.expanded) {
+            return;
+        }
+        let end = self.subtree_end(index);
+        self.nodes.drain(index + 1..end);
+        self.nodes[index].expanded = false;
+    }
+
+    pub fn parent(&self, index: usize) -> Option<usize> {
+        let depth = self.nodes.get(index)?.depth;
+        self.nodes[..index].iter().rposition(|node| node.depth < depth)
+    }
+}
+
+/// 目录在前, 同类按自然顺序排序 (app.log.2 排在 app.log.10 前面)
+fn read_children(dir: &Path, depth: usize) -> std::io::Result<Vec<FileNode>> {
+    let mut children = std::fs::read_dir(dir)?
+        .filter_map(Result::ok)
+        .map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            FileNode::new(entry.path(), name, depth)
+        })
+        .collect::<Vec<_>>();
+    children.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| natural_cmp(&a.name, &b.name)));
+    Ok(children)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_tree() {
+        let root = std::env::temp_dir().join(format!("tcmhelper-file-tree-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("cfg/sub")).unwrap();
+        for file in ["app.log.10", "app.log.2", "cfg/svr.toml", "start.sh"] {
+            std::fs::write(root.join(file), "").unwrap();
+        }
+
+        let mut tree = FileTree::new(vec![
+            ("WorkPath".to_string(), root.clone()),
+            ("Missing".to_string(), root.join("missing")),
+        ])
+        .unwrap();
+        let names = |tree: &FileTree| tree.nodes().iter().map(|n| n.name.clone()).collect::<Vec<_>>();
+        assert_eq!(
+            names(&tree),
+            ["WorkPath", "cfg", "app.log.2", "app.log.10", "start.sh", "Missing"]
+        );
+
+        tree.expand(1).unwrap();
+        assert_eq!(names(&tree)[2..4], ["sub", "svr.toml"]);
+        assert_eq!(tree.node(3).unwrap().depth, 2);
+        assert_eq!(tree.parent(3), Some(1));
+        assert_eq!(tree.parent(1), Some(0));
+        assert_eq!(tree.parent(0), None);
+
+        tree.collapse(1);
+        assert_eq!(names(&tree).len(), 6);
+        tree.collapse(0);
+        assert_eq!(names(&tree), ["WorkPath", "Missing"]);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,278 @@
+use std::collections::VecDeque;
+use std::fs::{File, Metadata};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+/// 打开文件时最多从末尾读取这么多字节
+const TAIL_BYTES: u64 = 1 << 20;
+/// 每次 read 的字节数, 一次追加很多内容时也只在内存里保留 capacity 行
+const READ_CHUNK: usize = 64 * 1024;
+
+/// tail -f: 记住读到的位置, 每次 poll 读取新增的内容. 文件被截断或者
+/// 被轮转 (改名后新建同名文件) 时重新读取新文件的末尾. 只保留最后 capacity 行
+pub struct LogFollower {
+    path: PathBuf,
+    file: File,
+    offset: u64,
+    id: Option<u64>,
+    lines: VecDeque<String>,
+    /// 最后一行还没有换行符的部分
+    partial: String,
+    /// 读到一半的 UTF-8 字符, 和下次读到的字节拼起来再解码
+    pending: Vec<u8>,
+    /// 从文件中间开始读时第一行不完整, 读到换行符之前的内容都丢掉
+    skip_line: bool,
+    capacity: usize,
+    /// 从头读取以来一共读到的完整行数, 包括已经丢掉的行
+    pushed: usize,
+    /// 文件被截断或轮转的次数, 变化时之前读到的行都作废
+    generation: usize,
+}
+
+impl LogFollower {
+    pub fn open(path: &Path, capacity: usize) -> std::io::Result<Self> {
+        let file = File::open(path)?;
+        let meta = file.metadata()?;
+        let mut follower = Self {
+            path: path.to_path_buf(),
+            id: file_id(&meta),
+            file,
+            offset: 0,
+            lines: VecDeque::new(),
+            partial: String::new(),
+            pending: Vec::new(),
+            skip_line: false,
+            capacity,
+            pushed: 0,
+            generation: 0,
+        };
+        follower.read_tail(meta.len())?;
+        Ok(follower)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 保留的完整行
+    pub fn complete_lines(&self) -> impl Iterator<Item = &str> {
+        self.lines.iter().map(String::as_str)
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    pub fn pushed(&self) -> usize {
+        self.pushed
+    }
+
+    /// 还没有换行符的最后一行
+    pub fn partial(&self) -> &str {
+        &self.partial
+    }
+
+    /// 第 from 行 (按 pushed 计数) 之后的完整行, 其中有行已经被丢掉时返回 None
+    pub fn lines_after(&self, from: usize) -> Option<impl Iterator<Item = &str>> {
+        let dropped = self.pushed - self.lines.len();
+        let skip = from.checked_sub(dropped)?;
+        Some(self.lines.iter().skip(skip).map(String::as_str))
+    }
+
+    /// 有新内容时返回 true. 文件暂时不存在 (轮转中) 时保留已经读到的内容
+    pub fn poll(&mut self) -> std::io::Result<bool> {
+        let meta = match std::fs::metadata(&self.path) {
+            Ok(meta) => meta,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+            Err(e) => return Err(e),
+        };
+        let rotated = file_id(&meta) != self.id;
+        if rotated || meta.len() < self.offset {
+            self.file = File::open(&self.path)?;
+            let meta = self.file.metadata()?;
+            self.id = file_id(&meta);
+            self.lines.clear();
+            self.partial.clear();
+            self.pending.clear();
+            self.pushed = 0;
+            self.generation += 1;
+            self.read_tail(meta.len())?;
+            return Ok(true);
+        }
+        if meta.len() == self.offset {
+            return Ok(false);
+        }
+        self.read_new()?;
+        Ok(true)
+    }
+
+    /// 从最后 TAIL_BYTES 字节开始读取
+    fn read_tail(&mut self, len: u64) -> std::io::Result<()> {
+        self.offset = len.saturating_sub(TAIL_BYTES);
+        self.skip_line = self.offset > 0;
+        self.read_new()
+    }
+
+    fn read_new(&mut self) -> std::io::Result<()> {
+        self.file.seek(SeekFrom::Start(self.offset))?;
+        let mut buf = vec![0; READ_CHUNK];
+        loop {
+            let n = self.file.read(&mut buf)?;
+            if n == 0 {
+                return Ok(());
+            }
+            self.offset += n as u64;
+            self.push_bytes(&buf[..n]);
+        }
+    }
+
+    fn push_bytes(&mut self, mut bytes: &[u8]) {
+        if self.skip_line {
+            let Some(end) = bytes.iter().position(|&b| b == b'\n') else {
+                return;
+            };
+            bytes = &bytes[end + 1..];
+            self.skip_line = false;
+        }
+        let mut data = std::mem::take(&mut self.pending);
+        data.extend_from_slice(bytes);
+        let valid = data.len() - incomplete_tail(&data);
+        self.pending = data.split_off(valid);
+        self.partial.push_str(&String::from_utf8_lossy(&data));
+
+        let Some(end) = self.partial.rfind('\n') else {
+            return;
+        };
+        let rest = self.partial.split_off(end + 1);
+        let complete = std::mem::replace(&mut self.partial, rest);
+        for line in complete.lines() {
+            self.lines.push_back(line.to_string());
+            self.pushed += 1;
+            if self.lines.len() > self.capacity {
+                self.lines.pop_front();
+            }
+        }
+    }
+}
+
+/// 末尾被截断的 UTF-8 字符的字节数, 没有时返回 0
+fn incomplete_tail(bytes: &[u8]) -> usize {
+    (1..=bytes.len().min(3))
+        .find(|&len| {
+            std::str::from_utf8(&bytes[bytes.len() - len..])
+                .is_err_and(|e| e.valid_up_to() == 0 && e.error_len().is_none())
+        })
+        .unwrap_or(0)
+}
+
+/// 用 inode 判断文件是否被轮转, 其他平台只能靠文件变短判断
+#[cfg(unix)]
+fn file_id(meta: &Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    Some(meta.ino())
+}
+
+#[cfg(not(unix))]
+fn file_id(_meta: &Metadata) -> Option<u64> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_log_follower() {
+        let dir = std::env::temp_dir().join(format!("tcmhelper-follow-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("svr.log");
+        std::fs::write(&path, "a\nb\nc\n").unwrap();
+
+        let mut follower = LogFollower::open(&path, 3).unwrap();
+        // 包括还没有换行符的最后一行
+        let lines = |f: &LogFollower| {
+            f.complete_lines()
+                .chain(Some(f.partial()).filter(|partial| !partial.is_empty()))
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(lines(&follower), ["a", "b", "c"]);
+        assert!(!follower.poll().unwrap());
+
+        // 追加, 没有换行符的部分也显示出来, 超过容量时丢掉最早的行
+        let mut file = std::fs::OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"d\ne").unwrap();
+        assert!(follower.poll().unwrap());
+        assert_eq!(lines(&follower), ["b", "c", "d", "e"]);
+        file.write_all(b"f\n").unwrap();
+        assert!(follower.poll().unwrap());
+        assert_eq!(lines(&follower), ["c", "d", "ef"]);
+        let after = |f: &LogFollower, from| {
+            f.lines_after(from)
+                .map(|l| l.map(str::to_string).collect::<Vec<_>>())
+        };
+        assert_eq!(follower.pushed(), 5);
+        assert_eq!(after(&follower, 4), Some(vec!["ef".to_string()]));
+        assert_eq!(after(&follower, 5), Some(Vec::new()));
+        assert_eq!(after(&follower, 1), None);
+
+        // 截断
+        std::fs::write(&path, "x\n").unwrap();
+        assert!(follower.poll().unwrap());
+        assert_eq!(lines(&follower), ["x"]);
+        assert_eq!((follower.generation(), follower.pushed()), (1, 1));
+
+        // 轮转: 改名后新建同名文件
+        std::fs::rename(&path, dir.join("svr.log.1")).unwrap();
+        assert!(!follower.poll().unwrap());
+        assert_eq!(lines(&follower), ["x"]);
+        std::fs::write(&path, "new\n").unwrap();
+        assert!(follower.poll().unwrap());
+        assert_eq!(lines(&follower), ["new"]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_split_multibyte_char() {
+        let dir = std::env::temp_dir().join(format!("tcmhelper-follow-utf8-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("svr.log");
+        let bytes = "启动\n".as_bytes();
+        std::fs::write(&path, &bytes[..1]).unwrap();
+
+        let mut follower = LogFollower::open(&path, 10).unwrap();
+        assert_eq!(follower.partial(), "");
+        let mut file = std::fs::OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(&bytes[1..4]).unwrap();
+        assert!(follower.poll().unwrap());
+        assert_eq!(follower.partial(), "启");
+        file.write_all(&bytes[4..]).unwrap();
+        assert!(follower.poll().unwrap());
+        assert_eq!(follower.complete_lines().collect::<Vec<_>>(), ["启动"]);
+        assert_eq!(follower.partial(), "");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_open_reads_tail() {
+        let dir = std::env::temp_dir().join(format!("tcmhelper-follow-tail-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("svr.log");
+        let line = "x".repeat(1023) + "\n";
+        std::fs::write(&path, format!("first\n{}last\n", line.repeat(1024))).unwrap();
+
+        let follower = LogFollower::open(&path, usize::MAX).unwrap();
+        // 只读最后 TAIL_BYTES 字节, 丢掉开头不完整的一行
+        assert_eq!(follower.pushed(), 1024);
+        assert_eq!(follower.complete_lines().last(), Some("last"));
+        assert!(follower.complete_lines().all(|l| l != "first"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
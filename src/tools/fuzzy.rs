@@ -0,0 +1,81 @@
+/// 模糊匹配: pattern 的每个字符按顺序出现在 text 中即可匹配, 不区分大小写, 空格忽略.
+/// 返回分数 (越大越好) 和匹配到的字符下标; 连续匹配和单词开头的匹配加分, 中间跳过的字符扣分
+pub fn fuzzy_match(pattern: &str, text: &str) -> Option<(i64, Vec<usize>)> {
+    let chars: Vec<char> = text.chars().flat_map(char::to_lowercase).collect();
+    let pattern: Vec<char> = pattern
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect();
+    let Some(first) = pattern.first() else {
+        return Some((0, Vec::new()));
+    };
+    // 第一个字符可能出现在多处, 每处都按顺序贪心匹配一次, 取分数最高的
+    (0..chars.len())
+        .filter(|&start| chars[start] == *first)
+        .filter_map(|start| match_from(&pattern, &chars, start))
+        .max_by_key(|(score, positions)| (*score, std::cmp::Reverse(positions[0])))
+}
+
+fn match_from(pattern: &[char], chars: &[char], start: usize) -> Option<(i64, Vec<usize>)> {
+    let mut positions: Vec<usize> = Vec::with_capacity(pattern.len());
+    let mut score = 0;
+    let mut from = start;
+    for p in pattern {
+        let found = (from..chars.len()).find(|&i| chars[i] == *p)?;
+        score += 1;
+        if positions.last().is_some_and(|last| last + 1 == found) {
+            score += 5;
+        }
+        if found == 0 || !chars[found - 1].is_alphanumeric() {
+            score += 3;
+        }
+        if !positions.is_empty() {
+            score -= (found - from) as i64;
+        }
+        positions.push(found);
+        from = found + 1;
+    }
+    Some((score, positions))
+}
+
+/// 匹配的条目按分数从高到低排序, 分数相同时短的在前, 再按原来的顺序.
+/// 返回条目的下标和匹配到的字符下标
+pub fn fuzzy_filter<'a>(pattern: &str, items: impl Iterator<Item = &'a str>) -> Vec<(usize, Vec<usize>)> {
+    let mut matches: Vec<(i64, usize, usize, Vec<usize>)> = items
+        .enumerate()
+        .filter_map(|(i, text)| {
+            fuzzy_match(pattern, text).map(|(score, positions)| (score, text.chars().count(), i, positions))
+        })
+        .collect();
+    matches.sort_by_key(|(score, len, i, _)| (-score, *len, *i));
+    matches.into_iter().map(|(_, _, i, positions)| (i, positions)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_match() {
+        assert_eq!(fuzzy_match("", "Export rows").map(|(_, p)| p), Some(vec![]));
+        assert_eq!(fuzzy_match("exr", "Export rows").map(|(_, p)| p), Some(vec![0, 1, 4]));
+        assert_eq!(fuzzy_match("EXP", "export").map(|(_, p)| p), Some(vec![0, 1, 2]));
+        assert!(fuzzy_match("xe", "Export").is_none());
+        // 选择单词开头的 t, 而不是 Switch 中的 t
+        assert_eq!(fuzzy_match("tab", "Switch tab: host").map(|(_, p)| p), Some(vec![7, 8, 9]));
+        // 空格只用来分隔, 不参与匹配
+        assert!(fuzzy_match("sw tab", "Switch tab: host").is_some());
+    }
+
+    #[test]
+    fn test_fuzzy_filter() {
+        let items = ["Switch theme", "Export rows to csv", "Switch tab: host", "Table palette"];
+        let order: Vec<usize> = fuzzy_filter("tab", items.iter().copied())
+            .into_iter()
+            .map(|(i, _)| i)
+            .collect();
+        assert_eq!(order, [3, 2]);
+        assert_eq!(fuzzy_filter("", items.iter().copied()).len(), 4);
+    }
+}
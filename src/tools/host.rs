@@ -1,6 +1,6 @@
 use crate::database::TcmQueryResult;
 use crate::description::*;
-use crate::HOST_HASHMAP;
+use hashbrown::HashMap;
 use serde::Deserialize;
 use serde::Serialize;
 
@@ -24,7 +24,8 @@ impl From<&TcmQueryResult> for HostInfo {
     }
 }
 
-pub fn collect_host_info(deploy_center: DeployTcmCenter) -> Vec<HostInfo> {
+/// `host_map` 是 host.xml 中的主机名到内网 IP
+pub fn collect_host_info(deploy_center: DeployTcmCenter, host_map: &HashMap<String, String>) -> Vec<HostInfo> {
     let mut hosts: Vec<HostInfo> = Vec::new();
     for deploy_group in deploy_center.cluster_deploy.deploy_groups {
         let host_name: String;
@@ -33,7 +34,7 @@ pub fn collect_host_info(deploy_center: DeployTcmCenter) -> Vec<HostInfo> {
             None => host_name = "TcmHost".to_string(),
         };
 
-        match HOST_HASHMAP.get(&host_name) {
+        match host_map.get(&host_name) {
             Some(inner_ip) => hosts.push(HostInfo {
                 inner_ip: inner_ip.to_string(),
                 host_name: host_name.to_string(),
@@ -46,7 +47,7 @@ pub fn collect_host_info(deploy_center: DeployTcmCenter) -> Vec<HostInfo> {
     for world in deploy_center.cluster_deploy.worlds {
         for zone in world.zone_list {
             for deploy_group in zone.deploy_groups {
-                match HOST_HASHMAP.get(&deploy_group.host) {
+                match host_map.get(&deploy_group.host) {
                     Some(inner_ip) => hosts.push(HostInfo {
                         inner_ip: inner_ip.to_string(),
                         host_name: deploy_group.host,
@@ -1,4 +1,14 @@
 pub mod host;
 pub mod search;
 pub mod proc;
-pub mod deploy;
\ No newline at end of file
+pub mod deploy;
+pub mod tcm_command;
+pub mod topology;
+pub mod text_search;
+pub mod file_tree;
+pub mod diff;
+pub mod follow;
+pub mod binary;
+pub mod editor;
+pub mod fuzzy;
+pub mod notify;
@@ -0,0 +1,151 @@
+use crate::tools::binary::format_utc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// 同时显示的提示数量, 更早的只保留在消息记录中
+pub const MAX_TOASTS: usize = 3;
+/// 提示消失前淡出的时间
+const FADE: Duration = Duration::from_secs(1);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Level {
+    Info,
+    Warn,
+    Error,
+}
+
+impl Level {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Level::Info => "INFO",
+            Level::Warn => "WARN",
+            Level::Error => "ERROR",
+        }
+    }
+
+    /// 提示显示的时间, 错误停留更久
+    fn duration(&self) -> Duration {
+        match self {
+            Level::Info => Duration::from_secs(3),
+            Level::Warn => Duration::from_secs(5),
+            Level::Error => Duration::from_secs(8),
+        }
+    }
+}
+
+/// 提示显示到了哪个阶段
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fade {
+    Shown,
+    Fading,
+    Gone,
+}
+
+pub struct Notice {
+    pub level: Level,
+    pub text: String,
+    /// 从 UNIX_EPOCH 开始的秒数, 消息记录中显示
+    pub time: u64,
+    created: Instant,
+}
+
+impl Notice {
+    pub fn fade(&self, now: Instant) -> Fade {
+        let elapsed = now.saturating_duration_since(self.created);
+        let duration = self.level.duration();
+        if elapsed >= duration {
+            Fade::Gone
+        } else if elapsed + FADE >= duration {
+            Fade::Fading
+        } else {
+            Fade::Shown
+        }
+    }
+
+    /// 消息记录中的一行, 也是复制的内容
+    pub fn log_line(&self) -> String {
+        format!("{} [{}] {}", format_utc(self.time), self.level.name(), self.text)
+    }
+}
+
+/// 本次运行的所有提示, 按时间先后排列
+#[derive(Default)]
+pub struct NoticeLog {
+    notices: Vec<Notice>,
+}
+
+impl NoticeLog {
+    pub fn push(&mut self, level: Level, text: String) {
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs());
+        self.push_at(level, text, time, Instant::now());
+    }
+
+    fn push_at(&mut self, level: Level, text: String, time: u64, created: Instant) {
+        self.notices.push(Notice {
+            level,
+            text,
+            time,
+            created,
+        });
+    }
+
+    pub fn notices(&self) -> &[Notice] {
+        &self.notices
+    }
+
+    /// 还没有消失的提示, 最新的在前
+    pub fn toasts(&self, now: Instant) -> Vec<(&Notice, Fade)> {
+        self.notices
+            .iter()
+            .rev()
+            .map(|notice| (notice, notice.fade(now)))
+            .filter(|(_, fade)| *fade != Fade::Gone)
+            .take(MAX_TOASTS)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_toasts() {
+        let start = Instant::now();
+        let mut log = NoticeLog::default();
+        log.push_at(Level::Info, "已复制".to_string(), 0, start);
+        log.push_at(Level::Error, "文件不存在".to_string(), 0, start);
+
+        let texts = |now| {
+            log.toasts(now)
+                .into_iter()
+                .map(|(notice, fade)| (notice.text.as_str(), fade))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(texts(start), [("文件不存在", Fade::Shown), ("已复制", Fade::Shown)]);
+        assert_eq!(
+            texts(start + Duration::from_millis(2500)),
+            [("文件不存在", Fade::Shown), ("已复制", Fade::Fading)]
+        );
+        // 错误停留更久, 消失后仍然保留在记录中
+        assert_eq!(texts(start + Duration::from_secs(5)), [("文件不存在", Fade::Shown)]);
+        assert!(texts(start + Duration::from_secs(8)).is_empty());
+        assert_eq!(log.notices().len(), 2);
+
+        for i in 0..5 {
+            log.push_at(Level::Warn, i.to_string(), 0, start);
+        }
+        assert_eq!(log.toasts(start).len(), MAX_TOASTS);
+    }
+
+    #[test]
+    fn test_log_line() {
+        let mut log = NoticeLog::default();
+        log.push_at(Level::Warn, "按键绑定冲突".to_string(), 1_700_000_000, Instant::now());
+        assert_eq!(
+            log.notices()[0].log_line(),
+            "2023-11-14 22:13:20 UTC [WARN] 按键绑定冲突"
+        );
+    }
+}
@@ -1,9 +1,11 @@
-use crate::description::proc::{ClusterEelement, ProcTcmCenter, WorldElement};
+use crate::description::proc::{ClusterEelement, Proc, ProcTcmCenter, WorldElement, WorldProc};
+use crate::utils::TcmCommand;
+use anyhow::Context;
 use hashbrown::HashMap;
 use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
 
-#[derive(Clone, Deserialize, Serialize, PartialEq, Debug)]
+#[derive(Clone, Copy, Deserialize, Serialize, PartialEq, Debug)]
 pub enum ProcAction {
     Start,
     Stop,
@@ -13,6 +15,27 @@ pub enum ProcAction {
     RunShell,
 }
 
+impl ProcAction {
+    /// 进程弹窗里可选的操作
+    pub const POPUP: [ProcAction; 4] = [
+        ProcAction::Check,
+        ProcAction::Start,
+        ProcAction::Stop,
+        ProcAction::Restart,
+    ];
+
+    /// 操作对应的 tcm console 命令, tcm 没有 restart 命令, 重启是先 stop 再 start
+    pub fn tcm_commands(&self) -> &'static [TcmCommand] {
+        match self {
+            ProcAction::Check => &[TcmCommand::ListProc],
+            ProcAction::Start => &[TcmCommand::Start],
+            ProcAction::Stop => &[TcmCommand::Stop],
+            ProcAction::Restart => &[TcmCommand::Stop, TcmCommand::Start],
+            ProcAction::Auto | ProcAction::RunShell => &[],
+        }
+    }
+}
+
 impl From<String> for ProcAction {
     fn from(s: String) -> Self {
         match s.as_str() {
@@ -76,10 +99,68 @@ pub struct ProcInfo {
     pub func_id: i32,
     pub work_path: String,
     pub proc_name: String,
+    #[serde(flatten)]
+    pub attributes: ProcAttributes,
 }
 
-pub fn collect_proc_info(proc_center: ProcTcmCenter) -> Vec<ProcInfo> {
+/// proc.xml 中 Proc 节点上其余的属性, 以及 ProcGroup 里配置的启动参数
+#[derive(Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ProcAttributes {
+    pub flag: String,
+    pub is_common: Option<String>,
+    pub config_path: Option<String>,
+    pub seq: Option<String>,
+    pub auto_script: Option<String>,
+    pub restart_cmd: Option<String>,
+    pub args: Option<String>,
+}
+
+impl ProcAttributes {
+    /// 按 XML 属性名列出, 未配置的属性为 None
+    pub fn pairs(&self) -> Vec<(&'static str, Option<&str>)> {
+        vec![
+            ("Flag", Some(self.flag.as_str())),
+            ("IsCommon", self.is_common.as_deref()),
+            ("ConfigPath", self.config_path.as_deref()),
+            ("Seq", self.seq.as_deref()),
+            ("AutoScript", self.auto_script.as_deref()),
+            ("ReStartCmd", self.restart_cmd.as_deref()),
+            ("Agrs", self.args.as_deref()),
+        ]
+    }
+}
+
+impl From<&Proc> for ProcAttributes {
+    fn from(p: &Proc) -> Self {
+        ProcAttributes {
+            flag: p.flag.clone(),
+            is_common: p.is_common.clone(),
+            config_path: p.config_path.clone(),
+            seq: p.seq.clone(),
+            auto_script: p.auto_script.clone(),
+            restart_cmd: p.restart_cmd.clone(),
+            args: None,
+        }
+    }
+}
+
+impl From<&WorldProc> for ProcAttributes {
+    fn from(p: &WorldProc) -> Self {
+        ProcAttributes {
+            flag: p.flag.clone(),
+            is_common: p.is_common.clone(),
+            config_path: p.config_path.clone(),
+            seq: p.seq.clone(),
+            auto_script: p.auto_script.clone(),
+            restart_cmd: p.restart_cmd.clone(),
+            args: None,
+        }
+    }
+}
+
+pub fn collect_proc_info(proc_center: ProcTcmCenter) -> anyhow::Result<Vec<ProcInfo>> {
     let mut proc_group_name_map = HashMap::new();
+    let mut proc_args_map = HashMap::new();
     let mut procs: Vec<ProcInfo> = Vec::new();
     proc_center
         .procgroup_vec
@@ -91,77 +172,103 @@ pub fn collect_proc_info(proc_center: ProcTcmCenter) -> Vec<ProcInfo> {
                 .for_each(|proc_info| {
                     proc_group_name_map
                         .insert(proc_info.func_name.clone(), proc_group_info.name.clone());
+                    proc_args_map.insert(proc_info.func_name.clone(), proc_info.agrs.clone());
                 });
         });
-    proc_center.cluster_vec.iter().for_each(|cluster| {
+    // 每个 Proc 的 FuncName 都必须出现在某个 ProcGroup 里
+    let group_name = |func_name: &String| {
+        proc_group_name_map
+            .get(func_name)
+            .cloned()
+            .with_context(|| format!("FuncName {} 不在任何 ProcGroup 中", func_name))
+    };
+    for cluster in proc_center.cluster_vec.iter() {
         let base_work_path = cluster.work_path.clone();
-        cluster.proc_list.iter().for_each(|element| match element {
-            ClusterEelement::Proc(p) => {
-                let proc_work_path= p.work_path.clone().unwrap_or("./".to_string());
-                let proc_name: String = p.proc_name.clone().unwrap_or_else(|| p.func_name.clone());
-                let work_path = join_work_path(&base_work_path, &proc_work_path);
-                let proc_info = ProcInfo {
-                    layer: LayerEnum::Cluster.into(),
-                    funcname: p.func_name.clone(),
-                    group_name: proc_group_name_map.get(&p.func_name).clone()
-                    func_id: p.func_id,
-                    proc_name: proc_name,
-                    work_path,
-                };
-                procs.push(proc_info);
-            }
-            ClusterEelement::World(w) => {
-                w.proc_list
-                    .iter()
-                    .for_each(|world_element| match world_element {
-                        WorldElement::Proc(world_proc) => {
-                            let world_proc_work_path = world_proc.work_path.clone().unwrap_or("./".to_string());
-                            let work_path = join_work_path(&base_work_path, &world_proc_work_path);
-                            let proc_name: String = world_proc
-                                .proc_name
-                                .clone()
-                                .unwrap_or_else(|| world_proc.func_name.clone());
-                            let proc_info = ProcInfo {
-                                layer: LayerEnum::Cluster.into(),
-                                funcname: world_proc.func_name.clone(),
-                                group_name: proc_group_name_map
-                                    .get(&world_proc.func_name)
-                                    .unwrap()
-                                    .clone(),
-                                func_id: world_proc.func_id,
-                                work_path: work_path,
-                                proc_name: proc_name,
-                            };
-                            procs.push(proc_info);
-                        }
-                        WorldElement::Zone(zone_proc) => {
-                            zone_proc.zone_proc_vec.iter().for_each(|zone_proc| {
-                                let zone_work_path = zone_proc.work_path.clone().unwrap_or("./".to_string());
+        for element in cluster.proc_list.iter() {
+            match element {
+                ClusterEelement::Proc(p) => {
+                    let proc_work_path = p.work_path.clone().unwrap_or("./".to_string());
+                    let proc_name: String =
+                        p.proc_name.clone().unwrap_or_else(|| p.func_name.clone());
+                    let work_path = join_work_path(&base_work_path, &proc_work_path);
+                    let proc_info = ProcInfo {
+                        layer: LayerEnum::Cluster.into(),
+                        funcname: p.func_name.clone(),
+                        group_name: group_name(&p.func_name)?,
+                        func_id: p.func_id,
+                        proc_name: proc_name,
+                        work_path,
+                        attributes: ProcAttributes {
+                            args: proc_args_map.get(&p.func_name).cloned().flatten(),
+                            ..p.into()
+                        },
+                    };
+                    procs.push(proc_info);
+                }
+                ClusterEelement::World(w) => {
+                    for world_element in w.proc_list.iter() {
+                        match world_element {
+                            WorldElement::Proc(world_proc) => {
+                                let world_proc_work_path =
+                                    world_proc.work_path.clone().unwrap_or("./".to_string());
                                 let work_path =
-                                    join_work_path(&base_work_path, &zone_work_path);
-                                let proc_name: String = zone_proc
+                                    join_work_path(&base_work_path, &world_proc_work_path);
+                                let proc_name: String = world_proc
                                     .proc_name
                                     .clone()
-                                    .unwrap_or_else(|| zone_proc.func_name.clone());
+                                    .unwrap_or_else(|| world_proc.func_name.clone());
                                 let proc_info = ProcInfo {
-                                    layer: LayerEnum::Zone.into(),
-                                    funcname: zone_proc.func_name.clone(),
-                                    group_name: proc_group_name_map
-                                        .get(&zone_proc.func_name)
-                                        .unwrap()
-                                        .clone(),
-                                    func_id: zone_proc.func_id,
-                                    work_path,
+                                    layer: LayerEnum::Cluster.into(),
+                                    funcname: world_proc.func_name.clone(),
+                                    group_name: group_name(&world_proc.func_name)?,
+                                    func_id: world_proc.func_id,
+                                    work_path: work_path,
                                     proc_name: proc_name,
+                                    attributes: ProcAttributes {
+                                        args: proc_args_map
+                                            .get(&world_proc.func_name)
+                                            .cloned()
+                                            .flatten(),
+                                        ..world_proc.into()
+                                    },
                                 };
                                 procs.push(proc_info);
-                            });
+                            }
+                            WorldElement::Zone(zone_proc) => {
+                                for zone_proc in zone_proc.zone_proc_vec.iter() {
+                                    let zone_work_path =
+                                        zone_proc.work_path.clone().unwrap_or("./".to_string());
+                                    let work_path =
+                                        join_work_path(&base_work_path, &zone_work_path);
+                                    let proc_name: String = zone_proc
+                                        .proc_name
+                                        .clone()
+                                        .unwrap_or_else(|| zone_proc.func_name.clone());
+                                    let proc_info = ProcInfo {
+                                        layer: LayerEnum::Zone.into(),
+                                        funcname: zone_proc.func_name.clone(),
+                                        group_name: group_name(&zone_proc.func_name)?,
+                                        func_id: zone_proc.func_id,
+                                        work_path,
+                                        proc_name: proc_name,
+                                        attributes: ProcAttributes {
+                                            args: proc_args_map
+                                                .get(&zone_proc.func_name)
+                                                .cloned()
+                                                .flatten(),
+                                            ..zone_proc.into()
+                                        },
+                                    };
+                                    procs.push(proc_info);
+                                }
+                            }
                         }
-                    });
+                    }
+                }
             }
-        });
-    });
-    procs
+        }
+    }
+    Ok(procs)
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
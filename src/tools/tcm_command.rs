@@ -0,0 +1,183 @@
+use crate::components::total_proc::TotalProc;
+use crate::tools::host::HostInfo;
+use crate::tools::proc::ProcInfo;
+use crate::utils::TcmCommand;
+use hashbrown::HashMap;
+
+/// 把连续的 InstID 折叠成区间, 例如 `[1, 2, 3, 5]` -> `["1-3", "5"]`
+pub fn collapse_inst_ids(inst_ids: &[i32]) -> Vec<String> {
+    let mut ids = inst_ids.to_vec();
+    ids.sort_unstable();
+    ids.dedup();
+
+    let mut ranges = Vec::new();
+    let mut iter = ids.into_iter();
+    let Some(first) = iter.next() else {
+        return ranges;
+    };
+    let (mut start, mut end) = (first, first);
+    for id in iter {
+        if id == end + 1 {
+            end = id;
+            continue;
+        }
+        ranges.push(format_range(start, end));
+        (start, end) = (id, id);
+    }
+    ranges.push(format_range(start, end));
+    ranges
+}
+
+fn format_range(start: i32, end: i32) -> String {
+    if start == end {
+        start.to_string()
+    } else {
+        format!("{}-{}", start, end)
+    }
+}
+
+/// 根据进程实例生成 tcm console 命令, 同一个 world.zone.func 下的 InstID 合并成区间
+pub fn proc_commands(cmd: TcmCommand, procs: &[TotalProc]) -> Vec<String> {
+    proc_selectors(procs)
+        .into_iter()
+        .map(|selector| format!("{} {}", cmd, selector))
+        .collect()
+}
+
+type WorldZoneFunc<'a> = (&'a str, &'a str, &'a str);
+
+/// 进程实例对应的 world.zone.func.inst 选择器,
+/// 不是数字的 InstID 无法合并成区间, 原样单独列出, 避免 stop/start 时漏掉实例
+pub fn proc_selectors(procs: &[TotalProc]) -> Vec<String> {
+    let mut order: Vec<WorldZoneFunc> = Vec::new();
+    let mut inst_map: HashMap<WorldZoneFunc, (Vec<i32>, Vec<&str>)> = HashMap::new();
+    for p in procs {
+        let key = (p.world_id.as_str(), p.zone_id.as_str(), p.func_id.as_str());
+        let (inst_ids, raw_ids) = inst_map.entry(key).or_insert_with(|| {
+            order.push(key);
+            (Vec::new(), Vec::new())
+        });
+        match p.inst_id.parse::<i32>() {
+            Ok(inst_id) => inst_ids.push(inst_id),
+            Err(_) if !raw_ids.contains(&p.inst_id.as_str()) => raw_ids.push(&p.inst_id),
+            Err(_) => {}
+        }
+    }
+
+    order
+        .into_iter()
+        .flat_map(|key @ (world_id, zone_id, func_id)| {
+            let (inst_ids, raw_ids) = &inst_map[&key];
+            collapse_inst_ids(inst_ids)
+                .into_iter()
+                .chain(raw_ids.iter().map(|id| id.to_string()))
+                .map(move |range| format!("{}.{}.{}.{}", world_id, zone_id, func_id, range))
+        })
+        .collect()
+}
+
+/// 没有部署信息时, 只能按 FuncID 生成命令
+pub fn func_commands(cmd: TcmCommand, procs: &[ProcInfo]) -> Vec<String> {
+    func_selectors(procs)
+        .into_iter()
+        .map(|selector| format!("{} {}", cmd, selector))
+        .collect()
+}
+
+pub fn func_selectors(procs: &[ProcInfo]) -> Vec<String> {
+    let mut func_ids: Vec<i32> = procs.iter().map(|p| p.func_id).collect();
+    func_ids.sort_unstable();
+    func_ids.dedup();
+    func_ids
+        .into_iter()
+        .map(|func_id| format!("*.*.{}.*", func_id))
+        .collect()
+}
+
+/// 主机所在的 world.zone 选择器, 按出现顺序去重
+pub fn host_selectors(hosts: &[HostInfo]) -> Vec<String> {
+    let mut selectors: Vec<String> = Vec::new();
+    for host in hosts {
+        let selector = format!("{}.{}.*.*", host.world_id, host.zone_id);
+        if !selectors.contains(&selector) {
+            selectors.push(selector);
+        }
+    }
+    selectors
+}
+
+pub fn query_command(cmd: TcmCommand, query: &str) -> String {
+    format!("{} {}", cmd, query.trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn total_proc(world_id: &str, zone_id: &str, func_id: &str, inst_id: &str) -> TotalProc {
+        TotalProc {
+            func_id: func_id.to_string(),
+            inst_id: inst_id.to_string(),
+            proc_name: "gamesvr".to_string(),
+            group_name: "GameGroup".to_string(),
+            inner_ip: "127.0.0.1".to_string(),
+            host_name: "Host_Main_70".to_string(),
+            world_id: world_id.to_string(),
+            zone_id: zone_id.to_string(),
+            work_path: "/data/gamesvr".to_string(),
+            func_name: "gamesvr".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_collapse_inst_ids() {
+        assert_eq!(collapse_inst_ids(&[]), Vec::<String>::new());
+        assert_eq!(collapse_inst_ids(&[1]), vec!["1"]);
+        assert_eq!(
+            collapse_inst_ids(&[3, 1, 2, 5, 5, 7, 8]),
+            vec!["1-3", "5", "7-8"]
+        );
+    }
+
+    #[test]
+    fn test_proc_commands() {
+        let procs = vec![
+            total_proc("2", "70", "201", "2"),
+            total_proc("2", "70", "201", "1"),
+            total_proc("2", "71", "201", "1"),
+            total_proc("2", "70", "201", "4"),
+            total_proc("2", "70", "201", "3"),
+        ];
+        assert_eq!(
+            proc_commands(TcmCommand::Start, &procs),
+            vec!["start 2.70.201.1-4", "start 2.71.201.1"]
+        );
+        assert_eq!(
+            proc_commands(TcmCommand::Stop, &procs[..2]),
+            vec!["stop 2.70.201.1-2"]
+        );
+        assert_eq!(
+            proc_selectors(&procs[2..3]),
+            vec!["2.71.201.1"]
+        );
+        assert_eq!(
+            query_command(TcmCommand::ListProc, " 2.70.*.* "),
+            "listProc 2.70.*.*"
+        );
+    }
+
+    #[test]
+    fn test_proc_selectors_keep_unparsed_inst_ids() {
+        let procs = vec![
+            total_proc("2", "70", "201", "1"),
+            total_proc("2", "70", "201", "a"),
+            total_proc("2", "70", "201", "2"),
+            total_proc("2", "70", "201", "a"),
+            total_proc("2", "70", "202", ""),
+        ];
+        assert_eq!(
+            proc_selectors(&procs),
+            vec!["2.70.201.1-2", "2.70.201.a", "2.70.202."]
+        );
+    }
+}
@@ -0,0 +1,104 @@
+use anyhow::Context;
+use regex::Regex;
+use std::ops::Range;
+
+/// 文件中的一处匹配, range 是行内的字节范围
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextMatch {
+    pub line: usize,
+    pub range: Range<usize>,
+}
+
+/// regex 为 false 时按普通文本匹配
+pub fn compile(pattern: &str, regex: bool) -> anyhow::Result<Regex> {
+    if regex {
+        Regex::new(pattern).with_context(|| format!("Invalid regex -> [{}]", pattern))
+    } else {
+        Ok(Regex::new(&regex::escape(pattern))?)
+    }
+}
+
+/// 按行查找 pattern, 空匹配会被跳过
+pub fn find_matches(text: &str, pattern: &str, regex: bool) -> anyhow::Result<Vec<TextMatch>> {
+    if pattern.is_empty() {
+        return Ok(Vec::new());
+    }
+    let re = compile(pattern, regex)?;
+    let mut matches = Vec::new();
+    for (line, content) in text.lines().enumerate() {
+        matches.extend(
+            re.find_iter(content)
+                .filter(|m| !m.is_empty())
+                .map(|m| TextMatch {
+                    line,
+                    range: m.range(),
+                }),
+        );
+    }
+    Ok(matches)
+}
+
+/// 把匹配的高亮叠加到一行已有的分段样式上, 匹配边界处把分段切开.
+/// hits 按起始位置排序且互不重叠
+pub fn overlay<S: Copy>(
+    items: &[(S, Range<usize>)],
+    hits: &[(Range<usize>, S)],
+    patch: impl Fn(S, S) -> S,
+) -> Vec<(S, Range<usize>)> {
+    let mut result = Vec::with_capacity(items.len() + hits.len() * 2);
+    for (style, range) in items {
+        let mut start = range.start;
+        for (hit, hit_style) in hits {
+            if hit.end <= start || hit.start >= range.end {
+                continue;
+            }
+            if hit.start > start {
+                result.push((*style, start..hit.start));
+            }
+            let end = hit.end.min(range.end);
+            result.push((patch(*style, *hit_style), hit.start.max(start)..end));
+            start = end;
+        }
+        if start < range.end {
+            result.push((*style, start..range.end));
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_matches() {
+        let text = "listen 0.0.0.0:8080\nport = 8080\n\nname a.b";
+        let plain = find_matches(text, "8080", false).unwrap();
+        assert_eq!(
+            plain,
+            vec![
+                TextMatch { line: 0, range: 15..19 },
+                TextMatch { line: 1, range: 7..11 },
+            ]
+        );
+        // 普通文本中的 . 不是通配符
+        assert_eq!(find_matches(text, "a.b", false).unwrap().len(), 1);
+        assert_eq!(find_matches(text, "0.0", false).unwrap().len(), 2);
+        assert_eq!(find_matches(text, r"\d+", true).unwrap().len(), 6);
+        assert!(find_matches(text, "", false).unwrap().is_empty());
+        assert!(find_matches(text, "x*", true).unwrap().is_empty());
+        assert!(find_matches(text, "(", true).is_err());
+    }
+
+    #[test]
+    fn test_overlay() {
+        let items = [(1, 0..4), (2, 4..10)];
+        let hits = [(2..6, 10), (8..9, 20)];
+        let result = overlay(&items, &hits, |a, b| a + b);
+        assert_eq!(
+            result,
+            vec![(1, 0..2), (11, 2..4), (12, 4..6), (2, 6..8), (22, 8..9), (2, 9..10)]
+        );
+        assert_eq!(overlay(&items, &[], |a, b| a + b), items.to_vec());
+    }
+}
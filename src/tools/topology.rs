@@ -0,0 +1,285 @@
+use crate::database::TcmQueryResult;
+use crate::tools::host::HostInfo;
+use crate::tools::tcm_command::collapse_inst_ids;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NodeKind {
+    Cluster,
+    World,
+    Zone,
+    Host(HostInfo),
+    Group,
+    Proc { func_id: i32, inst_ids: Vec<i32> },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TopologyNode {
+    pub kind: NodeKind,
+    pub name: String,
+    pub depth: usize,
+    /// 子树下的进程实例数
+    pub count: usize,
+    /// 子树下的节点数 (不含自己), 节点按先序存放, 子树就是后面连续的 descendants 个节点
+    descendants: usize,
+    pub expanded: bool,
+}
+
+impl TopologyNode {
+    fn new(kind: NodeKind, name: String, depth: usize) -> Self {
+        Self {
+            kind,
+            name,
+            depth,
+            count: 0,
+            descendants: 0,
+            expanded: depth < 2,
+        }
+    }
+
+    pub fn is_leaf(&self) -> bool {
+        self.descendants == 0
+    }
+
+    pub fn label(&self) -> String {
+        match &self.kind {
+            NodeKind::Proc { func_id, inst_ids } => format!(
+                "{} ({}) inst {}",
+                self.name,
+                func_id,
+                collapse_inst_ids(inst_ids).join(",")
+            ),
+            _ => format!("{} [{}]", self.name, self.count),
+        }
+    }
+}
+
+/// cluster → world → zone → host → group → proc 的部署拓扑
+#[derive(Debug, Clone, Default)]
+pub struct Topology {
+    nodes: Vec<TopologyNode>,
+}
+
+impl Topology {
+    pub fn build(rows: &[TcmQueryResult]) -> Self {
+        let mut rows = rows.iter().collect::<Vec<_>>();
+        rows.sort_by_key(|r| {
+            (
+                r.world_id.parse::<i64>().unwrap_or(i64::MAX),
+                r.zone_id.parse::<i64>().unwrap_or(i64::MAX),
+                r.host_name.clone(),
+                r.inner_ip.clone(),
+                r.proc_group_name.clone(),
+                r.func_id,
+                r.inst_id,
+            )
+        });
+
+        let mut nodes = vec![TopologyNode::new(NodeKind::Cluster, "Cluster".to_string(), 0)];
+        let mut previous: Option<[String; 5]> = None;
+        for row in rows {
+            let path = [
+                row.world_id.clone(),
+                row.zone_id.clone(),
+                format!("{}/{}", row.host_name, row.inner_ip),
+                row.proc_group_name.clone(),
+                row.func_id.to_string(),
+            ];
+            // 第一个和上一行不同的层级开始新建节点
+            let level = previous
+                .as_ref()
+                .and_then(|p| p.iter().zip(path.iter()).position(|(a, b)| a != b))
+                .unwrap_or(if previous.is_some() { path.len() } else { 0 });
+            for depth in (level + 1)..=path.len() {
+                let (kind, name) = match depth {
+                    1 => (NodeKind::World, format!("World {}", row.world_id)),
+                    2 => (NodeKind::Zone, format!("Zone {}", row.zone_id)),
+                    3 => (
+                        NodeKind::Host(HostInfo {
+                            inner_ip: row.inner_ip.clone(),
+                            host_name: row.host_name.clone(),
+                            world_id: row.world_id.clone(),
+                            zone_id: row.zone_id.clone(),
+                        }),
+                        format!("{} {}", row.host_name, row.inner_ip),
+                    ),
+                    4 => (NodeKind::Group, row.proc_group_name.clone()),
+                    _ => (
+                        NodeKind::Proc {
+                            func_id: row.func_id,
+                            inst_ids: Vec::new(),
+                        },
+                        row.func_name.clone(),
+                    ),
+                };
+                nodes.push(TopologyNode::new(kind, name, depth));
+            }
+            if let Some(NodeKind::Proc { inst_ids, .. }) = nodes.last_mut().map(|n| &mut n.kind) {
+                inst_ids.push(row.inst_id);
+            }
+            previous = Some(path);
+        }
+
+        // 倒序累加子树大小和实例数
+        for i in (0..nodes.len()).rev() {
+            let depth = nodes[i].depth;
+            let (mut descendants, mut count) = (0, 0);
+            let mut j = i + 1;
+            while j < nodes.len() && nodes[j].depth > depth {
+                descendants += 1 + nodes[j].descendants;
+                count += nodes[j].count;
+                j += 1 + nodes[j].descendants;
+            }
+            nodes[i].descendants = descendants;
+            nodes[i].count = match &nodes[i].kind {
+                NodeKind::Proc { inst_ids, .. } => inst_ids.len(),
+                _ => count,
+            };
+        }
+        Self { nodes }
+    }
+
+    pub fn node(&self, index: usize) -> Option<&TopologyNode> {
+        self.nodes.get(index)
+    }
+
+    pub fn parent(&self, index: usize) -> Option<usize> {
+        let depth = self.nodes.get(index)?.depth;
+        (0..index).rev().find(|&i| self.nodes[i].depth + 1 == depth)
+    }
+
+    pub fn set_expanded(&mut self, index: usize, expanded: bool) {
+        if let Some(node) = self.nodes.get_mut(index) {
+            node.expanded = expanded && !node.is_leaf();
+        }
+    }
+
+    /// 当前可见节点的下标. 有过滤词时保留命中的节点, 它们的祖先和子树, 并忽略折叠状态
+    pub fn visible(&self, filter: &str) -> Vec<usize> {
+        if filter.is_empty() {
+            let mut visible = Vec::new();
+            let mut i = 0;
+            while i < self.nodes.len() {
+                visible.push(i);
+                let node = &self.nodes[i];
+                i += if node.expanded { 1 } else { 1 + node.descendants };
+            }
+            return visible;
+        }
+
+        let filter = filter.to_lowercase();
+        let mut keep = vec![false; self.nodes.len()];
+        for (i, node) in self.nodes.iter().enumerate() {
+            if !node.label().to_lowercase().contains(&filter) {
+                continue;
+            }
+            keep[i..=i + node.descendants].fill(true);
+            let mut child = i;
+            while let Some(parent) = self.parent(child) {
+                keep[parent] = true;
+                child = parent;
+            }
+        }
+        (0..self.nodes.len()).filter(|&i| keep[i]).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(world: &str, zone: &str, host: &str, group: &str, func_id: i32, inst_id: i32) -> TcmQueryResult {
+        TcmQueryResult {
+            host_id: 0,
+            inner_ip: format!("10.0.0.{}", host.len()),
+            host_name: host.to_string(),
+            world_id: world.to_string(),
+            zone_id: zone.to_string(),
+            func_id,
+            proc_type: "Zone".to_string(),
+            work_path: String::new(),
+            func_name: format!("svr{}", func_id),
+            proc_name: format!("svr{}", func_id),
+            proc_group_name: group.to_string(),
+            inst_id,
+        }
+    }
+
+    fn labels(topology: &Topology, visible: &[usize]) -> Vec<String> {
+        visible
+            .iter()
+            .map(|&i| {
+                let node = topology.node(i).unwrap();
+                format!("{}{}", "  ".repeat(node.depth), node.label())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_build_topology() {
+        let rows = vec![
+            row("2", "70", "Main", "Game", 201, 2),
+            row("2", "70", "Main", "Game", 201, 1),
+            row("2", "10", "DB", "DB", 202, 1),
+            row("0", "0", "Main", "Dir", 10, 1),
+        ];
+        let mut topology = Topology::build(&rows);
+        assert_eq!(
+            labels(&topology, &topology.visible("")),
+            vec![
+                "Cluster [4]",
+                "  World 0 [1]",
+                "    Zone 0 [1]",
+                "  World 2 [3]",
+                "    Zone 10 [1]",
+                "    Zone 70 [2]",
+            ]
+        );
+
+        for i in 0..topology.nodes.len() {
+            topology.set_expanded(i, true);
+        }
+        assert_eq!(
+            labels(&topology, &topology.visible("")),
+            vec![
+                "Cluster [4]",
+                "  World 0 [1]",
+                "    Zone 0 [1]",
+                "      Main 10.0.0.4 [1]",
+                "        Dir [1]",
+                "          svr10 (10) inst 1",
+                "  World 2 [3]",
+                "    Zone 10 [1]",
+                "      DB 10.0.0.2 [1]",
+                "        DB [1]",
+                "          svr202 (202) inst 1",
+                "    Zone 70 [2]",
+                "      Main 10.0.0.4 [2]",
+                "        Game [2]",
+                "          svr201 (201) inst 1-2",
+            ]
+        );
+        assert_eq!(topology.parent(4), Some(3));
+        assert_eq!(topology.parent(0), None);
+    }
+
+    #[test]
+    fn test_filter_topology() {
+        let rows = vec![
+            row("2", "70", "Main", "Game", 201, 1),
+            row("2", "71", "DB", "DB", 202, 1),
+        ];
+        let topology = Topology::build(&rows);
+        assert_eq!(
+            labels(&topology, &topology.visible("db 10")),
+            vec![
+                "Cluster [2]",
+                "  World 2 [2]",
+                "    Zone 71 [1]",
+                "      DB 10.0.0.2 [1]",
+                "        DB [1]",
+                "          svr202 (202) inst 1",
+            ]
+        );
+        assert!(topology.visible("nothing").is_empty());
+    }
+}
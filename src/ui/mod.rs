@@ -1,3 +1,53 @@
 pub mod stateful_paragraph;
 pub mod reflow;
-pub mod scrollbar;
\ No newline at end of file
+pub mod scrollbar;
+
+use ratatui::layout::Rect;
+
+/// 弹出框居中放在 frame 里, 终端太小时缩到 frame 的大小
+pub fn centered_rect(size: (u16, u16), frame: Rect) -> Rect {
+    Rect::new(
+        frame.x + frame.width.saturating_sub(size.0) / 2,
+        frame.y + frame.height.saturating_sub(size.1) / 2,
+        size.0.min(frame.width),
+        size.1.min(frame.height),
+    )
+}
+
+/// 列表选中下一项, 到末尾后回到第一项
+pub fn wrap_next(selected: Option<usize>, count: usize) -> Option<usize> {
+    if count == 0 {
+        return None;
+    }
+    Some(selected.map_or(0, |i| (i + 1) % count))
+}
+
+/// 列表选中上一项, 到第一项后回到末尾
+pub fn wrap_previous(selected: Option<usize>, count: usize) -> Option<usize> {
+    if count == 0 {
+        return None;
+    }
+    Some(selected.map_or(0, |i| (i + count - 1) % count))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_centered_rect() {
+        let frame = Rect::new(0, 0, 120, 40);
+        assert_eq!(centered_rect((80, 20), frame), Rect::new(20, 10, 80, 20));
+        let small = Rect::new(0, 0, 50, 10);
+        assert_eq!(centered_rect((80, 20), small), Rect::new(0, 0, 50, 10));
+    }
+
+    #[test]
+    fn test_wrap() {
+        assert_eq!(wrap_next(None, 3), Some(0));
+        assert_eq!(wrap_next(Some(2), 3), Some(0));
+        assert_eq!(wrap_previous(Some(0), 3), Some(2));
+        assert_eq!(wrap_previous(Some(2), 3), Some(1));
+        assert_eq!(wrap_next(Some(0), 0), None);
+    }
+}
@@ -3,7 +3,7 @@ use ratatui::{
 	buffer::Buffer,
 	layout::{Alignment, Rect},
 	style::Style,
-	text::{StyledGrapheme, Text},
+	text::{Line, StyledGrapheme, Text},
 	widgets::{Block, StatefulWidget, Widget, Wrap},
 };
 use std::iter;
@@ -39,6 +39,12 @@ pub struct StatefulParagraph<'a> {
 	text: Text<'a>,
 	/// Alignment of the text
 	alignment: Alignment,
+	/// Style of the line number gutter, no gutter if `None`
+	line_numbers: Option<Style>,
+	/// Number of the first line, when the text is a window of a larger file
+	first_line: usize,
+	/// Line count of the whole file, decides the gutter width
+	total_lines: usize,
 }
 
 #[derive(Debug, Default, Clone, Copy)]
@@ -55,6 +61,10 @@ pub struct ParagraphState {
 	lines: u16,
 	/// last visible height
 	height: u16,
+	/// width of the text area without block and gutter
+	width: u16,
+	/// width of the longest line, limits horizontal scrolling
+	max_width: u16,
 }
 
 impl ParagraphState {
@@ -73,6 +83,43 @@ impl ParagraphState {
 	pub fn set_scroll(&mut self, scroll: ScrollPos) {
 		self.scroll = scroll;
 	}
+
+	pub const fn width(self) -> u16 {
+		self.width
+	}
+
+	pub const fn max_width(self) -> u16 {
+		self.max_width
+	}
+
+}
+
+/// Width of the line number gutter for a file with `total_lines` lines
+pub fn gutter_width(total_lines: usize) -> u16 {
+	let digits = total_lines.max(1).ilog10() + 1;
+	// one column of padding after the number
+	Cast::<u16>::cast(digits) + 1
+}
+
+/// Number of rows `line` takes in `width` columns
+pub fn line_rows(line: &Line, width: u16, wrap: bool) -> usize {
+	if !wrap {
+		return 1;
+	}
+	let mut styled = line
+		.spans
+		.iter()
+		.flat_map(|span| span.styled_graphemes(Style::default()))
+		.chain(iter::once(StyledGrapheme {
+			symbol: "\n",
+			style: Style::default(),
+		}));
+	let mut composer = WordWrapper::new(&mut styled, width, false);
+	let mut rows = 0;
+	while composer.next_line().is_some() {
+		rows += 1;
+	}
+	rows.max(1)
 }
 
 impl<'a> StatefulParagraph<'a> {
@@ -86,6 +133,9 @@ impl<'a> StatefulParagraph<'a> {
 			wrap: None,
 			text: text.into(),
 			alignment: Alignment::Left,
+			line_numbers: None,
+			first_line: 0,
+			total_lines: 0,
 		}
 	}
 
@@ -99,6 +149,65 @@ impl<'a> StatefulParagraph<'a> {
 		self
 	}
 
+	pub const fn line_numbers(mut self, style: Style) -> Self {
+		self.line_numbers = Some(style);
+		self
+	}
+
+	/// The text is the window starting at line `first` of a file with `total` lines
+	pub const fn window(mut self, first: usize, total: usize) -> Self {
+		self.first_line = first;
+		self.total_lines = total;
+		self
+	}
+
+	fn gutter_width(&self) -> u16 {
+		self.line_numbers.map_or(0, |_| {
+			gutter_width(self.total_lines.max(self.first_line + self.text.lines.len()))
+		})
+	}
+
+	/// Composes a single unwrapped line, calling `f` for every resulting row
+	fn compose_line<'b, F>(
+		&self,
+		line: &'b Line<'b>,
+		width: u16,
+		horizontal_offset: u16,
+		mut f: F,
+	) where
+		F: FnMut(&[StyledGrapheme<'b>], u16),
+	{
+		let style = self.style;
+		let mut styled = line
+			.spans
+			.iter()
+			.flat_map(|span| span.styled_graphemes(style))
+			// Required given the way composers work but might be refactored out if we change
+			// composers to operate on lines instead of a stream of graphemes.
+			.chain(iter::once(StyledGrapheme {
+				symbol: "\n",
+				style,
+			}));
+
+		let mut line_composer: Box<dyn LineComposer> =
+			if let Some(Wrap { trim }) = self.wrap {
+				Box::new(WordWrapper::new(&mut styled, width, trim))
+			} else {
+				let mut line_composer =
+					Box::new(LineTruncator::new(&mut styled, width));
+				if self.alignment == Alignment::Left {
+					line_composer
+						.set_horizontal_offset(horizontal_offset);
+				}
+				line_composer
+			};
+		while let Some((current_line, current_line_width)) =
+			line_composer.next_line()
+		{
+			f(current_line, current_line_width);
+		}
+	}
+
 	// pub const fn style(mut self, style: Style) -> Self {
 	// 	self.style = style;
 	// 	self
@@ -130,71 +239,140 @@ impl<'a> StatefulWidget for StatefulParagraph<'a> {
 			return;
 		}
 
-		let style = self.style;
-		let mut styled = self.text.lines.iter().flat_map(|line| {
-			line.spans
-				.iter()
-				.flat_map(|span| span.styled_graphemes(style))
-				// Required given the way composers work but might be refactored out if we change
-				// composers to operate on lines instead of a stream of graphemes.
-				.chain(iter::once(StyledGrapheme {
-					symbol: "\n",
-					style: self.style,
-				}))
-		});
+		let gutter = self.gutter_width();
+		let width = text_area.width.saturating_sub(gutter);
 
-		let mut line_composer: Box<dyn LineComposer> =
-			if let Some(Wrap { trim }) = self.wrap {
-				Box::new(WordWrapper::new(
-					&mut styled,
-					text_area.width,
-					trim,
-				))
-			} else {
-				let mut line_composer = Box::new(LineTruncator::new(
-					&mut styled,
-					text_area.width,
-				));
-				if self.alignment == Alignment::Left {
-					line_composer
-						.set_horizontal_offset(state.scroll.x);
-				}
-				line_composer
-			};
-		let mut y = 0;
-		let mut end_reached = false;
-		while let Some((current_line, current_line_width)) =
-			line_composer.next_line()
-		{
-			if !end_reached && y >= state.scroll.y {
-				let mut x = get_line_offset(
-					current_line_width,
-					text_area.width,
-					self.alignment,
-				);
-				for StyledGrapheme { symbol, style } in current_line {
-					buf.get_mut(
-						text_area.left() + x,
-						text_area.top() + y - state.scroll.y,
-					)
-					.set_symbol(if symbol.is_empty() {
-						// If the symbol is empty, the last char which rendered last time will
-						// leave on the line. It's a quick fix.
-						" "
-					} else {
-						symbol
-					})
-					.set_style(*style);
-					x += Cast::<u16>::cast(symbol.width());
-				}
-			}
-			y += 1;
-			if y >= text_area.height + state.scroll.y {
-				end_reached = true;
-			}
+		let mut y: u16 = 0;
+		for (number, line) in self.text.lines.iter().enumerate() {
+			let mut first_row = true;
+			self.compose_line(
+				line,
+				width,
+				state.scroll.x,
+				|current_line, current_line_width| {
+					let visible = y >= state.scroll.y
+						&& y < state.scroll.y.saturating_add(text_area.height);
+					if visible {
+						let row = text_area.top() + y - state.scroll.y;
+						if let (Some(gutter_style), true) =
+							(self.line_numbers, first_row)
+						{
+							let label = format!(
+								"{:>width$}",
+								self.first_line + number + 1,
+								width = usize::from(gutter.saturating_sub(1))
+							);
+							buf.set_string(
+								text_area.left(),
+								row,
+								label,
+								gutter_style,
+							);
+						}
+						let mut x = gutter
+							+ get_line_offset(
+								current_line_width,
+								width,
+								self.alignment,
+							);
+						for StyledGrapheme { symbol, style } in current_line
+						{
+							buf.get_mut(text_area.left() + x, row)
+								.set_symbol(if symbol.is_empty() {
+									// If the symbol is empty, the last char which rendered last time will
+									// leave on the line. It's a quick fix.
+									" "
+								} else {
+									symbol
+								})
+								.set_style(*style);
+							x += Cast::<u16>::cast(symbol.width());
+						}
+					}
+					first_row = false;
+					y = y.saturating_add(1);
+				},
+			);
 		}
 
 		state.lines = y;
 		state.height = area.height;
+		state.width = width;
+		state.max_width = self
+			.text
+			.lines
+			.iter()
+			.map(|line| Cast::<u16>::cast(line.width().min(usize::from(u16::MAX))))
+			.max()
+			.unwrap_or_default();
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn render(
+		paragraph: StatefulParagraph,
+		state: &mut ParagraphState,
+	) -> Vec<String> {
+		let area = Rect::new(0, 0, 8, 3);
+		let mut buf = Buffer::empty(area);
+		paragraph.render(area, &mut buf, state);
+		(0..area.height)
+			.map(|y| {
+				(0..area.width)
+					.map(|x| buf.get(x, y).symbol().to_string())
+					.collect::<String>()
+			})
+			.collect()
+	}
+
+	#[test]
+	fn test_line_numbers() {
+		let text = "aaaa bbbb\ncc\ndd";
+		let mut state = ParagraphState::default();
+		// only the first row of a wrapped line gets a number
+		let paragraph = StatefulParagraph::new(text)
+			.line_numbers(Style::default())
+			.wrap(Wrap { trim: false });
+		assert_eq!(
+			render(paragraph, &mut state),
+			vec!["1 aaaa  ", "  bbbb  ", "2 cc    "]
+		);
+		assert_eq!(state.lines(), 4);
+		assert_eq!(state.width(), 6);
+
+		// a window of a larger file keeps the file's line numbers
+		let paragraph = StatefulParagraph::new("dd\nee")
+			.line_numbers(Style::default())
+			.window(98, 100);
+		assert_eq!(
+			render(paragraph, &mut state),
+			vec![" 99 dd  ", "100 ee  ", "        "]
+		);
+	}
+
+	#[test]
+	fn test_line_rows() {
+		let line = Line::raw("aaaa bbbb cc");
+		assert_eq!(line_rows(&line, 6, true), 3);
+		assert_eq!(line_rows(&line, 6, false), 1);
+		assert_eq!(line_rows(&Line::raw(""), 6, true), 1);
+		assert_eq!(gutter_width(9), 2);
+		assert_eq!(gutter_width(100), 4);
+	}
+
+	#[test]
+	fn test_horizontal_offset() {
+		let mut state = ParagraphState::default();
+		state.set_scroll(ScrollPos { x: 3, y: 0 });
+		let paragraph =
+			StatefulParagraph::new("0123456789\nab").line_numbers(Style::default());
+		assert_eq!(
+			render(paragraph, &mut state),
+			vec!["1 345678", "2       ", "        "]
+		);
+		assert_eq!(state.max_width(), 10);
 	}
 }
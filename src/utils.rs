@@ -1,9 +1,10 @@
 use anyhow::anyhow;
 use clap::Parser;
+use hashbrown::HashMap;
 use core::panic;
 use quick_xml::de::from_str;
 use serde::{Deserialize, Serialize};
-use sqlx::SqlitePool;
+use sqlx::{SqliteConnection, SqlitePool};
 use std::path::{Path, PathBuf};
 use tracing::debug;
 use unicode_segmentation::UnicodeSegmentation;
@@ -11,7 +12,7 @@ use unicode_width::UnicodeWidthStr;
 
 
 use crate::{
-    database::{get_host_id, insert_deploy, insert_hosts, insert_procs, SQLX_DATABASE_URL},
+    database::{clear_tables, get_host_id, insert_deploy, insert_hosts, insert_procs, SQLX_DATABASE_URL},
     description::{deploy::DeployTcmCenter, host::HostTcmCenter, proc::ProcTcmCenter},
     tools::{
         deploy::DeployInfo,
@@ -20,7 +21,7 @@ use crate::{
     },
     HOST_HASHMAP,
 };
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum TcmCommand {
     ListProc,
     Start,
@@ -28,14 +29,15 @@ pub enum TcmCommand {
     CheckBe,
     CheckNo,
 }
-impl Into<String> for TcmCommand {
-    fn into(self) -> String {
+
+impl std::fmt::Display for TcmCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            TcmCommand::ListProc => "listProc".to_string(),
-            TcmCommand::Start => "start".to_string(),
-            TcmCommand::Stop => "stop".to_string(),
-            TcmCommand::CheckBe => "checkbe".to_string(),
-            TcmCommand::CheckNo => "checkno".to_string(),
+            TcmCommand::ListProc => write!(f, "listProc"),
+            TcmCommand::Start => write!(f, "start"),
+            TcmCommand::Stop => write!(f, "stop"),
+            TcmCommand::CheckBe => write!(f, "checkbe"),
+            TcmCommand::CheckNo => write!(f, "checkno"),
         }
     }
 }
@@ -90,6 +92,9 @@ pub struct Args {
     pub config_path: PathBuf,
     #[arg(short= 'd', default_value= "false")]
     pub debug: bool,
+    /// 用户配置文件, 默认读取 $XDG_CONFIG_HOME/tcmhelper/config.toml
+    #[arg(short = 'u', long)]
+    pub user_config: Option<PathBuf>,
 }
 
 pub fn return_parsed_center(
@@ -124,19 +129,23 @@ pub fn return_parsed_center(
     }
 }
 
-pub fn return_hosts(path: &PathBuf) -> anyhow::Result<Vec<HostInfo>> {
+pub fn return_hosts(path: &PathBuf, host_map: &HashMap<String, String>) -> anyhow::Result<Vec<HostInfo>> {
     let center: DeployTcmCenter = return_parsed_center(ParseType::DEPLOY, path)?.into();
-    Ok(collect_host_info(center))
+    Ok(collect_host_info(center, host_map))
 }
 pub fn return_procs(path: &PathBuf) -> anyhow::Result<Vec<ProcInfo>> {
     let center: ProcTcmCenter = return_parsed_center(ParseType::PROC, path)?.into();
-    let procs = collect_proc_info(center);
+    let procs = collect_proc_info(center)?;
     Ok(procs)
 }
 
-pub async fn return_deploy(db: &SqlitePool, path: &PathBuf) -> anyhow::Result<Vec<DeployInfo>> {
+pub async fn return_deploy(
+    conn: &mut SqliteConnection,
+    path: &PathBuf,
+    host_map: &HashMap<String, String>,
+) -> anyhow::Result<Vec<DeployInfo>> {
     let center: DeployTcmCenter = return_parsed_center(ParseType::DEPLOY, path)?.into();
-    match collect_deploy_info(center, db).await {
+    match collect_deploy_info(center, conn, host_map).await {
         Ok(result) => Ok(result),
         Err(e) => {
             return Err(anyhow!("Parse config xml failed! -> [{}]", e));
@@ -146,7 +155,8 @@ pub async fn return_deploy(db: &SqlitePool, path: &PathBuf) -> anyhow::Result<Ve
 
 pub async fn collect_deploy_info(
     center: DeployTcmCenter,
-    db: &SqlitePool,
+    conn: &mut SqliteConnection,
+    host_map: &HashMap<String, String>,
 ) -> Result<Vec<DeployInfo>, sqlx::Error> {
     let mut deploy_info = Vec::new();
     for deploy in center.cluster_deploy.deploy_groups {
@@ -156,23 +166,23 @@ pub async fn collect_deploy_info(
 
         match deploy.host {
             Some(host) => {
-                inner_ip = HOST_HASHMAP.get(&host).unwrap().clone();
+                inner_ip = host_map.get(&host).unwrap().clone();
             }
             None => {
                 inner_ip = "localhost".to_string();
             }
         }
         let inst_id = deploy.inst_id.unwrap_or_else(|| 0);
-        let host_id = get_host_id(db, &inner_ip, &world_id, &zone_id).await?;
+        let host_id = get_host_id(conn, &inner_ip, &world_id, &zone_id).await?;
         deploy_info.push(DeployInfo::new(host_id, deploy.group, inst_id));
     }
     for deploy in center.cluster_deploy.worlds {
         let world_id = deploy.id;
         for zone in deploy.zone_list {
             for deploy in zone.deploy_groups {
-                let inner_ip = HOST_HASHMAP.get(&deploy.host).unwrap().clone();
+                let inner_ip = host_map.get(&deploy.host).unwrap().clone();
                 let inst_id = deploy.inst_id.unwrap_or_else(|| 1);
-                let host_id = get_host_id(db, &inner_ip, &world_id, &zone.id).await?;
+                let host_id = get_host_id(conn, &inner_ip, &world_id, &zone.id).await?;
                 deploy_info.push(DeployInfo::new(host_id, deploy.group, inst_id));
             }
         }
@@ -190,16 +200,74 @@ pub fn drop_app() {
 }
 
 pub async fn init_data(db: &SqlitePool, path: PathBuf) -> anyhow::Result<()> {
-    let hosts = return_hosts(&path)?;
-    insert_hosts(db, &hosts).await?;
-    let procs = return_procs(&path)?;
-    insert_procs(db, &procs).await?;
-    let deploys = return_deploy(db, &path).await?;
-    insert_deploy(db, &deploys).await?;
+    let host_map = HOST_HASHMAP.read().unwrap().clone();
+    let mut tx = db.begin().await?;
+    import_data(&mut tx, &path, &host_map).await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+/// 导入 XML 中的数据, 调用方在同一个事务里执行, 出错时整体回滚
+async fn import_data(
+    conn: &mut SqliteConnection,
+    path: &PathBuf,
+    host_map: &HashMap<String, String>,
+) -> anyhow::Result<()> {
+    let hosts = return_hosts(path, host_map)?;
+    insert_hosts(conn, &hosts).await?;
+    let procs = return_procs(path)?;
+    insert_procs(conn, &procs).await?;
+    let deploys = return_deploy(conn, path, host_map).await?;
+    insert_deploy(conn, &deploys).await?;
     debug!("insert data done");
     Ok(())
 }
 
+/// host.xml 中的主机名到内网 IP, 另外加上本机 TcmHost
+pub fn load_host_map(path: &Path) -> anyhow::Result<HashMap<String, String>> {
+    let xml = path.join("host.xml");
+    let host_xml = std::fs::read_to_string(&xml)
+        .map_err(|e| anyhow!("Read config file -> {} failed: {}", xml.display(), e))?;
+    let host_xml_content: HostTcmCenter = from_str(&host_xml)?;
+    let mut m = HashMap::new();
+    for host_info in host_xml_content.host_tab.hosts {
+        // TODO: 这里 IP 应该是唯一的 对应多个 Name
+        m.insert(host_info.name, host_info.inner_ip);
+    }
+    m.insert("TcmHost".to_string(), "127.0.0.1".to_string());
+    Ok(m)
+}
+
+/// 配置文件修改后重新导入. 先解析所有 XML 并检查部署用到的主机, 清空和导入在同一个
+/// 事务里执行, 提交后才替换主机表. 有错误时数据库和主机表都保留原来的数据
+pub async fn reimport_data(db: &SqlitePool, path: PathBuf) -> anyhow::Result<()> {
+    let hosts = load_host_map(&path)?;
+    let deploy: DeployTcmCenter = return_parsed_center(ParseType::DEPLOY, &path)?.into();
+    let cluster_hosts = deploy
+        .cluster_deploy
+        .deploy_groups
+        .iter()
+        .filter_map(|group| group.host.as_ref());
+    let zone_hosts = deploy
+        .cluster_deploy
+        .worlds
+        .iter()
+        .flat_map(|world| &world.zone_list)
+        .flat_map(|zone| &zone.deploy_groups)
+        .map(|group| &group.host);
+    if let Some(host) = cluster_hosts.chain(zone_hosts).find(|host| !hosts.contains_key(*host)) {
+        return Err(anyhow!("procdeploy.xml 中的主机 {} 不在 host.xml 中", host));
+    }
+    return_parsed_center(ParseType::PROC, &path)?;
+
+    let mut tx = db.begin().await?;
+    clear_tables(&mut tx).await?;
+    import_data(&mut tx, &path, &hosts).await?;
+    tx.commit().await?;
+    *HOST_HASHMAP.write().unwrap() = hosts;
+    Ok(())
+}
+
 pub fn tabs_to_spaces(input: String) -> String {
     if input.contains('\t') {
         input.replace('\t', "  ")
@@ -235,8 +303,85 @@ pub fn file_content(path: &std::path::Path) -> anyhow::Result<String> {
     Ok(content)
 }
 
-fn is_binary_file<P: AsRef<Path>>(path: P) -> std::io::Result<bool> {
+pub fn is_binary_file<P: AsRef<Path>>(path: P) -> std::io::Result<bool> {
     let file_type = infer::get_from_path(path)?;
 
     Ok(file_type.map_or(false, |ftype| !ftype.mime_type().starts_with("text/")))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::{create_tables, select_all_deploys};
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    const HOST_XML: &str = r#"<TcmCenter><HostTab>
+<Host Name="Host_Main_70" InnerIP="127.0.0.1"/>
+<Host Name="Host_DB_70" InnerIP="10.0.0.2"/>
+</HostTab></TcmCenter>"#;
+    const PROC_XML: &str = r#"<TcmCenter>
+<cluster WorkPath="/tmp/tcmwork"><world Isolated="1"><zone Isolated="1">
+<Proc FuncName="gamesvr" FuncID="201" Flag="1" WorkPath="gamesvr"/>
+<Proc FuncName="dbsvr" FuncID="202" Flag="1" WorkPath="dbsvr"/>
+</zone></world></cluster>
+<ProcGroup Name="GameGroup" Layer="Zone"><Proc FuncName="gamesvr"/></ProcGroup>
+<ProcGroup Name="DBGroup" Layer="Zone"><Proc FuncName="dbsvr"/></ProcGroup>
+</TcmCenter>"#;
+
+    fn procdeploy(groups: &str) -> String {
+        format!(
+            r#"<TcmCenter><ClusterDeploy>
+<DeloyGroup Group="DBGroup" Host="Host_DB_70" InstID="9"/>
+<world ID="2"><zone ID="70">{}</zone></world>
+</ClusterDeploy></TcmCenter>"#,
+            groups
+        )
+    }
+
+    #[tokio::test]
+    async fn test_reimport_keeps_data_on_error() {
+        let dir = std::env::temp_dir().join(format!("tcmhelper-reimport-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("host.xml"), HOST_XML).unwrap();
+        std::fs::write(dir.join("proc.xml"), PROC_XML).unwrap();
+        std::fs::write(
+            dir.join("procdeploy.xml"),
+            procdeploy(
+                r#"<DeloyGroup Group="GameGroup" Host="Host_Main_70" InstID="1"/>
+<DeloyGroup Group="DBGroup" Host="Host_DB_70" InstID="1"/>"#,
+            ),
+        )
+        .unwrap();
+
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .idle_timeout(None)
+            .max_lifetime(None)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        create_tables(&pool).await.unwrap();
+        let host_map = load_host_map(&dir).unwrap();
+        let mut tx = pool.begin().await.unwrap();
+        import_data(&mut tx, &dir, &host_map).await.unwrap();
+        tx.commit().await.unwrap();
+        let before = select_all_deploys(&pool).await.unwrap();
+        assert_eq!(before.len(), 3);
+
+        // 重复的部署违反唯一约束, 清空表之后才在插入时失败
+        std::fs::write(
+            dir.join("procdeploy.xml"),
+            procdeploy(
+                r#"<DeloyGroup Group="GameGroup" Host="Host_Main_70" InstID="1"/>
+<DeloyGroup Group="GameGroup" Host="Host_Main_70" InstID="1"/>"#,
+            ),
+        )
+        .unwrap();
+        let err = reimport_data(&pool, dir.clone()).await.unwrap_err();
+        assert!(err.to_string().contains("UNIQUE"), "{:#}", err);
+        assert_eq!(select_all_deploys(&pool).await.unwrap(), before);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}